@@ -68,6 +68,17 @@ fn get_kv(scope: &str, key: &str, kv: &serde_json::Value) -> Result<String, Heca
     }
 }
 
+///
+/// Return the value of a key added after the auth config format was first released,
+/// falling back to a default rule when it is missing so that existing configs still load
+///
+fn get_kv_or(scope: &str, key: &str, kv: &serde_json::Value, default: &str) -> Result<String, HecateError> {
+    match kv.get(key) {
+        None => Ok(String::from(default)),
+        Some(_) => get_kv(scope, key, kv)
+    }
+}
+
 pub trait AuthModule {
     fn default() -> Self;
     fn parse(value: Option<&serde_json::Value>) -> Result<Box<Self>, HecateError>;
@@ -298,7 +309,9 @@ pub struct AuthMVT {
     pub get: String,
     pub delete: String,
     pub regen: String,
-    pub meta: String
+    pub meta: String,
+    pub seed: String,
+    pub export: String
 }
 
 impl AuthModule for AuthMVT {
@@ -307,7 +320,9 @@ impl AuthModule for AuthMVT {
             get: String::from("public"),
             delete: String::from("admin"),
             regen: String::from("user"),
-            meta: String::from("public")
+            meta: String::from("public"),
+            seed: String::from("admin"),
+            export: String::from("user")
         }
     }
 
@@ -318,7 +333,9 @@ impl AuthModule for AuthMVT {
                     get: get_kv("mvt", "get", value)?,
                     delete: get_kv("mvt", "delete", value)?,
                     regen: get_kv("mvt", "regen", value)?,
-                    meta: get_kv("mvt", "meta", value)?
+                    meta: get_kv("mvt", "meta", value)?,
+                    seed: get_kv_or("mvt", "seed", value, &AuthMVT::default().seed)?,
+                    export: get_kv_or("mvt", "export", value, &AuthMVT::default().export)?
                 }))
             },
             None => {
@@ -326,7 +343,9 @@ impl AuthModule for AuthMVT {
                     get: String::from("disabled"),
                     delete: String::from("disabled"),
                     regen: String::from("disabled"),
-                    meta: String::from("disabled")
+                    meta: String::from("disabled"),
+                    seed: String::from("disabled"),
+                    export: String::from("disabled")
                 }))
             }
         }
@@ -337,6 +356,8 @@ impl AuthModule for AuthMVT {
         is_all("mvt::regen", &self.regen)?;
         is_all("mvt::delete", &self.regen)?;
         is_all("mvt::meta", &self.meta)?;
+        is_auth("mvt::seed", &self.seed)?;
+        is_auth("mvt::export", &self.export)?;
 
         Ok(true)
     }
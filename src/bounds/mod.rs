@@ -1,5 +1,6 @@
 use crate::err::HecateError;
 use crate::stream::PGStream;
+use geo::prelude::*;
 
 pub fn set(conn: &impl postgres::GenericConnection, name: &str, feat: &serde_json::Value) -> Result<bool, HecateError> {
     match conn.execute("
@@ -133,6 +134,46 @@ pub fn meta(conn: &impl postgres::GenericConnection, name: String) -> Result<ser
     }
 }
 
+///
+/// Return whether a bounds with the given name exists
+///
+pub fn exists(conn: &impl postgres::GenericConnection, name: &str) -> Result<bool, HecateError> {
+    match conn.query("
+        SELECT 1 FROM bounds WHERE name = $1
+    ", &[ &name ]) {
+        Ok(rows) => Ok(!rows.is_empty()),
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
+///
+/// Return the geometry of a given bounds
+///
+pub fn geom(conn: &impl postgres::GenericConnection, name: &str) -> Result<geo::Geometry<f64>, HecateError> {
+    match conn.query("
+        SELECT
+            geom
+        FROM
+            bounds
+        WHERE
+            name = $1
+    ", &[ &name ]) {
+        Ok(rows) => {
+            if rows.len() != 1 {
+                return Err(HecateError::new(404, String::from("bound not found"), None));
+            }
+
+            let geom: postgis::ewkb::GeometryT<postgis::ewkb::Point> = rows.get(0).get(0);
+
+            match FromPostgis::from_postgis(&geom) {
+                Some(geom) => Ok(geom),
+                None => Err(HecateError::new(500, String::from("Invalid bounds geometry"), None))
+            }
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
 pub fn stats_json(conn: &impl postgres::GenericConnection, bounds: String) -> Result<serde_json::Value, HecateError> {
     match conn.query("
         SELECT
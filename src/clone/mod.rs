@@ -1,5 +1,6 @@
 use crate::stream::PGStream;
 use crate::err::HecateError;
+use crate::delta;
 
 pub fn get(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>) -> Result<PGStream, HecateError> {
     match PGStream::new(conn, String::from("next_clone"), String::from(r#"
@@ -23,6 +24,50 @@ pub fn get(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager
     }
 }
 
+///
+/// Stream every feature as it existed at a given point in the edit history,
+/// excluding features that had been deleted by that point
+///
+pub fn get_at(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>, at: &delta::At) -> Result<PGStream, HecateError> {
+    let (at_delta, at_time): (Option<i64>, Option<chrono::NaiveDateTime>) = match at {
+        delta::At::Delta(delta) => (Some(*delta), None),
+        delta::At::Timestamp(time) => (None, Some(*time))
+    };
+
+    PGStream::new(conn, String::from("next_clone_at"), String::from(r#"
+        DECLARE next_clone_at CURSOR FOR
+            SELECT
+                row_to_json(t)::TEXT
+            FROM (
+                SELECT
+                    h.id AS id,
+                    h.key AS key,
+                    'Feature' AS type,
+                    h.version AS version,
+                    ST_AsGeoJSON(h.geom)::JSON AS geometry,
+                    h.props AS properties
+                FROM (
+                    SELECT DISTINCT ON (geo_history.id)
+                        geo_history.*
+                    FROM
+                        geo_history,
+                        deltas
+                    WHERE
+                        geo_history.delta = deltas.id
+                        AND (
+                            ($1::BIGINT IS NOT NULL AND deltas.id <= $1::BIGINT)
+                            OR ($2::TIMESTAMP IS NOT NULL AND deltas.created <= $2::TIMESTAMP)
+                        )
+                    ORDER BY
+                        geo_history.id,
+                        geo_history.version DESC
+                ) h
+                WHERE
+                    h.action != 'delete'
+            ) t
+    "#), &[&at_delta, &at_time])
+}
+
 pub fn query(read_conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>, query: &str, limit: &Option<i64>) -> Result<PGStream, HecateError> {
     Ok(PGStream::new(read_conn, String::from("next_clone_query"), format!(r#"
         DECLARE next_clone_query CURSOR FOR
@@ -2,8 +2,19 @@ use postgres;
 use geo::prelude::*;
 use std::collections::HashMap;
 use crate::err::HecateError;
+use crate::feature;
 use serde_json::Value;
 
+///
+/// A point in the edit history of the database, either immediately after
+/// a given delta id or at a given timestamp
+///
+#[derive(PartialEq, Debug, Clone)]
+pub enum At {
+    Delta(i64),
+    Timestamp(chrono::NaiveDateTime)
+}
+
 pub struct Delta {
     pub id: Option<i64>,
     pub uid: i64,
@@ -37,6 +48,45 @@ impl Delta {
     }
 }
 
+///
+/// Optional filters that can be applied when listing deltas
+///
+#[derive(Default, Debug)]
+pub struct ListFilter {
+    pub uid: Option<i64>,
+    pub bbox: Option<Vec<f64>>,
+    pub bounds: Option<String>,
+    pub feature: Option<i64>,
+    pub message: Option<String>
+}
+
+///
+/// SQL conditions for a ListFilter, using params $1 - $5
+///
+static LIST_FILTER: &str = "
+    AND ($1::BIGINT IS NULL OR deltas.uid = $1::BIGINT)
+    AND ($2::BIGINT IS NULL OR $2::BIGINT = ANY(deltas.affected))
+    AND ($3::TEXT IS NULL OR strpos(lower(deltas.props->>'message'), lower($3::TEXT)) > 0)
+    AND (
+        ($4::FLOAT8[] IS NULL AND $5::TEXT IS NULL)
+        OR EXISTS (
+            SELECT 1
+            FROM geo_history h
+            WHERE
+                h.delta = deltas.id
+                AND ST_Intersects(
+                    COALESCE(h.geom, (
+                        SELECT p.geom FROM geo_history p WHERE p.id = h.id AND p.version = h.version - 1
+                    )),
+                    COALESCE(
+                        ST_MakeEnvelope(($4::FLOAT8[])[1], ($4::FLOAT8[])[2], ($4::FLOAT8[])[3], ($4::FLOAT8[])[4], 4326),
+                        (SELECT bounds.geom FROM bounds WHERE bounds.name = $5::TEXT)
+                    )
+                )
+        )
+    )
+";
+
 pub fn open(trans: &postgres::transaction::Transaction, props: &HashMap<String, Option<String>>, uid: i64) -> Result<i64, HecateError> {
     match trans.query("
         INSERT INTO deltas (id, created, props, uid) VALUES (
@@ -68,8 +118,8 @@ pub fn create(trans: &postgres::transaction::Transaction, fc: &geojson::FeatureC
     }
 }
 
-pub fn list_by_date(conn: &impl postgres::GenericConnection, start: Option<chrono::NaiveDateTime>, end: Option<chrono::NaiveDateTime>, limit: Option<i64>) -> Result<serde_json::Value, HecateError> {
-    match conn.query("
+pub fn list_by_date(conn: &impl postgres::GenericConnection, start: Option<chrono::NaiveDateTime>, end: Option<chrono::NaiveDateTime>, limit: Option<i64>, filter: &ListFilter) -> Result<serde_json::Value, HecateError> {
+    match conn.query(&*format!("
         SELECT COALESCE(array_to_json(Array_Agg(djson.delta)), '[]')::JSON
         FROM (
             SELECT row_to_json(d) as delta
@@ -86,24 +136,25 @@ pub fn list_by_date(conn: &impl postgres::GenericConnection, start: Option<chron
                 WHERE
                     deltas.uid = users.id
                     AND ((
-                        $1::TIMESTAMP IS NOT NULL
-                        AND $2::TIMESTAMP IS NOT NULL
-                        AND deltas.created < $1::TIMESTAMP
-                        AND deltas.created > $2::TIMESTAMP
+                        $6::TIMESTAMP IS NOT NULL
+                        AND $7::TIMESTAMP IS NOT NULL
+                        AND deltas.created < $6::TIMESTAMP
+                        AND deltas.created > $7::TIMESTAMP
                     ) OR (
-                        $1::TIMESTAMP IS NOT NULL
-                        AND $2::TIMESTAMP IS NULL
-                        AND deltas.created < $1::TIMESTAMP
+                        $6::TIMESTAMP IS NOT NULL
+                        AND $7::TIMESTAMP IS NULL
+                        AND deltas.created < $6::TIMESTAMP
                     ) OR (
-                        $1::TIMESTAMP IS NULL
-                        AND $2::TIMESTAMP IS NOT NULL
-                        AND deltas.created > $2::TIMESTAMP
+                        $6::TIMESTAMP IS NULL
+                        AND $7::TIMESTAMP IS NOT NULL
+                        AND deltas.created > $7::TIMESTAMP
                     ))
+                    {}
                 ORDER BY id DESC
-                LIMIT $3
+                LIMIT $8
             ) d
         ) djson;
-    ", &[&start, &end, &limit]) {
+    ", LIST_FILTER), &[&filter.uid, &filter.feature, &filter.message, &filter.bbox, &filter.bounds, &start, &end, &limit]) {
         Err(err) => Err(HecateError::from_db(err)),
         Ok(res) => {
             let d_json: serde_json::Value = res.get(0).get(0);
@@ -112,7 +163,7 @@ pub fn list_by_date(conn: &impl postgres::GenericConnection, start: Option<chron
     }
 }
 
-pub fn list_by_offset(conn: &impl postgres::GenericConnection, offset: Option<i64>, limit: Option<i64>) -> Result<serde_json::Value, HecateError> {
+pub fn list_by_offset(conn: &impl postgres::GenericConnection, offset: Option<i64>, limit: Option<i64>, filter: &ListFilter) -> Result<serde_json::Value, HecateError> {
     let offset = match offset {
         None => String::from("Infinity"),
         Some(offset) => offset.to_string()
@@ -129,7 +180,7 @@ pub fn list_by_offset(conn: &impl postgres::GenericConnection, offset: Option<i6
         }
     };
 
-    match conn.query("
+    match conn.query(&*format!("
         SELECT COALESCE(array_to_json(Array_Agg(djson.delta)), '[]')::JSON
         FROM (
             SELECT row_to_json(d) as delta
@@ -145,12 +196,61 @@ pub fn list_by_offset(conn: &impl postgres::GenericConnection, offset: Option<i6
                     users
                 WHERE
                     deltas.uid = users.id
-                    AND deltas.id < $1::TEXT::FLOAT8
+                    AND deltas.id < $6::TEXT::FLOAT8
+                    {}
+                ORDER BY id DESC
+                LIMIT $7
+            ) d
+        ) djson;
+    ", LIST_FILTER), &[&filter.uid, &filter.feature, &filter.message, &filter.bbox, &filter.bounds, &offset, &limit]) {
+        Err(err) => Err(HecateError::from_db(err)),
+        Ok(res) => {
+            let d_json: serde_json::Value = res.get(0).get(0);
+            Ok(d_json)
+        }
+    }
+}
+
+///
+/// Return the 100 most recent deltas created within a time range, or a single delta by id,
+/// along with the extent of & number of features affected by each
+///
+pub fn changesets(conn: &impl postgres::GenericConnection, id: Option<i64>, start: Option<chrono::NaiveDateTime>, end: Option<chrono::NaiveDateTime>, filter: &ListFilter) -> Result<serde_json::Value, HecateError> {
+    match conn.query(&*format!("
+        SELECT COALESCE(array_to_json(Array_Agg(djson.delta)), '[]')::JSON
+        FROM (
+            SELECT row_to_json(d) as delta
+            FROM (
+                SELECT
+                    deltas.id,
+                    deltas.uid,
+                    users.username,
+                    to_char(deltas.created, 'YYYY-MM-DD\"T\"HH24:MI:SS\"Z\"') AS created,
+                    deltas.props,
+                    NOT COALESCE(deltas.finalized, false) AS open,
+                    COALESCE(cardinality(deltas.affected), 0) AS changes,
+                    (
+                        SELECT
+                            CASE WHEN ST_Extent(geom) IS NULL THEN NULL
+                            ELSE ARRAY[ST_XMin(ST_Extent(geom)), ST_YMin(ST_Extent(geom)), ST_XMax(ST_Extent(geom)), ST_YMax(ST_Extent(geom))]
+                            END
+                        FROM geo_history
+                        WHERE geo_history.delta = deltas.id
+                    ) AS bbox
+                FROM
+                    deltas,
+                    users
+                WHERE
+                    deltas.uid = users.id
+                    AND ($6::BIGINT IS NULL OR deltas.id = $6::BIGINT)
+                    AND ($7::TIMESTAMP IS NULL OR deltas.created >= $7::TIMESTAMP)
+                    AND ($8::TIMESTAMP IS NULL OR deltas.created <= $8::TIMESTAMP)
+                    {}
                 ORDER BY id DESC
-                LIMIT $2
+                LIMIT 100
             ) d
         ) djson;
-    ", &[&offset, &limit]) {
+    ", LIST_FILTER), &[&filter.uid, &filter.feature, &filter.message, &filter.bbox, &filter.bounds, &id, &start, &end]) {
         Err(err) => Err(HecateError::from_db(err)),
         Ok(res) => {
             let d_json: serde_json::Value = res.get(0).get(0);
@@ -160,6 +260,8 @@ pub fn list_by_offset(conn: &impl postgres::GenericConnection, offset: Option<i6
 }
 
 pub fn tiles(conn: &impl postgres::GenericConnection, id: i64, min_zoom: u8, max_zoom: u8) -> Result<Vec<(i32, i32, u8)>, HecateError> {
+    // Tiles containing the previous version of a modified or
+    // deleted feature must also be included
     match conn.query("
         SELECT
             geom
@@ -167,6 +269,16 @@ pub fn tiles(conn: &impl postgres::GenericConnection, id: i64, min_zoom: u8, max
             geo_history
         WHERE
             delta = $1
+        UNION ALL
+        SELECT
+            prev.geom
+        FROM
+            geo_history
+                INNER JOIN geo_history prev
+                    ON prev.id = geo_history.id
+                    AND prev.version = geo_history.version - 1
+        WHERE
+            geo_history.delta = $1
     ", &[&id]) {
         Err(err) => Err(HecateError::from_db(err)),
         Ok(results) => {
@@ -256,6 +368,293 @@ pub fn get_json(conn: &impl postgres::GenericConnection, id: i64) -> Result<serd
     }
 }
 
+///
+/// Compute the inverse of a given delta as a FeatureCollection that, once applied,
+/// returns every affected feature to the state it was in before the delta
+///
+/// Returns a 409 listing every feature that has been modified by a later delta
+///
+pub fn revert(conn: &impl postgres::GenericConnection, id: i64) -> Result<geojson::FeatureCollection, HecateError> {
+    match conn.query("
+        SELECT 1 FROM deltas WHERE id = $1
+    ", &[&id]) {
+        Err(err) => { return Err(HecateError::from_db(err)); },
+        Ok(res) => {
+            if res.is_empty() {
+                return Err(HecateError::new(404, String::from("Delta Not Found"), None));
+            }
+        }
+    };
+
+    let results = match conn.query("
+        SELECT
+            d.id,
+            head.version,
+            head.action,
+            head.key,
+            base.action,
+            base.key,
+            ST_AsGeoJSON(base.geom)::TEXT,
+            base.props::JSON,
+            (
+                SELECT MAX(version) FROM geo_history WHERE id = d.id
+            ) AS current_version,
+            (
+                SELECT ARRAY_AGG(delta ORDER BY version) FROM geo_history WHERE id = d.id AND version > d.head_version
+            ) AS later
+        FROM (
+            SELECT
+                id,
+                MIN(version) AS first_version,
+                MAX(version) AS head_version
+            FROM
+                geo_history
+            WHERE
+                delta = $1
+            GROUP BY id
+        ) d
+            INNER JOIN geo_history head
+                ON head.id = d.id AND head.version = d.head_version
+            LEFT JOIN geo_history base
+                ON base.id = d.id AND base.version = d.first_version - 1
+        ORDER BY d.id
+    ", &[&id]) {
+        Err(err) => { return Err(HecateError::from_db(err)); },
+        Ok(results) => results
+    };
+
+    if results.is_empty() {
+        return Err(HecateError::new(400, String::from("Delta has no features to revert"), None));
+    }
+
+    let mut conflicts: Vec<serde_json::Value> = Vec::new();
+    let mut features: Vec<geojson::Feature> = Vec::new();
+
+    for res in results.iter() {
+        let feat_id: i64 = res.get(0);
+        let version: i64 = res.get(1);
+        let head_action: String = res.get(2);
+        let head_key: Option<String> = res.get(3);
+        let base_action: Option<String> = res.get(4);
+        let base_key: Option<String> = res.get(5);
+        let base_geom: Option<String> = res.get(6);
+        let base_props: Option<serde_json::Value> = res.get(7);
+        let current_version: i64 = res.get(8);
+        let later: Option<Vec<i64>> = res.get(9);
+
+        if current_version != version {
+            conflicts.push(json!({
+                "id": feat_id,
+                "version": version,
+                "current_version": current_version,
+                "deltas": later.unwrap_or_default()
+            }));
+
+            continue;
+        }
+
+        let existed_before = match base_action {
+            None => false,
+            Some(ref action) => action != "delete"
+        };
+        let exists_after = head_action != "delete";
+
+        let action = match (existed_before, exists_after) {
+            (false, true) => "delete",
+            (true, false) => "restore",
+            (true, true) => "modify",
+            (false, false) => continue
+        };
+
+        let mut members = serde_json::Map::new();
+        members.insert(String::from("action"), json!(action));
+        members.insert(String::from("version"), json!(version));
+
+        let mut feat = geojson::Feature {
+            bbox: None,
+            geometry: None,
+            id: Some(geojson::feature::Id::Number(serde_json::Number::from(feat_id))),
+            properties: None,
+            foreign_members: None
+        };
+
+        if action == "delete" {
+            members.insert(String::from("key"), json!(head_key));
+        } else {
+            members.insert(String::from("key"), json!(base_key));
+
+            feat.geometry = match base_geom {
+                None => None,
+                Some(base_geom) => match serde_json::from_str::<geojson::Geometry>(&base_geom) {
+                    Ok(geom) => Some(geom),
+                    Err(err) => { return Err(HecateError::new(500, String::from("Failed to parse historic geometry"), Some(err.to_string()))); }
+                }
+            };
+
+            feat.properties = match base_props {
+                Some(serde_json::Value::Object(props)) => Some(props),
+                _ => Some(serde_json::Map::new())
+            };
+        }
+
+        feat.foreign_members = Some(members);
+
+        features.push(feat);
+    }
+
+    if !conflicts.is_empty() {
+        return Err(HecateError::new(409, String::from("Revert Conflict"), None)
+            .set_json(json!({
+                "code": 409,
+                "status": "Conflict",
+                "reason": "Revert Conflict",
+                "conflicts": conflicts
+            })));
+    }
+
+    Ok(geojson::FeatureCollection {
+        bbox: None,
+        features,
+        foreign_members: None
+    })
+}
+
+///
+/// Summarize the property & geometry changes made to each feature in a delta
+/// by pairing each version created by the delta with the version preceding it
+///
+pub fn diff(conn: &impl postgres::GenericConnection, id: i64) -> Result<serde_json::Value, HecateError> {
+    match conn.query("
+        SELECT 1 FROM deltas WHERE id = $1
+    ", &[&id]) {
+        Err(err) => { return Err(HecateError::from_db(err)); },
+        Ok(res) => {
+            if res.is_empty() {
+                return Err(HecateError::new(404, String::from("Delta Not Found"), None));
+            }
+        }
+    };
+
+    match conn.query(&*format!("
+        SELECT
+            t.id,
+            t.action,
+            t.version,
+            COALESCE(f.props::JSON, '{{}}'::JSON),
+            COALESCE(t.props::JSON, '{{}}'::JSON),
+            {}
+        FROM
+            geo_history t
+                LEFT JOIN geo_history f
+                    ON f.id = t.id AND f.version = t.version - 1
+        WHERE
+            t.delta = $1
+        ORDER BY
+            t.id,
+            t.version
+    ", feature::diff_geom_sql("f.geom", "t.geom")), &[&id]) {
+        Err(err) => Err(HecateError::from_db(err)),
+        Ok(results) => {
+            let empty = serde_json::Map::new();
+            let mut features: Vec<serde_json::Value> = Vec::new();
+
+            for res in results.iter() {
+                let feat_id: i64 = res.get(0);
+                let action: String = res.get(1);
+                let version: i64 = res.get(2);
+                let from_props: serde_json::Value = res.get(3);
+                let to_props: serde_json::Value = res.get(4);
+                let geometry: serde_json::Value = res.get(5);
+
+                features.push(json!({
+                    "id": feat_id,
+                    "action": action,
+                    "version": version,
+                    "properties": feature::diff_props(
+                        from_props.as_object().unwrap_or(&empty),
+                        to_props.as_object().unwrap_or(&empty)
+                    ),
+                    "geometry": geometry
+                }));
+            }
+
+            Ok(json!({
+                "id": id,
+                "features": features
+            }))
+        }
+    }
+}
+
+///
+/// Return each version of a feature created by a delta, paired with the
+/// version of the feature preceding it, if any
+///
+pub fn versions(conn: &impl postgres::GenericConnection, id: i64) -> Result<Vec<(Option<geojson::Feature>, geojson::Feature)>, HecateError> {
+    match conn.query("
+        SELECT 1 FROM deltas WHERE id = $1
+    ", &[&id]) {
+        Err(err) => { return Err(HecateError::from_db(err)); },
+        Ok(res) => {
+            if res.is_empty() {
+                return Err(HecateError::new(404, String::from("Delta Not Found"), None));
+            }
+        }
+    };
+
+    match conn.query("
+        SELECT
+            CASE WHEN f.id IS NULL THEN NULL ELSE JSON_Build_Object(
+                'id', f.id,
+                'action', f.action,
+                'key', f.key,
+                'type', 'Feature',
+                'version', f.version,
+                'geometry', ST_AsGeoJSON(f.geom)::JSON,
+                'properties', f.props
+            )::TEXT END,
+            JSON_Build_Object(
+                'id', t.id,
+                'action', t.action,
+                'key', t.key,
+                'type', 'Feature',
+                'version', t.version,
+                'geometry', ST_AsGeoJSON(t.geom)::JSON,
+                'properties', t.props
+            )::TEXT
+        FROM
+            geo_history t
+                LEFT JOIN geo_history f
+                    ON f.id = t.id AND f.version = t.version - 1
+        WHERE
+            t.delta = $1
+        ORDER BY
+            t.id,
+            t.version
+    ", &[&id]) {
+        Err(err) => Err(HecateError::from_db(err)),
+        Ok(results) => {
+            let parse = |feat: String| -> Result<geojson::Feature, HecateError> {
+                match feat.parse::<geojson::GeoJson>() {
+                    Ok(geojson::GeoJson::Feature(feat)) => Ok(feat),
+                    _ => Err(HecateError::new(500, String::from("Invalid Feature in History"), None))
+                }
+            };
+
+            let mut versions: Vec<(Option<geojson::Feature>, geojson::Feature)> = Vec::new();
+
+            for res in results.iter() {
+                let from: Option<String> = res.get(0);
+                let to: String = res.get(1);
+
+                versions.push((from.map(parse).transpose()?, parse(to)?));
+            }
+
+            Ok(versions)
+        }
+    }
+}
+
 pub fn modify_props(id: i64, trans: &postgres::transaction::Transaction, props: &HashMap<String, Option<String>>, uid: i64) -> Result<i64, HecateError> {
     match trans.query("
         UPDATE deltas
@@ -272,6 +671,10 @@ pub fn modify_props(id: i64, trans: &postgres::transaction::Transaction, props:
 }
 
 pub fn modify(id: i64, trans: &postgres::transaction::Transaction, fc: &geojson::FeatureCollection, uid: i64) -> Result<i64, HecateError> {
+    modify_affected(id, trans, &affected(&fc), uid)
+}
+
+pub fn modify_affected(id: i64, trans: &postgres::transaction::Transaction, affected: &[i64], uid: i64) -> Result<i64, HecateError> {
     match trans.query("
         UPDATE deltas
             SET
@@ -280,7 +683,7 @@ pub fn modify(id: i64, trans: &postgres::transaction::Transaction, fc: &geojson:
                 id = $1
                 AND uid = $2
                 AND finalized = false;
-    ", &[&id, &uid, &affected(&fc)]) {
+    ", &[&id, &uid, &affected]) {
         Err(err) => Err(HecateError::from_db(err)),
         _ => Ok(id)
     }
@@ -0,0 +1,339 @@
+use crate::err::HecateError;
+use crate::{auth, delta, feature};
+use std::collections::HashMap;
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+///
+/// Number of finished uploads to keep, so their progress can still be retrieved
+///
+static RETAIN: usize = 100;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Running,
+    Complete,
+    Failed
+}
+
+///
+/// Progress of a line-delimited upload, which can be polled
+/// while the upload is still being applied
+///
+#[derive(Serialize, Debug, Clone)]
+pub struct Progress {
+    pub delta: i64,
+    pub uid: i64,
+    pub status: Status,
+    pub total: usize,
+    pub success: usize,
+    pub failures: Vec<serde_json::Value>,
+    pub error: Option<serde_json::Value>
+}
+
+impl Progress {
+    pub fn to_value(&self) -> serde_json::Value {
+        json!(self)
+    }
+}
+
+///
+/// Progress of the running & recently finished line-delimited uploads, by delta id
+///
+#[derive(Clone, Default)]
+pub struct Uploads {
+    uploads: Arc<Mutex<HashMap<i64, Progress>>>
+}
+
+impl Uploads {
+    fn start(&self, delta: i64, uid: i64) -> Tracker {
+        let mut uploads = self.uploads.lock().unwrap();
+
+        prune(&mut uploads);
+
+        uploads.insert(delta, Progress {
+            delta,
+            uid,
+            status: Status::Running,
+            total: 0,
+            success: 0,
+            failures: Vec::new(),
+            error: None
+        });
+
+        Tracker {
+            delta,
+            uploads: self.clone()
+        }
+    }
+
+    pub fn list(&self) -> Vec<Progress> {
+        let uploads = self.uploads.lock().unwrap();
+
+        let mut list: Vec<Progress> = uploads.values().cloned().collect();
+        list.sort_by_key(|upload| upload.delta);
+
+        list
+    }
+
+    pub fn get(&self, delta: i64) -> Result<Progress, HecateError> {
+        match self.uploads.lock().unwrap().get(&delta) {
+            Some(upload) => Ok(upload.clone()),
+            None => Err(HecateError::new(404, String::from("Upload Not Found"), None))
+        }
+    }
+}
+
+///
+/// Remove the oldest finished uploads beyond the number retained
+///
+fn prune(uploads: &mut HashMap<i64, Progress>) {
+    let mut finished: Vec<i64> = uploads.values().filter(|upload| {
+        upload.status != Status::Running
+    }).map(|upload| upload.delta).collect();
+
+    if finished.len() <= RETAIN {
+        return;
+    }
+
+    finished.sort();
+
+    for delta in &finished[..finished.len() - RETAIN] {
+        uploads.remove(delta);
+    }
+}
+
+///
+/// Updates the progress of a single upload, marking it as failed
+/// if the upload is dropped before it is finished
+///
+struct Tracker {
+    delta: i64,
+    uploads: Uploads
+}
+
+impl Tracker {
+    fn update<F: FnOnce(&mut Progress)>(&self, update: F) {
+        if let Some(upload) = self.uploads.uploads.lock().unwrap().get_mut(&self.delta) {
+            update(upload);
+        }
+    }
+
+    fn fail(&self, err: HecateError) -> HecateError {
+        let err_json = err.as_json();
+
+        self.update(|upload| {
+            upload.status = Status::Failed;
+            upload.error = Some(err_json);
+        });
+
+        err
+    }
+}
+
+impl Drop for Tracker {
+    fn drop(&mut self) {
+        self.update(|upload| {
+            if upload.status == Status::Running {
+                upload.status = Status::Failed;
+            }
+        });
+    }
+}
+
+///
+/// Incrementally apply a line-delimited GeoJSON upload to the database
+/// within a single delta as the request body arrives, without ever
+/// holding the entire upload in memory
+///
+pub struct Ingest {
+    pub delta: i64,
+    uid: i64,
+    atomic: bool,
+    force: bool,
+    index: usize,
+    pending: Vec<u8>,
+    affected: Vec<i64>,
+    tracker: Tracker,
+    schema: Option<serde_json::value::Value>,
+    trans: postgres::transaction::Transaction<'static>,
+    #[allow(dead_code)]
+    conn: Box<r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>>
+}
+
+impl Ingest {
+    ///
+    /// Open a transaction & delta that all features in the upload will be applied to
+    ///
+    /// The progress of the upload is tracked in uploads under the id of the delta
+    ///
+    /// atomic: If false, failed features are skipped & reported rather than aborting the upload
+    /// force: Whether the user is allowed to upload features with the force flag set
+    ///
+    pub fn new(pg_conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>, uploads: &Uploads, props: &HashMap<String, Option<String>>, uid: i64, schema: Option<serde_json::value::Value>, atomic: bool, force: bool) -> Result<Self, HecateError> {
+        let conn = Box::new(pg_conn);
+
+        let trans = match conn.transaction() {
+            Ok(trans) => trans,
+            Err(err) => { return Err(HecateError::new(500, String::from("Failed to open transaction"), Some(err.to_string()))); }
+        };
+
+        let trans: postgres::transaction::Transaction<'static> = unsafe {
+            mem::transmute::<postgres::transaction::Transaction, postgres::transaction::Transaction<'static>>(trans)
+        };
+
+        let delta = delta::open(&trans, props, uid)?;
+
+        Ok(Ingest {
+            delta,
+            uid,
+            atomic,
+            force,
+            index: 0,
+            pending: Vec::new(),
+            affected: Vec::new(),
+            tracker: uploads.start(delta, uid),
+            schema,
+            trans,
+            conn
+        })
+    }
+
+    ///
+    /// Apply all complete features in a chunk of the request body, buffering
+    /// any trailing partial feature until the next chunk arrives
+    ///
+    pub fn push(&mut self, chunk: &[u8]) -> Result<(), HecateError> {
+        self.pending.extend_from_slice(chunk);
+
+        while let Some(pos) = self.pending.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=pos).collect();
+
+            if let Err(err) = self.line(&line) {
+                return Err(self.tracker.fail(err));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn line(&mut self, line: &[u8]) -> Result<(), HecateError> {
+        // Ignore RFC 8142 record separators & the EOT character hecate emits
+        let line: Vec<u8> = line.iter()
+            .filter(|byte| **byte != 0x1E && **byte != 0x04)
+            .copied()
+            .collect();
+
+        let line = match String::from_utf8(line) {
+            Ok(line) => line,
+            Err(err) => { return Err(HecateError::new(400, String::from("Invalid UTF8 Body"), Some(err.to_string()))); }
+        };
+
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+
+        let index = self.index;
+        self.index += 1;
+
+        let res = match line.parse::<geojson::GeoJson>() {
+            Ok(geojson::GeoJson::Feature(feat)) => self.apply(&feat),
+            _ => Err(HecateError::new(400, String::from("Each line must be a valid GeoJSON Feature"), None))
+        };
+
+        let total = self.index;
+
+        match res {
+            Ok(id) => {
+                if let Some(id) = id {
+                    self.affected.push(id);
+                }
+
+                let success = self.affected.len();
+
+                self.tracker.update(|upload| {
+                    upload.total = total;
+                    upload.success = success;
+                });
+
+                Ok(())
+            },
+            Err(err) => {
+                if self.atomic {
+                    let mut err_json = err.as_json();
+                    err_json["index"] = json!(index);
+
+                    Err(err.set_json(err_json))
+                } else {
+                    self.tracker.update(|upload| {
+                        upload.total = total;
+                        upload.failures.push(json!({
+                            "index": index,
+                            "outcome": "failure",
+                            "error": err.as_json()
+                        }));
+                    });
+
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    fn apply(&self, feat: &geojson::Feature) -> Result<Option<i64>, HecateError> {
+        if feature::is_force(feat)? && !self.force {
+            return Err(auth::config::not_authed());
+        }
+
+        let res = if self.atomic {
+            feature::action(&self.trans, &self.schema, feat, &None)?
+        } else {
+            feature::action_savepoint(&self.trans, &self.schema, feat, &None)?
+        };
+
+        Ok(res.new.or(res.old))
+    }
+
+    ///
+    /// Apply any remaining buffered feature and finalize the delta, returning
+    /// a summary of the upload
+    ///
+    /// If dry_run is set or no features were applied, all changes are rolled back
+    ///
+    pub fn finish(mut self, dry_run: bool) -> Result<serde_json::Value, HecateError> {
+        let pending = mem::replace(&mut self.pending, Vec::new());
+        if let Err(err) = self.line(&pending) {
+            return Err(self.tracker.fail(err));
+        }
+
+        let commit = !dry_run && !self.affected.is_empty();
+
+        let summary = json!({
+            "delta": if commit { Some(self.delta) } else { None },
+            "total": self.index,
+            "success": self.affected.len(),
+            "failures": self.tracker.uploads.get(self.delta)?.failures
+        });
+
+        if commit {
+            if let Err(err) = delta::modify_affected(self.delta, &self.trans, &self.affected, self.uid) {
+                return Err(self.tracker.fail(err));
+            }
+            if let Err(err) = delta::finalize(self.delta, &self.trans) {
+                return Err(self.tracker.fail(err));
+            }
+
+            if self.trans.commit().is_err() {
+                return Err(self.tracker.fail(HecateError::new(500, String::from("Failed to commit transaction"), None)));
+            }
+        } else {
+            self.trans.set_rollback();
+            self.trans.finish().unwrap();
+        }
+
+        self.tracker.update(|upload| upload.status = Status::Complete);
+
+        Ok(summary)
+    }
+}
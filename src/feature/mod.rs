@@ -1,6 +1,10 @@
+pub mod ingest;
+
 use crate::stream::PGStream;
 use crate::err::HecateError;
 use crate::validate;
+use crate::delta;
+pub use self::ingest::{Ingest, Uploads};
 
 #[derive(PartialEq, Debug)]
 pub enum Action {
@@ -59,6 +63,251 @@ pub fn is_force(feat: &geojson::Feature) -> Result<bool, HecateError> {
     }
 }
 
+///
+/// Check if the feature has the merge: true flag set and if so
+/// validate that it is only being used on a modify
+///
+pub fn is_merge(feat: &geojson::Feature) -> Result<bool, HecateError> {
+    match feat.foreign_members {
+        None => Ok(false),
+        Some(ref members) => match members.get("merge") {
+            Some(merge) => {
+                match merge.as_bool() {
+                    Some(true) => {
+                        if get_action(feat)? != Action::Modify {
+                            return Err(import_error(feat, "merge can only be used on modify", None));
+                        }
+
+                        Ok(true)
+                    },
+                    Some(false) => Ok(false),
+                    None => Err(import_error(feat, "merge must be a boolean", None))
+                }
+            },
+            None => Ok(false)
+        }
+    }
+}
+
+///
+/// Three-way merge of a single value, returning None if the
+/// current & submitted values have both diverged from the base
+///
+fn merge_value<T: PartialEq + Clone>(base: &T, current: &T, submitted: &T) -> Option<T> {
+    if submitted == base {
+        Some(current.clone())
+    } else if current == base || current == submitted {
+        Some(submitted.clone())
+    } else {
+        None
+    }
+}
+
+///
+/// Three-way merge of feature properties, key by key, returning
+/// the merged properties and any conflicting keys
+///
+pub fn merge_props(base: &serde_json::Map<String, serde_json::Value>, current: &serde_json::Map<String, serde_json::Value>, submitted: &serde_json::Map<String, serde_json::Value>) -> (serde_json::Map<String, serde_json::Value>, serde_json::Map<String, serde_json::Value>) {
+    let mut merged = serde_json::Map::new();
+    let mut conflicts = serde_json::Map::new();
+
+    let mut keys: Vec<&String> = base.keys().chain(current.keys()).chain(submitted.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    for key in keys {
+        let base_value = base.get(key);
+        let current_value = current.get(key);
+        let submitted_value = submitted.get(key);
+
+        match merge_value(&base_value, &current_value, &submitted_value) {
+            Some(Some(value)) => {
+                merged.insert(key.clone(), value.clone());
+            },
+            Some(None) => (),
+            None => {
+                conflicts.insert(key.clone(), json!({
+                    "base": base_value,
+                    "current": current_value,
+                    "submitted": submitted_value
+                }));
+            }
+        }
+    }
+
+    (merged, conflicts)
+}
+
+///
+/// Given a modify feature submitted against a stale version, merge its changes
+/// with those made since that version, using the submitted version in geo_history as the base
+///
+/// Returns a feature with the merged properties, geometry & key set against the current version
+/// or a 409 describing each conflict if both sets of changes modified the same value
+///
+pub fn merge(trans: &postgres::transaction::Transaction, feat: &geojson::Feature) -> Result<geojson::Feature, HecateError> {
+    let id = get_id(feat)?;
+    let version = get_version(feat)?;
+    let key = get_key(feat)?;
+
+    let submitted_props = match feat.properties {
+        None => { return Err(import_error(feat, "Properties Required", None)); },
+        Some(ref props) => props
+    };
+
+    let current = match trans.query("
+        SELECT
+            version,
+            key,
+            ST_AsGeoJSON(geom)::TEXT,
+            props::JSON
+        FROM
+            geo
+        WHERE
+            id = $1
+    ", &[&id]) {
+        Err(err) => { return Err(HecateError::from_db(err)); },
+        Ok(res) => res
+    };
+
+    // Missing features & up to date versions are left to the regular modify checks
+    if current.is_empty() { return Ok(feat.clone()); }
+
+    let current_version: i64 = current.get(0).get(0);
+    if current_version == version { return Ok(feat.clone()); }
+
+    let current_key: Option<String> = current.get(0).get(1);
+    let current_geom: Option<String> = current.get(0).get(2);
+    let current_props: serde_json::Value = current.get(0).get(3);
+
+    let base = match trans.query("
+        SELECT
+            key,
+            ST_AsGeoJSON(geom)::TEXT,
+            props::JSON
+        FROM
+            geo_history
+        WHERE
+            id = $1
+            AND version = $2
+    ", &[&id, &version]) {
+        Err(err) => { return Err(HecateError::from_db(err)); },
+        Ok(res) => res
+    };
+
+    if base.is_empty() {
+        return Err(import_error(feat, "Merge Base Version Not Found", None));
+    }
+
+    let base_key: Option<String> = base.get(0).get(0);
+    let base_geom: Option<String> = base.get(0).get(1);
+    let base_props: Option<serde_json::Value> = base.get(0).get(2);
+
+    let parse_geom = |geom: Option<String>| -> Result<Option<geojson::Geometry>, HecateError> {
+        match geom {
+            None => Ok(None),
+            Some(geom) => match serde_json::from_str::<geojson::Geometry>(&geom) {
+                Ok(geom) => Ok(Some(geom)),
+                Err(err) => Err(HecateError::new(500, String::from("Failed to parse stored geometry"), Some(err.to_string())))
+            }
+        }
+    };
+
+    let base_geom = parse_geom(base_geom)?;
+    let current_geom = parse_geom(current_geom)?;
+
+    let submitted_geom: Option<String> = match feat.geometry {
+        None => None,
+        Some(ref geom) => match serde_json::to_string(geom) {
+            Ok(geom) => Some(geom),
+            Err(err) => { return Err(import_error(feat, "Invalid Geometry", Some(err.to_string()))); }
+        }
+    };
+
+    // Geometries are compared in PostGIS, as the GeoJSON output of a
+    // stored geometry is rounded & would rarely equal the submitted one
+    let geom_equals = match trans.query("
+        SELECT
+            COALESCE(ST_OrderingEquals(submitted.geom, base.geom), submitted.geom IS NULL AND base.geom IS NULL),
+            COALESCE(ST_OrderingEquals(current.geom, base.geom), current.geom IS NULL AND base.geom IS NULL),
+            COALESCE(ST_OrderingEquals(current.geom, submitted.geom), current.geom IS NULL AND submitted.geom IS NULL)
+        FROM
+            (SELECT ST_SetSRID(ST_GeomFromGeoJSON($3::TEXT), 4326) AS geom) submitted,
+            (SELECT geom FROM geo WHERE id = $1) current,
+            (SELECT geom FROM geo_history WHERE id = $1 AND version = $2) base
+    ", &[&id, &version, &submitted_geom]) {
+        Err(err) => { return Err(HecateError::from_db(err)); },
+        Ok(res) => res
+    };
+
+    let submitted_is_base: bool = geom_equals.get(0).get(0);
+    let current_is_base: bool = geom_equals.get(0).get(1);
+    let current_is_submitted: bool = geom_equals.get(0).get(2);
+
+    let empty = serde_json::Map::new();
+    let (props, prop_conflicts) = merge_props(
+        base_props.as_ref().and_then(|props| props.as_object()).unwrap_or(&empty),
+        current_props.as_object().unwrap_or(&empty),
+        submitted_props
+    );
+
+    // Same three-way merge as merge_value, using the geometry comparisons
+    let geom = if submitted_is_base {
+        Some(current_geom.clone())
+    } else if current_is_base || current_is_submitted {
+        Some(feat.geometry.clone())
+    } else {
+        None
+    };
+    let merged_key = merge_value(&base_key, &current_key, &key);
+
+    if !prop_conflicts.is_empty() || geom.is_none() || merged_key.is_none() {
+        let geom_conflict = match geom {
+            Some(_) => serde_json::Value::Null,
+            None => json!({
+                "base": base_geom,
+                "current": current_geom,
+                "submitted": feat.geometry
+            })
+        };
+
+        let key_conflict = match merged_key {
+            Some(_) => serde_json::Value::Null,
+            None => json!({
+                "base": base_key,
+                "current": current_key,
+                "submitted": key
+            })
+        };
+
+        return Err(HecateError::new(409, String::from("Merge Conflict"), None)
+            .set_json(json!({
+                "code": 409,
+                "status": "Conflict",
+                "reason": "Merge Conflict",
+                "id": id,
+                "version": version,
+                "current_version": current_version,
+                "conflicts": {
+                    "properties": prop_conflicts,
+                    "geometry": geom_conflict,
+                    "key": key_conflict
+                }
+            })));
+    }
+
+    let mut merged = feat.clone();
+    merged.properties = Some(props);
+    merged.geometry = geom.unwrap();
+
+    if let Some(ref mut members) = merged.foreign_members {
+        members.insert(String::from("version"), json!(current_version));
+        members.insert(String::from("key"), json!(merged_key.unwrap()));
+    }
+
+    Ok(merged)
+}
+
 pub fn del_version(feat: &mut geojson::Feature) {
     match feat.foreign_members {
         None => (),
@@ -212,6 +461,7 @@ pub fn get_key(feat: &geojson::Feature) -> Result<Option<String>, HecateError> {
 
 pub fn action(trans: &postgres::transaction::Transaction, schema_json: &Option<serde_json::value::Value>, feat: &geojson::Feature, delta: &Option<i64>) -> Result<Response, HecateError> {
     let action = get_action(&feat)?;
+    is_merge(feat)?;
 
     let mut scope = valico::json_schema::Scope::new();
     let schema = match *schema_json {
@@ -234,6 +484,33 @@ pub fn action(trans: &postgres::transaction::Transaction, schema_json: &Option<s
     Ok(res)
 }
 
+///
+/// Apply a feature action within its own savepoint so that a failed feature
+/// is rolled back without aborting the parent transaction
+///
+pub fn action_savepoint(trans: &postgres::transaction::Transaction, schema_json: &Option<serde_json::value::Value>, feat: &geojson::Feature, delta: &Option<i64>) -> Result<Response, HecateError> {
+    let savepoint = match trans.savepoint("feature") {
+        Ok(savepoint) => savepoint,
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    match action(&savepoint, schema_json, feat, delta) {
+        Ok(res) => {
+            if let Err(err) = savepoint.commit() {
+                return Err(HecateError::from_db(err));
+            }
+
+            Ok(res)
+        },
+        Err(err) => {
+            savepoint.set_rollback();
+            savepoint.finish().unwrap();
+
+            Err(err)
+        }
+    }
+}
+
 pub fn create(trans: &postgres::transaction::Transaction, schema: &Option<valico::json_schema::schema::ScopedSchema>, feat: &geojson::Feature, delta: &Option<i64>) -> Result<Response, HecateError> {
     if get_version(&feat).is_ok() {
         return Err(import_error(&feat, "Cannot have Version", None));
@@ -380,6 +657,14 @@ pub fn create(trans: &postgres::transaction::Transaction, schema: &Option<valico
 }
 
 pub fn modify(trans: &postgres::transaction::Transaction, schema: &Option<valico::json_schema::schema::ScopedSchema>, feat: &geojson::Feature, delta: &Option<i64>) -> Result<Response, HecateError> {
+    let merged;
+    let feat = if is_merge(feat)? {
+        merged = merge(trans, feat)?;
+        &merged
+    } else {
+        feat
+    };
+
     let props = match feat.properties {
         None => { return Err(import_error(&feat, "Properties Required", None)); },
         Some(ref props) => props
@@ -611,6 +896,64 @@ pub fn get(conn: &impl postgres::GenericConnection, id: i64) -> Result<geojson::
     }
 }
 
+///
+/// Reconstruct a feature as it existed at a given point in history
+///
+/// Returns a 404 if the feature had not yet been created or was in a
+/// deleted state at that point
+///
+pub fn get_at(conn: &impl postgres::GenericConnection, id: i64, at: &delta::At) -> Result<geojson::Feature, HecateError> {
+    let (at_delta, at_time): (Option<i64>, Option<chrono::NaiveDateTime>) = match at {
+        delta::At::Delta(delta) => (Some(*delta), None),
+        delta::At::Timestamp(time) => (None, Some(*time))
+    };
+
+    match conn.query("
+        SELECT
+            row_to_json(f)::TEXT AS feature
+        FROM (
+            SELECT
+                h.id AS id,
+                h.key AS key,
+                'Feature' AS type,
+                h.version AS version,
+                ST_AsGeoJSON(h.geom)::JSON AS geometry,
+                h.props AS properties
+            FROM (
+                SELECT
+                    geo_history.*
+                FROM
+                    geo_history,
+                    deltas
+                WHERE
+                    geo_history.id = $1
+                    AND geo_history.delta = deltas.id
+                    AND (
+                        ($2::BIGINT IS NOT NULL AND deltas.id <= $2::BIGINT)
+                        OR ($3::TIMESTAMP IS NOT NULL AND deltas.created <= $3::TIMESTAMP)
+                    )
+                ORDER BY geo_history.version DESC
+                LIMIT 1
+            ) h
+            WHERE
+                h.action != 'delete'
+        ) f;
+    ", &[&id, &at_delta, &at_time]) {
+        Ok(res) => {
+            if res.len() != 1 { return Err(HecateError::new(404, String::from("Not Found"), None)); }
+
+            let feat: String = res.get(0).get(0);
+            let feat: geojson::Feature = match feat.parse() {
+                Ok(geojson::GeoJson::Feature(feat)) => feat,
+                _ => { return Err(HecateError::new(400, String::from("Invalid Feature"), None)); }
+            };
+
+            Ok(feat)
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
+
 pub fn restore(trans: &postgres::transaction::Transaction, schema: &Option<valico::json_schema::schema::ScopedSchema>, feat: &geojson::Feature, delta: &Option<i64>) -> Result<Response, HecateError> {
     let props = match feat.properties {
         None => { return Err(import_error(&feat, "Properties Required", None)); },
@@ -857,14 +1200,166 @@ pub fn history(conn: &impl postgres::GenericConnection, feat_id: i64) -> Result<
             if res.is_empty() {
                 return Err(HecateError::new(400, String::from("Could not find history for given id"), None))
             }
-            let history: serde_json::Value = res.get(0).get(0);
-
-            Ok(history)
+            match res.get(0).get::<_, Option<serde_json::Value>>(0) {
+                Some(history) => Ok(history),
+                None => Err(HecateError::new(400, String::from("Could not find history for given id"), None))
+            }
         },
         Err(err) => Err(HecateError::from_db(err))
     }
 }
 
+///
+/// Diff the properties of two versions of a feature, listing
+/// added & removed keys and the old & new value of changed keys
+///
+pub fn diff_props(from: &serde_json::Map<String, serde_json::Value>, to: &serde_json::Map<String, serde_json::Value>) -> serde_json::Value {
+    let mut added = serde_json::Map::new();
+    let mut removed = serde_json::Map::new();
+    let mut changed = serde_json::Map::new();
+
+    for (key, value) in from {
+        match to.get(key) {
+            None => {
+                removed.insert(key.clone(), value.clone());
+            },
+            Some(to_value) => {
+                if to_value != value {
+                    changed.insert(key.clone(), json!({
+                        "from": value,
+                        "to": to_value
+                    }));
+                }
+            }
+        }
+    }
+
+    for (key, value) in to {
+        if !from.contains_key(key) {
+            added.insert(key.clone(), value.clone());
+        }
+    }
+
+    json!({
+        "added": added,
+        "removed": removed,
+        "changed": changed
+    })
+}
+
+///
+/// SQL expression summarizing the change between two geometry expressions
+/// as JSON: type change, vertex count change, hausdorff distance & bbox change
+///
+pub fn diff_geom_sql(from: &str, to: &str) -> String {
+    format!(r#"
+        JSON_Build_Object(
+            'changed', COALESCE(NOT ST_OrderingEquals({from}, {to}), ({from} IS NULL) != ({to} IS NULL)),
+            'type', JSON_Build_Object(
+                'from', Replace(ST_GeometryType({from}), 'ST_', ''),
+                'to', Replace(ST_GeometryType({to}), 'ST_', ''),
+                'changed', COALESCE(ST_GeometryType({from}), '') != COALESCE(ST_GeometryType({to}), '')
+            ),
+            'vertices', JSON_Build_Object(
+                'from', ST_NPoints({from}),
+                'to', ST_NPoints({to}),
+                'delta', COALESCE(ST_NPoints({to}), 0) - COALESCE(ST_NPoints({from}), 0)
+            ),
+            'hausdorff', ST_HausdorffDistance({from}, {to}),
+            'bbox', JSON_Build_Object(
+                'from', CASE WHEN {from} IS NULL THEN NULL ELSE JSON_Build_Array(ST_XMin({from}), ST_YMin({from}), ST_XMax({from}), ST_YMax({from})) END,
+                'to', CASE WHEN {to} IS NULL THEN NULL ELSE JSON_Build_Array(ST_XMin({to}), ST_YMin({to}), ST_XMax({to}), ST_YMax({to})) END
+            )
+        )
+    "#, from = from, to = to)
+}
+
+///
+/// Return a structured diff of the properties & geometry between two versions of a feature
+///
+/// If to is not given the latest version is used, if from is not given the version
+/// immediately preceding to is used. Version 0 is the empty state before the feature
+/// was created, so a feature with a single version is diffed against it
+///
+pub fn diff(conn: &impl postgres::GenericConnection, id: i64, from: Option<i64>, to: Option<i64>) -> Result<serde_json::Value, HecateError> {
+    let to = match to {
+        Some(to) => to,
+        None => match conn.query("
+            SELECT MAX(version) FROM geo_history WHERE id = $1
+        ", &[&id]) {
+            Err(err) => { return Err(HecateError::from_db(err)); },
+            Ok(res) => {
+                let version: Option<i64> = res.get(0).get(0);
+
+                match version {
+                    Some(version) => version,
+                    None => { return Err(HecateError::new(404, String::from("Feature Not Found"), None)); }
+                }
+            }
+        }
+    };
+
+    let from = from.unwrap_or(to - 1);
+
+    if from < 0 {
+        return Err(HecateError::new(400, String::from("from version cannot be negative"), None));
+    } else if from >= to {
+        return Err(HecateError::new(400, String::from("from version must be less than to version"), None));
+    }
+
+    match conn.query(&*format!("
+        SELECT
+            f.action,
+            t.action,
+            COALESCE(f.props::JSON, '{{}}'::JSON),
+            COALESCE(t.props::JSON, '{{}}'::JSON),
+            {}
+        FROM
+            geo_history t
+                LEFT JOIN geo_history f
+                    ON f.id = t.id AND f.version = $2
+        WHERE
+            t.id = $1
+            AND t.version = $3
+    ", diff_geom_sql("f.geom", "t.geom")), &[&id, &from, &to]) {
+        Err(err) => Err(HecateError::from_db(err)),
+        Ok(res) => {
+            if res.is_empty() {
+                return Err(HecateError::new(404, String::from("Feature Version Not Found"), None));
+            }
+
+            let from_action: Option<String> = res.get(0).get(0);
+            let to_action: String = res.get(0).get(1);
+            let from_props: serde_json::Value = res.get(0).get(2);
+            let to_props: serde_json::Value = res.get(0).get(3);
+            let geometry: serde_json::Value = res.get(0).get(4);
+
+            if from > 0 && from_action.is_none() {
+                return Err(HecateError::new(404, String::from("Feature Version Not Found"), None));
+            }
+
+            let empty = serde_json::Map::new();
+
+            Ok(json!({
+                "id": id,
+                "from": {
+                    "version": from,
+                    "action": from_action
+                },
+                "to": {
+                    "version": to,
+                    "action": to_action
+                },
+                "properties": diff_props(
+                    from_props.as_object().unwrap_or(&empty),
+                    to_props.as_object().unwrap_or(&empty)
+                ),
+                "geometry": geometry
+            }))
+        }
+    }
+}
+
 pub fn get_point_history_stream(conn: r2d2::PooledConnection<r2d2_postgres::PostgresConnectionManager>, point: &str) -> Result<PGStream, HecateError> {
     let (lng, lat) = validate::point(point)?;
 
@@ -915,3 +1410,63 @@ pub fn get_bbox_history_stream(conn: r2d2::PooledConnection<r2d2_postgres::Postg
             ) f;
     "#), &[&bbox[0], &bbox[1], &bbox[2], &bbox[3]])?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_props_changes() {
+        let from = json!({ "name": "main st", "lanes": 2, "oneway": true });
+        let to = json!({ "name": "Main St", "lanes": 2, "surface": "paved" });
+
+        assert_eq!(diff_props(from.as_object().unwrap(), to.as_object().unwrap()), json!({
+            "added": { "surface": "paved" },
+            "removed": { "oneway": true },
+            "changed": {
+                "name": { "from": "main st", "to": "Main St" }
+            }
+        }));
+    }
+
+    #[test]
+    fn merge_props_disjoint() {
+        let base = json!({ "name": "main st", "lanes": 2 });
+        let current = json!({ "name": "Main St", "lanes": 2 });
+        let submitted = json!({ "name": "main st", "lanes": 4, "oneway": true });
+
+        let (merged, conflicts) = merge_props(base.as_object().unwrap(), current.as_object().unwrap(), submitted.as_object().unwrap());
+
+        assert_eq!(json!(merged), json!({ "name": "Main St", "lanes": 4, "oneway": true }));
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn merge_props_removed() {
+        let base = json!({ "name": "main st", "lanes": 2 });
+        let current = json!({ "name": "main st", "lanes": 2, "oneway": true });
+        let submitted = json!({ "name": "main st" });
+
+        let (merged, conflicts) = merge_props(base.as_object().unwrap(), current.as_object().unwrap(), submitted.as_object().unwrap());
+
+        assert_eq!(json!(merged), json!({ "name": "main st", "oneway": true }));
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn merge_props_conflict() {
+        let base = json!({ "name": "main st", "lanes": 2 });
+        let current = json!({ "name": "Main St", "lanes": 2 });
+        let submitted = json!({ "name": "Main Street", "lanes": 2 });
+
+        let (_, conflicts) = merge_props(base.as_object().unwrap(), current.as_object().unwrap(), submitted.as_object().unwrap());
+
+        assert_eq!(json!(conflicts), json!({
+            "name": {
+                "base": "main st",
+                "current": "Main St",
+                "submitted": "Main Street"
+            }
+        }));
+    }
+}
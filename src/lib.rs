@@ -49,7 +49,8 @@ pub fn start(
     port: Option<u16>,
     workers: Option<u16>,
     schema: Option<serde_json::value::Value>,
-    auth: Option<auth::CustomAuth>
+    auth: Option<auth::CustomAuth>,
+    tiles: Option<mvt::Config>
 ) {
     let auth_rules: auth::CustomAuth = match auth {
         None => auth::CustomAuth::default(),
@@ -69,7 +70,21 @@ pub fn start(
     let db_sandbox = DbSandbox::new(Some(database.sandbox.iter().map(|db| db::init_pool(&db)).collect()));
     let db_main = DbReadWrite::new(init_pool(&database.main));
 
-    let worker = worker::Worker::new(database.main);
+    let tiles: mvt::Config = match tiles {
+        None => mvt::Config::default(),
+        Some(tiles) => {
+            if let Err(err) = tiles.is_valid() {
+                println!("ERROR: {}", err);
+                std::process::exit(1);
+            }
+
+            tiles
+        }
+    };
+
+    let worker = worker::Worker::new(database.main, tiles.clone());
+
+    let uploads = feature::Uploads::default();
 
     std::env::set_var("RUST_LOG", "actix_web=info");
     env_logger::init();
@@ -89,10 +104,12 @@ pub fn start(
             .wrap(middleware::Compress::default())
             .data(auth_rules.clone())
             .data(worker.clone())
+            .data(uploads.clone())
             .data(db_replica.clone())
             .data(db_sandbox.clone())
             .data(db_main.clone())
             .data(schema.clone())
+            .data(tiles.clone())
             //TODO HANDLE GENERIC 404
             .route("/", web::get().to(index))
             .service(
@@ -120,6 +137,7 @@ pub fn start(
                         .route(web::put().to_async(osm_changeset_create))
                     )
                     .service(web::resource("changeset/{delta_id}")
+                        .route(web::get().to(osm_changeset_get))
                         .route(web::put().to_async(osm_changeset_modify))
                     )
                     .service(web::resource("changeset/{delta_id}/close")
@@ -128,6 +146,24 @@ pub fn start(
                     .service(web::resource("changeset/{delta_id}/upload")
                         .route(web::post().to_async(osm_changeset_upload))
                     )
+                    .service(web::resource("changeset/{delta_id}/download")
+                        .route(web::get().to(osm_changeset_download))
+                    )
+                    .service(web::resource("changesets")
+                        .route(web::get().to(osm_changesets))
+                    )
+                    .service(web::resource("node/{id}")
+                        .route(web::get().to(osm_node))
+                    )
+                    .service(web::resource("nodes")
+                        .route(web::get().to(osm_nodes))
+                    )
+                    .service(web::resource("way/{id}/full")
+                        .route(web::get().to(osm_way_full))
+                    )
+                    .service(web::resource("{element}/{id}/history")
+                        .route(web::get().to(osm_history))
+                    )
                 )
                 .service(web::resource("auth")
                     .route(web::get().to(auth_get))
@@ -155,6 +191,9 @@ pub fn start(
                     .service(web::resource("{style_id}/private")
                         .route(web::post().to(style_private))
                     )
+                    .service(web::resource("{style_id}/tiles/{z}/{x}/{y:\\d+}.png")
+                        .route(web::get().to_async(style_tile))
+                    )
                  )
                 .service(web::scope("styles")
                     .service(web::resource("")
@@ -170,9 +209,18 @@ pub fn start(
                 .service(web::resource("deltas")
                     .route(web::get().to_async(delta_list))
                 )
+                .service(web::resource("delta/{id:\\d+}.osc")
+                    .route(web::get().to_async(delta_osc))
+                )
                 .service(web::resource("delta/{id}")
                     .route(web::get().to_async(delta))
                 )
+                .service(web::resource("delta/{id}/diff")
+                    .route(web::get().to_async(delta_diff))
+                )
+                .service(web::resource("delta/{id}/revert")
+                    .route(web::post().to_async(delta_revert))
+                )
                 .service(web::scope("webhooks")
                     .service(web::resource("")
                         .route(web::get().to(webhooks_list))
@@ -188,6 +236,20 @@ pub fn start(
                     .service(web::resource("")
                         .route(web::delete().to_async(mvt_wipe))
                     )
+                    .service(web::resource("tilejson.json")
+                        .route(web::get().to_async(mvt_tilejson))
+                    )
+                    .service(web::resource("export")
+                        .route(web::get().to_async(mvt_export))
+                    )
+                    .service(web::resource("seed")
+                        .route(web::get().to(mvt_seed_list))
+                        .route(web::post().to_async(mvt_seed))
+                    )
+                    .service(web::resource("seed/{id}")
+                        .route(web::get().to(mvt_seed_get))
+                        .route(web::delete().to(mvt_seed_cancel))
+                    )
                     .service(web::resource("{z}/{x}/{y}")
                         .route(web::get().to_async(mvt_get))
                     )
@@ -197,6 +259,12 @@ pub fn start(
                     .service(web::resource("{z}/{x}/{y}/regen")
                         .route(web::get().to_async(mvt_regen))
                     )
+                    .service(web::resource("activity/{z}/{x}/{y}")
+                        .route(web::get().to_async(mvt_activity))
+                    )
+                    .service(web::resource("{grid}/{z}/{x}/{y}")
+                        .route(web::get().to_async(mvt_get_grid))
+                    )
                 )
                 .service(web::resource("users")
                     .route(web::get().to(users))
@@ -241,6 +309,9 @@ pub fn start(
                     .service(web::resource("feature/{id}/history")
                         .route(web::get().to_async(feature_get_history))
                     )
+                    .service(web::resource("feature/{id}/diff")
+                        .route(web::get().to_async(feature_get_diff))
+                    )
                     .service(web::resource("features")
                         .route(web::post().to_async(features_action))
                         .route(web::get().to(features_query))
@@ -248,6 +319,12 @@ pub fn start(
                     .service(web::resource("features/history")
                         .route(web::get().to(features_history_query))
                     )
+                    .service(web::resource("features/uploads")
+                        .route(web::get().to(features_uploads))
+                    )
+                    .service(web::resource("features/uploads/{delta}")
+                        .route(web::get().to(features_upload_get))
+                    )
                     .service(web::resource("stats")
                         .route(web::get().to_async(stats_get))
                     )
@@ -304,6 +381,18 @@ struct Map {
     point: Option<String>
 }
 
+#[derive(Deserialize, Debug)]
+struct Changesets {
+    user: Option<i64>,
+    bbox: Option<String>,
+    time: Option<String>
+}
+
+#[derive(Deserialize, Debug)]
+struct Nodes {
+    nodes: String
+}
+
 #[derive(Deserialize, Debug)]
 struct Token {
     name: Option<String>,
@@ -311,12 +400,34 @@ struct Token {
     scope: Option<String> //read, full (default read)
 }
 
+#[derive(Deserialize, Debug)]
+struct ExportOpts {
+    bbox: Option<String>,
+    bounds: Option<String>,
+    minzoom: Option<u8>,
+    maxzoom: Option<u8>,
+    format: Option<String>
+}
+
+#[derive(Deserialize, Debug)]
+struct SeedOpts {
+    bbox: Option<Vec<f64>>,
+    bounds: Option<String>,
+    minzoom: Option<u8>,
+    maxzoom: Option<u8>
+}
+
 #[derive(Deserialize, Debug)]
 struct DeltaList {
     offset: Option<i64>,
     limit: Option<i64>,
     start: Option<String>,
-    end: Option<String>
+    end: Option<String>,
+    uid: Option<i64>,
+    bbox: Option<String>,
+    bounds: Option<String>,
+    feature: Option<i64>,
+    message: Option<String>
 }
 
 #[derive(Deserialize, Debug)]
@@ -325,6 +436,42 @@ struct CloneQuery {
     limit: Option<i64>
 }
 
+#[derive(Deserialize, Debug)]
+struct FeaturesOpts {
+    dry_run: Option<bool>,
+    atomic: Option<bool>,
+    message: Option<String>
+}
+
+#[derive(Deserialize, Debug)]
+struct TileAt {
+    delta: Option<i64>
+}
+
+#[derive(Deserialize, Debug)]
+struct ActivityOpts {
+    start: Option<String>,
+    end: Option<String>,
+    uid: Option<i64>
+}
+
+#[derive(Deserialize, Debug)]
+struct CloneAt {
+    delta: Option<i64>,
+    timestamp: Option<String>
+}
+
+#[derive(Deserialize, Debug)]
+struct FeatureDiff {
+    from: Option<i64>,
+    to: Option<i64>
+}
+
+#[derive(Deserialize, Debug)]
+struct FeatureAt {
+    at: Option<String>
+}
+
 #[derive(Deserialize, Debug)]
 struct FeatureQuery {
     key: Option<String>,
@@ -427,34 +574,176 @@ fn meta_set(
 }
 
 
+#[allow(clippy::too_many_arguments)]
 fn mvt_get(
     conn_write: web::Data<DbReadWrite>,
     conn_read: web::Data<DbReplica>,
     auth: auth::Auth,
     auth_rules: web::Data<auth::AuthContainer>,
-    path: web::Path<(u8, u32, u32)>
+    tiles: web::Data<mvt::Config>,
+    path: web::Path<(u8, u32, u32)>,
+    opts: web::Query<TileAt>,
+    req: HttpRequest
+) -> impl Future<Item = HttpResponse, Error = HecateError> {
+    let (z, x, y) = path.into_inner();
+
+    mvt_tile(conn_write, conn_read, auth, auth_rules, tiles, String::from("WebMercator"), z, x, y, opts.delta, req)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn mvt_get_grid(
+    conn_write: web::Data<DbReadWrite>,
+    conn_read: web::Data<DbReplica>,
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    tiles: web::Data<mvt::Config>,
+    path: web::Path<(String, u8, u32, u32)>,
+    opts: web::Query<TileAt>,
+    req: HttpRequest
+) -> impl Future<Item = HttpResponse, Error = HecateError> {
+    let (grid, z, x, y) = path.into_inner();
+
+    mvt_tile(conn_write, conn_read, auth, auth_rules, tiles, grid, z, x, y, opts.delta, req)
+}
+
+///
+/// Only current web mercator tiles are cached, tiles in other grids or
+/// as of a given delta are rendered on every request
+///
+#[allow(clippy::too_many_arguments)]
+fn mvt_tile(
+    conn_write: web::Data<DbReadWrite>,
+    conn_read: web::Data<DbReplica>,
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    tiles: web::Data<mvt::Config>,
+    grid: String,
+    z: u8, x: u32, y: u32,
+    delta: Option<i64>,
+    req: HttpRequest
 ) -> impl Future<Item = HttpResponse, Error = HecateError> {
+    let cached = grid == "WebMercator" && delta.is_none();
+
     web::block(move || {
         auth::check(&auth_rules.0.mvt.get, auth::RW::Read, &auth)?;
 
-        let z = path.0;
-        let x = path.1;
-        let y = path.2;
-
         if z > 17 { return Err(HecateError::new(404, String::from("Tile Not Found"), None)); }
 
-        Ok(mvt::get(&*conn_read.get()?, &*conn_write.get()?, z, x, y, false)?)
-    }).then(|res: Result<Vec<u8>, actix_threadpool::BlockingError<HecateError>>| match res {
-        Ok(tile) => {
+        if cached {
+            return mvt::get(&*conn_read.get()?, &*conn_write.get()?, &tiles, z, x, y, false);
+        }
+
+        let at = match delta {
+            Some(delta) => {
+                auth::check(&auth_rules.0.feature.history, auth::RW::Read, &auth)?;
+
+                if delta < 1 {
+                    return Err(HecateError::new(400, String::from("Delta id must be greater than 0"), None));
+                }
+
+                Some(delta::At::Delta(delta))
+            },
+            None => None
+        };
+
+        Ok(mvt::Tile {
+            tile: mvt::render(&*conn_read.get()?, &tiles, &tiles.grid(&grid)?, z, x, y, at.as_ref())?,
+            created: chrono::Utc::now()
+        })
+    }).then(move |res: Result<mvt::Tile, actix_threadpool::BlockingError<HecateError>>| match res {
+        // Uncached tiles are rendered on every request so have no cache headers
+        Ok(tile) => if cached {
+            Ok(mvt_response(&req, tile))
+        } else {
             Ok(HttpResponse::build(actix_web::http::StatusCode::OK)
                 .content_type("application/x-protobuf")
-                .content_length(tile.len() as u64)
-                .body(tile))
+                .content_length(tile.tile.len() as u64)
+                .body(tile.tile))
         },
         Err(err) => Ok(HecateError::from(err).error_response())
     })
 }
 
+///
+/// Activity tiles are always rendered from the latest history & never cached
+///
+fn mvt_activity(
+    conn: web::Data<DbReplica>,
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    path: web::Path<(u8, u32, u32)>,
+    opts: web::Query<ActivityOpts>
+) -> impl Future<Item = HttpResponse, Error = HecateError> {
+    let (z, x, y) = path.into_inner();
+
+    web::block(move || {
+        auth::check(&auth_rules.0.mvt.get, auth::RW::Read, &auth)?;
+        auth::check(&auth_rules.0.delta.list, auth::RW::Read, &auth)?;
+
+        let start: Option<chrono::NaiveDateTime> = match &opts.start {
+            None => None,
+            Some(start) => Some(validate::timestamp(start)?)
+        };
+
+        let end: Option<chrono::NaiveDateTime> = match &opts.end {
+            None => None,
+            Some(end) => Some(validate::timestamp(end)?)
+        };
+
+        let filter = mvt::activity::Filter {
+            start,
+            end,
+            uid: opts.uid
+        };
+
+        mvt::activity::render(&*conn.get()?, z, x, y, &filter)
+    }).then(|res: Result<Vec<u8>, actix_threadpool::BlockingError<HecateError>>| match res {
+        Ok(tile) => Ok(HttpResponse::build(actix_web::http::StatusCode::OK)
+            .content_type("application/x-protobuf")
+            .content_length(tile.len() as u64)
+            .body(tile)),
+        Err(err) => Ok(HecateError::from(err).error_response())
+    })
+}
+
+///
+/// Return a tile with the headers needed for a client to revalidate it,
+/// or an empty 304 if the client's copy is still current
+///
+fn mvt_response(req: &HttpRequest, tile: mvt::Tile) -> HttpResponse {
+    let etag = tile.etag();
+
+    let not_modified = match req.headers().get(actix_web::http::header::IF_NONE_MATCH) {
+        Some(matches) => match matches.to_str() {
+            Ok(matches) => matches.split(',').any(|matches| matches.trim() == etag || matches.trim() == "*"),
+            Err(_) => false
+        },
+        None => match req.headers().get(actix_web::http::header::IF_MODIFIED_SINCE) {
+            Some(since) => match since.to_str().map(chrono::DateTime::parse_from_rfc2822) {
+                Ok(Ok(since)) => tile.created.timestamp() <= since.timestamp(),
+                _ => false
+            },
+            None => false
+        }
+    };
+
+    let mut res = if not_modified {
+        HttpResponse::build(actix_web::http::StatusCode::NOT_MODIFIED)
+    } else {
+        HttpResponse::build(actix_web::http::StatusCode::OK)
+    };
+
+    res.header(actix_web::http::header::ETAG, etag)
+        .header(actix_web::http::header::LAST_MODIFIED, tile.last_modified());
+
+    if not_modified {
+        res.finish()
+    } else {
+        res.content_type("application/x-protobuf")
+            .content_length(tile.tile.len() as u64)
+            .body(tile.tile)
+    }
+}
 
 fn mvt_meta(
     conn: web::Data<DbReplica>,
@@ -498,6 +787,7 @@ fn mvt_regen(
     conn_read: web::Data<DbReplica>,
     auth: auth::Auth,
     auth_rules: web::Data<auth::AuthContainer>,
+    tiles: web::Data<mvt::Config>,
     path: web::Path<(u8, u32, u32)>
 ) -> impl Future<Item = HttpResponse, Error = HecateError> {
     web::block(move || {
@@ -509,18 +799,168 @@ fn mvt_regen(
 
         if z > 17 { return Err(HecateError::new(404, String::from("Tile Not Found"), None)); }
 
-        Ok(mvt::get(&*conn_read.get()?, &*conn_write.get()?, z, x, y, true)?)
-    }).then(|res: Result<Vec<u8>, actix_threadpool::BlockingError<HecateError>>| match res {
+        Ok(mvt::get(&*conn_read.get()?, &*conn_write.get()?, &tiles, z, x, y, true)?)
+    }).then(|res: Result<mvt::Tile, actix_threadpool::BlockingError<HecateError>>| match res {
         Ok(tile) => {
             Ok(HttpResponse::build(actix_web::http::StatusCode::OK)
                 .content_type("application/x-protobuf")
-                .content_length(tile.len() as u64)
-                .body(tile))
+                .header(actix_web::http::header::ETAG, tile.etag())
+                .header(actix_web::http::header::LAST_MODIFIED, tile.last_modified())
+                .content_length(tile.tile.len() as u64)
+                .body(tile.tile))
+        },
+        Err(err) => Ok(HecateError::from(err).error_response())
+    })
+}
+
+fn mvt_tilejson(
+    conn: web::Data<DbReplica>,
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    tiles: web::Data<mvt::Config>,
+    schema: web::Data<Option<serde_json::value::Value>>,
+    req: HttpRequest
+) -> impl Future<Item = HttpResponse, Error = HecateError> {
+    // The original path retains any /token/{token} prefix, which the
+    // tile url must also carry for clients to be able to use it
+    let prefix = match req.path().find("/api/tiles/tilejson.json") {
+        Some(end) => String::from(&req.path()[..end]),
+        None => String::from("")
+    };
+
+    let url = format!("{}://{}{}/api/tiles/{{z}}/{{x}}/{{y}}", req.connection_info().scheme(), req.connection_info().host(), prefix);
+
+    web::block(move || {
+        auth::check(&auth_rules.0.mvt.get, auth::RW::Read, &auth)?;
+
+        mvt::tilejson(&*conn.get()?, &tiles, schema.get_ref(), url)
+    }).then(|res: Result<serde_json::Value, actix_threadpool::BlockingError<HecateError>>| match res {
+        Ok(tilejson) => Ok(actix_web::HttpResponse::Ok().json(tilejson)),
+        Err(err) => Ok(HecateError::from(err).error_response())
+    })
+}
+
+fn mvt_export(
+    conn: web::Data<DbReplica>,
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    tiles: web::Data<mvt::Config>,
+    schema: web::Data<Option<serde_json::value::Value>>,
+    opts: web::Query<ExportOpts>,
+    req: HttpRequest
+) -> impl Future<Item = HttpResponse, Error = HecateError> {
+    web::block(move || {
+        auth::check(&auth_rules.0.mvt.export, auth::RW::Read, &auth)?;
+
+        let format = mvt::export::Format::parse(opts.format.as_ref().map(String::as_str).unwrap_or("mbtiles"))?;
+
+        let (minzoom, maxzoom) = mvt::zooms(opts.minzoom, opts.maxzoom)?;
+
+        let bbox: Option<Vec<f64>> = match &opts.bbox {
+            None => None,
+            Some(bbox) => match bbox.split(',').map(|s| s.parse()).collect() {
+                Ok(bbox) => Some(bbox),
+                Err(_) => { return Err(HecateError::new(400, String::from("Invalid BBOX"), None)); }
+            }
+        };
+
+        let conn = conn.get()?;
+
+        let (geom, mut extent) = mvt::area(&*conn, &bbox, &opts.bounds)?;
+
+        mvt::limit(&tiles, &extent, minzoom, maxzoom)?;
+
+        // Limit the archive bounds to the extent of the data where it is known
+        if let Some(stats) = mvt::extent(&*conn)? {
+            if stats[0] <= extent[2] && stats[2] >= extent[0] && stats[1] <= extent[3] && stats[3] >= extent[1] {
+                extent = vec![
+                    extent[0].max(stats[0]),
+                    extent[1].max(stats[1]),
+                    extent[2].min(stats[2]),
+                    extent[3].min(stats[3])
+                ];
+            }
+        }
+
+        let metadata = mvt::export::Metadata {
+            bounds: extent,
+            minzoom,
+            maxzoom,
+            vector_layers: tiles.vector_layers(schema.get_ref())
+        };
+
+        let file = mvt::export::export(&*conn, &tiles, &format, &mvt::cover(&geom, minzoom, maxzoom)?, &metadata)?;
+
+        Ok((file, format!("hecate.{}", format.extension())))
+    }).then(move |res: Result<(std::fs::File, String), actix_threadpool::BlockingError<HecateError>>| match res {
+        Ok((file, filename)) => match actix_files::NamedFile::from_file(file, filename) {
+            Ok(file) => match actix_web::Responder::respond_to(file, &req) {
+                Ok(res) => Ok(res),
+                Err(err) => Ok(HecateError::new(500, String::from("Failed to read export"), Some(err.to_string())).error_response())
+            },
+            Err(err) => Ok(HecateError::new(500, String::from("Failed to read export"), Some(err.to_string())).error_response())
         },
         Err(err) => Ok(HecateError::from(err).error_response())
     })
 }
 
+fn mvt_seed(
+    conn: web::Data<DbReplica>,
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    worker: web::Data<worker::Worker>,
+    opts: Json<SeedOpts>
+) -> impl Future<Item = HttpResponse, Error = HecateError> {
+    web::block(move || {
+        auth::check(&auth_rules.0.mvt.seed, auth::RW::Full, &auth)?;
+
+        let opts = opts.into_inner();
+
+        let (minzoom, maxzoom) = mvt::zooms(opts.minzoom, opts.maxzoom)?;
+
+        let (geom, _) = mvt::area(&*conn.get()?, &opts.bbox, &opts.bounds)?;
+
+        Ok(worker.seeder.queue(geom, opts.bbox, opts.bounds, minzoom, maxzoom)?.to_value())
+    }).then(|res: Result<serde_json::Value, actix_threadpool::BlockingError<HecateError>>| match res {
+        Ok(seed) => Ok(actix_web::HttpResponse::Ok().json(seed)),
+        Err(err) => Ok(HecateError::from(err).error_response())
+    })
+}
+
+fn mvt_seed_list(
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    worker: web::Data<worker::Worker>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    auth::check(&auth_rules.0.mvt.seed, auth::RW::Read, &auth)?;
+
+    let seeds: Vec<serde_json::Value> = worker.seeder.list().iter().map(|seed| seed.to_value()).collect();
+
+    Ok(Json(json!(seeds)))
+}
+
+fn mvt_seed_get(
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    worker: web::Data<worker::Worker>,
+    id: web::Path<i64>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    auth::check(&auth_rules.0.mvt.seed, auth::RW::Read, &auth)?;
+
+    Ok(Json(worker.seeder.get(id.into_inner())?.to_value()))
+}
+
+fn mvt_seed_cancel(
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    worker: web::Data<worker::Worker>,
+    id: web::Path<i64>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    auth::check(&auth_rules.0.mvt.seed, auth::RW::Full, &auth)?;
+
+    Ok(Json(worker.seeder.cancel(id.into_inner())?.to_value()))
+}
+
 fn user_create(
     conn: web::Data<DbReadWrite>,
     auth: auth::Auth,
@@ -929,6 +1369,37 @@ fn style_get(
     Ok(Json(json!(style::get(&*conn.get()?, &auth.uid, style_id)?)))
 }
 
+fn style_tile(
+    conn_write: web::Data<DbReadWrite>,
+    conn_read: web::Data<DbReplica>,
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    tiles: web::Data<mvt::Config>,
+    path: web::Path<(i64, u8, u32, u32)>
+) -> impl Future<Item = HttpResponse, Error = HecateError> {
+    web::block(move || {
+        auth::check(&auth_rules.0.style.get, auth::RW::Read, &auth)?;
+        auth::check(&auth_rules.0.mvt.get, auth::RW::Read, &auth)?;
+
+        let (style_id, z, x, y) = path.into_inner();
+
+        if z > 17 { return Err(HecateError::new(404, String::from("Tile Not Found"), None)); }
+
+        let conn = conn_read.get()?;
+
+        let style = style::get(&*conn, &auth.uid, style_id)?;
+        let tile = mvt::get(&*conn, &*conn_write.get()?, &tiles, z, x, y, false)?;
+
+        style::render::render(&style["style"], &tile.tile, z)
+    }).then(|res: Result<Vec<u8>, actix_threadpool::BlockingError<HecateError>>| match res {
+        Ok(png) => Ok(HttpResponse::build(actix_web::http::StatusCode::OK)
+            .content_type("image/png")
+            .content_length(png.len() as u64)
+            .body(png)),
+        Err(err) => Ok(HecateError::from(err).error_response())
+    })
+}
+
 fn style_list_public(
     conn: web::Data<DbReplica>,
     auth: auth::Auth,
@@ -972,8 +1443,40 @@ fn delta_list(
     web::block(move || {
         auth::check(&auth_rules.0.delta.list, auth::RW::Read, &auth)?;
 
+        if opts.bbox.is_some() && opts.bounds.is_some() {
+            return Err(HecateError::new(400, String::from("bbox and bounds params cannot be used together"), None));
+        }
+
+        let bbox: Option<Vec<f64>> = match &opts.bbox {
+            None => None,
+            Some(bbox) => {
+                let bbox: Vec<f64> = match bbox.split(',').map(|s| s.parse()).collect() {
+                    Ok(bbox) => bbox,
+                    Err(_) => { return Err(HecateError::new(400, String::from("Invalid BBOX"), None)); }
+                };
+
+                validate::bbox(&bbox)?;
+
+                Some(bbox)
+            }
+        };
+
+        if let Some(ref name) = opts.bounds {
+            if !bounds::exists(&*conn.get()?, name)? {
+                return Err(HecateError::new(404, String::from("Bounds Not Found"), None));
+            }
+        }
+
+        let filter = delta::ListFilter {
+            uid: opts.uid,
+            bbox,
+            bounds: opts.bounds.clone(),
+            feature: opts.feature,
+            message: opts.message.clone()
+        };
+
         if opts.offset.is_none() && opts.limit.is_none() && opts.start.is_none() && opts.end.is_none() {
-            Ok(delta::list_by_offset(&*conn.get()?, None, None)?)
+            Ok(delta::list_by_offset(&*conn.get()?, None, None, &filter)?)
         } else if opts.offset.is_some() && (opts.start.is_some() || opts.end.is_some()) {
             Err(HecateError::new(400, String::from("Offset cannot be used with start or end"), None))
         } else if opts.start.is_some() || opts.end.is_some() {
@@ -997,9 +1500,9 @@ fn delta_list(
                 }
             };
 
-            Ok(delta::list_by_date(&*conn.get()?, start, end, opts.limit)?)
+            Ok(delta::list_by_date(&*conn.get()?, start, end, opts.limit, &filter)?)
         } else if opts.offset.is_some() || opts.limit.is_some() {
-            Ok(delta::list_by_offset(&*conn.get()?, opts.offset, opts.limit)?)
+            Ok(delta::list_by_offset(&*conn.get()?, opts.offset, opts.limit, &filter)?)
         } else {
             Err(HecateError::new(400, String::from("Invalid Query Params"), None))
         }
@@ -1025,12 +1528,127 @@ fn delta(
     })
 }
 
-fn bounds(
+fn delta_diff(
     conn: web::Data<DbReplica>,
-    auth:
-    auth::Auth,
+    auth: auth::Auth,
     auth_rules: web::Data<auth::AuthContainer>,
-    filter: web::Query<Filter>
+    id: web::Path<i64>
+) -> impl Future<Item = HttpResponse, Error = HecateError> {
+    web::block(move || {
+        auth::check(&auth_rules.0.delta.get, auth::RW::Read, &auth)?;
+
+        Ok(delta::diff(&*conn.get()?, id.into_inner())?)
+    }).then(|res: Result<serde_json::Value, actix_threadpool::BlockingError<HecateError>>| match res {
+        Ok(diff) => Ok(actix_web::HttpResponse::Ok().json(diff)),
+        Err(err) => Ok(HecateError::from(err).error_response())
+    })
+}
+
+fn delta_osc(
+    conn: web::Data<DbReplica>,
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    id: web::Path<i64>
+) -> impl Future<Item = HttpResponse, Error = HecateError> {
+    web::block(move || {
+        auth::check(&auth_rules.0.delta.get, auth::RW::Read, &auth)?;
+
+        let versions = delta::versions(&*conn.get()?, id.into_inner())?;
+
+        match osm::to_osmchange(&versions) {
+            Ok(xml_str) => Ok(xml_str),
+            Err(err) => Err(HecateError::new(417, String::from("Expectation Failed"), Some(err.to_string())))
+        }
+    }).then(|res: Result<String, actix_threadpool::BlockingError<HecateError>>| match res {
+        Ok(osc) => Ok(actix_web::HttpResponse::Ok()
+            .content_type("application/xml")
+            .body(osc)),
+        Err(err) => Ok(HecateError::from(err).error_response())
+    })
+}
+
+fn delta_revert(
+    conn: web::Data<DbReadWrite>,
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    worker: web::Data<worker::Worker>,
+    schema: web::Data<Option<serde_json::value::Value>>,
+    id: web::Path<i64>
+) -> impl Future<Item = HttpResponse, Error = HecateError> {
+    web::block(move || {
+        auth::check(&auth_rules.0.feature.create, auth::RW::Full, &auth)?;
+
+        let uid = auth.uid.unwrap();
+        let reverted = id.into_inner();
+
+        let conn = conn.get()?;
+
+        let trans = match conn.transaction() {
+            Ok(trans) => trans,
+            Err(err) => { return Err(HecateError::new(500, String::from("Failed to open transaction"), Some(err.to_string()))); }
+        };
+
+        let fc = match delta::revert(&trans, reverted) {
+            Ok(fc) => fc,
+            Err(err) => {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(err);
+            }
+        };
+
+        let mut map: HashMap<String, Option<String>> = HashMap::new();
+        map.insert(String::from("message"), Some(format!("Revert delta {}", reverted)));
+        map.insert(String::from("revert"), Some(reverted.to_string()));
+
+        let delta_id = match delta::open(&trans, &map, uid) {
+            Ok(id) => id,
+            Err(err) => {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(err);
+            }
+        };
+
+        for feat in &fc.features {
+            if let Err(err) = feature::action(&trans, &schema, feat, &None) {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(err);
+            }
+        }
+
+        if let Err(err) = delta::modify(delta_id, &trans, &fc, uid) {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        }
+
+        if let Err(err) = delta::finalize(delta_id, &trans) {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        }
+
+        if trans.commit().is_err() {
+            return Err(HecateError::new(500, String::from("Failed to commit transaction"), None));
+        }
+
+        worker.queue(worker::Task::new(worker::TaskType::Delta(delta_id)));
+
+        Ok(json!(delta_id))
+    }).then(|res: Result<serde_json::Value, actix_threadpool::BlockingError<HecateError>>| match res {
+        Ok(delta_id) => Ok(actix_web::HttpResponse::Ok().json(delta_id)),
+        Err(err) => Ok(HecateError::from(err).error_response())
+    })
+}
+
+fn bounds(
+    conn: web::Data<DbReplica>,
+    auth:
+    auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    filter: web::Query<Filter>
 ) -> impl Future<Item = HttpResponse, Error = HecateError> {
     web::block(move || {
         auth::check(&auth_rules.0.bounds.list, auth::RW::Read, &auth)?;
@@ -1220,12 +1838,36 @@ fn clone_query(
 fn clone_get(
     conn: web::Data<DbReplica>,
     auth: auth::Auth,
-    auth_rules: web::Data<auth::AuthContainer>
+    auth_rules: web::Data<auth::AuthContainer>,
+    opts: web::Query<CloneAt>
 ) -> Result<HttpResponse, HecateError> {
     auth::check(&auth_rules.0.clone.get, auth::RW::Read, &auth)?;
 
+    let at = match (opts.delta, &opts.timestamp) {
+        (Some(_), Some(_)) => {
+            return Err(HecateError::new(400, String::from("delta and timestamp params cannot be used together"), None));
+        },
+        (Some(delta), None) => {
+            if delta < 1 {
+                return Err(HecateError::new(400, String::from("Delta id must be greater than 0"), None));
+            }
+
+            Some(delta::At::Delta(delta))
+        },
+        (None, Some(timestamp)) => Some(delta::At::Timestamp(validate::timestamp(timestamp)?)),
+        (None, None) => None
+    };
+
+    if at.is_some() {
+        auth::check(&auth_rules.0.feature.history, auth::RW::Read, &auth)?;
+    }
+
     let mut resp = HttpResponse::build(actix_web::http::StatusCode::OK);
-    Ok(resp.streaming(clone::get(conn.get()?)?))
+
+    match at {
+        Some(at) => Ok(resp.streaming(clone::get_at(conn.get()?, &at)?)),
+        None => Ok(resp.streaming(clone::get(conn.get()?)?))
+    }
 }
 
 fn features_query(
@@ -1326,12 +1968,39 @@ fn stats_regen(
     })
 }
 
+fn features_uploads(
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    uploads: web::Data<feature::Uploads>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    auth::check(&auth_rules.0.feature.create, auth::RW::Read, &auth)?;
+
+    let uploads: Vec<serde_json::Value> = uploads.list().iter().map(|upload| upload.to_value()).collect();
+
+    Ok(Json(json!(uploads)))
+}
+
+fn features_upload_get(
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    uploads: web::Data<feature::Uploads>,
+    delta: web::Path<i64>
+) -> Result<Json<serde_json::Value>, HecateError> {
+    auth::check(&auth_rules.0.feature.create, auth::RW::Read, &auth)?;
+
+    Ok(Json(uploads.get(delta.into_inner())?.to_value()))
+}
+
+#[allow(clippy::too_many_arguments)]
 fn features_action(
     auth: auth::Auth,
     auth_rules: web::Data<auth::AuthContainer>,
     conn: web::Data<DbReadWrite>,
     worker: web::Data<worker::Worker>,
+    uploads: web::Data<feature::Uploads>,
     schema: web::Data<Option<serde_json::value::Value>>,
+    opts: web::Query<FeaturesOpts>,
+    req: HttpRequest,
     body: web::Payload
 ) -> impl Future<Item = Json<serde_json::Value>, Error = HecateError> {
     let conn = match conn.get() {
@@ -1344,8 +2013,42 @@ fn features_action(
     }
 
     let uid = auth.uid.unwrap();
+    let dry_run = opts.dry_run.unwrap_or(false);
+    let atomic = opts.atomic.unwrap_or(true);
+
+    if req.content_type() == "application/geo+json-seq" {
+        let delta_message = match opts.message {
+            Some(ref message) => message.clone(),
+            None => {
+                return Either::A(futures::future::err(HecateError::new(400, String::from("message param required for line-delimited uploads"), None)));
+            }
+        };
 
-    Either::B(body.map_err(HecateError::from).fold(bytes::BytesMut::new(), move |mut body, chunk| {
+        let mut map: HashMap<String, Option<String>> = HashMap::new();
+        map.insert(String::from("message"), Some(delta_message));
+
+        let force = auth::check(&auth_rules.0.feature.force, auth::RW::Full, &auth).is_ok();
+
+        let ingest = match feature::Ingest::new(conn, &uploads, &map, uid, schema.get_ref().clone(), atomic, force) {
+            Ok(ingest) => ingest,
+            Err(err) => { return Either::A(futures::future::err(err)); }
+        };
+
+        return Either::B(Either::A(body.map_err(HecateError::from).fold(ingest, move |mut ingest, chunk| {
+            ingest.push(&chunk)?;
+            Ok::<_, HecateError>(ingest)
+        }).and_then(move |ingest| {
+            let summary = ingest.finish(dry_run)?;
+
+            if let Some(delta_id) = summary["delta"].as_i64() {
+                worker.queue(worker::Task::new(worker::TaskType::Delta(delta_id)));
+            }
+
+            Ok(Json(summary))
+        })));
+    }
+
+    Either::B(Either::B(body.map_err(HecateError::from).fold(bytes::BytesMut::new(), move |mut body, chunk| {
         body.extend_from_slice(&chunk);
         Ok::<_, HecateError>(body)
     }).and_then(move |body| {
@@ -1391,6 +2094,75 @@ fn features_action(
             }
         };
 
+        if dry_run || !atomic {
+            let mut results: Vec<serde_json::Value> = Vec::new();
+            let mut applied: Vec<geojson::Feature> = Vec::new();
+
+            for (index, feat) in fc.features.iter().enumerate() {
+                let res = match feature::is_force(feat) {
+                    Err(err) => Err(err),
+                    Ok(force) => {
+                        if force {
+                            auth::check(&auth_rules.0.feature.force, auth::RW::Full, &auth)?;
+                        }
+
+                        feature::action_savepoint(&trans, &schema, feat, &None)
+                    }
+                };
+
+                results.push(match res {
+                    Ok(res) => {
+                        let mut feat = feat.clone();
+                        if res.new.is_some() {
+                            feat.id = Some(geojson::feature::Id::Number(serde_json::Number::from(res.new.unwrap())))
+                        }
+                        applied.push(feat);
+
+                        json!({
+                            "index": index,
+                            "outcome": "success",
+                            "id": res.new.or(res.old),
+                            "version": res.version
+                        })
+                    },
+                    Err(err) => json!({
+                        "index": index,
+                        "outcome": "failure",
+                        "error": err.as_json()
+                    })
+                });
+            }
+
+            if dry_run || applied.is_empty() {
+                trans.set_rollback();
+                trans.finish().unwrap();
+
+                return Ok(Json(json!(results)));
+            }
+
+            fc.features = applied;
+
+            if let Err(err) = delta::modify(delta_id, &trans, &fc, uid) {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(err);
+            }
+
+            if let Err(err) = delta::finalize(delta_id, &trans) {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(err);
+            }
+
+            if trans.commit().is_err() {
+                return Err(HecateError::new(500, String::from("Failed to commit transaction"), None));
+            }
+
+            worker.queue(worker::Task::new(worker::TaskType::Delta(delta_id)));
+
+            return Ok(Json(json!(results)));
+        }
+
         for feat in &mut fc.features {
             match feature::is_force(&feat) {
                 Err(err) => {
@@ -1439,7 +2211,139 @@ fn features_action(
                 Err(err)
             }
         }
-    }))
+    })))
+}
+
+///
+/// Add the existing nodes & ways referenced by an osmChange but not part of it,
+/// synthesized from the stored features they belong to
+///
+fn osm_resolve(trans: &postgres::transaction::Transaction, tree: &mut osm::OSMTree) -> Result<(), HecateError> {
+    let mut previous: Vec<(osm::Value, i64)> = Vec::new();
+
+    loop {
+        let missing = osm::missing(tree);
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        if let Some((element, id)) = missing.iter().find(|element| previous.contains(element)) {
+            return Err(HecateError::new(404, String::from("Element Not Found"), Some(format!("{:?} {} is not part of a stored feature", element, id))));
+        }
+
+        let mut features: Vec<i64> = missing.iter().map(|(element, id)| osm::feature_id(element, *id)).collect();
+        features.sort();
+        features.dedup();
+
+        for id in features {
+            if let Err(err) = osm::add_stored(tree, &feature::get(trans, id)?, &missing) {
+                return Err(HecateError::new(417, err.to_string(), None));
+            }
+        }
+
+        previous = missing;
+    }
+}
+
+///
+/// Modify the feature of each stored relation with a modified synthesized way,
+/// unless the relation is already part of the upload
+///
+/// A modified way must have the version of the feature it was synthesized from
+///
+fn osm_way_modifies(fc: &geojson::FeatureCollection, tree: &osm::OSMTree) -> Result<Vec<geojson::Feature>, HecateError> {
+    let uploaded: Vec<i64> = fc.features.iter().filter_map(|feat| feature::get_id(feat).ok()).collect();
+
+    let modifies = match osm::way_modifies(tree) {
+        Ok(modifies) => modifies,
+        Err(err) => { return Err(HecateError::new(417, err.to_string(), None)); }
+    };
+
+    let mut rebuilt: Vec<geojson::Feature> = Vec::new();
+
+    for (way, version, feat) in modifies {
+        let id = feature::get_id(&feat)?;
+
+        if feature::get_version(&feat)? != version {
+            return Err(HecateError::new(409, String::from("Modify Version Mismatch"), Some(format!("Way {} is not at version {}", way, version))));
+        }
+
+        if uploaded.contains(&id) || rebuilt.iter().any(|feat| feature::get_id(feat).ok() == Some(id)) {
+            continue;
+        }
+
+        rebuilt.push(feat);
+    }
+
+    Ok(rebuilt)
+}
+
+///
+/// Ensure each deleted synthesized node or way belongs to a feature that is part of the upload
+///
+/// Vertices & rings are removed by modifying their feature, a delete alone can't be applied
+///
+fn osm_synthesized_deletes(fc: &geojson::FeatureCollection, tree: &osm::OSMTree) -> Result<(), HecateError> {
+    let uploaded: Vec<i64> = fc.features.iter().filter_map(|feat| feature::get_id(feat).ok()).collect();
+
+    for (element, id, feature) in osm::synthesized_deletes(tree) {
+        if !uploaded.contains(&feature) {
+            return Err(HecateError::new(400, String::from("Invalid Delete"), Some(format!("{:?} {} can only be deleted by modifying feature {}", element, id, feature))));
+        }
+    }
+
+    Ok(())
+}
+
+///
+/// Modify each feature with a vertex at the old position of a moved node,
+/// unless the feature is already part of the upload
+///
+/// A moved node must have the version of the feature it was synthesized from
+///
+fn osm_vertex_moves(trans: &postgres::transaction::Transaction, fc: &geojson::FeatureCollection, tree: &osm::OSMTree) -> Result<Vec<geojson::Feature>, HecateError> {
+    let uploaded: Vec<i64> = fc.features.iter().filter_map(|feat| feature::get_id(feat).ok()).collect();
+
+    let mut moved: Vec<geojson::Feature> = Vec::new();
+
+    for (id, index, version, to) in osm::vertex_moves(tree) {
+        let current = feature::get(trans, id)?;
+
+        if feature::get_version(&current)? != version {
+            return Err(HecateError::new(409, String::from("Modify Version Mismatch"), Some(format!("Feature {} is not at version {}", id, version))));
+        }
+
+        let from = match osm::vertex_position(&current, index) {
+            Some(from) => from,
+            None => { return Err(HecateError::new(417, String::from("Vertex Not Found"), Some(format!("Feature {} has no vertex {}", id, index)))); }
+        };
+
+        for feat in feature::get_bbox(trans, vec![from[0], from[1], from[0], from[1]])?.features {
+            let feat_id = feature::get_id(&feat)?;
+
+            if uploaded.contains(&feat_id) {
+                continue;
+            }
+
+            match moved.iter_mut().find(|feat| feature::get_id(feat).ok() == Some(feat_id)) {
+                Some(existing) => { osm::move_vertex(existing, &from, &to); },
+                None => {
+                    let mut feat = feat;
+
+                    if osm::move_vertex(&mut feat, &from, &to) {
+                        if let Some(ref mut members) = feat.foreign_members {
+                            members.insert(String::from("action"), json!("modify"));
+                        }
+
+                        moved.push(feat);
+                    }
+                }
+            };
+        }
+    }
+
+    Ok(moved)
 }
 
 fn osm_map(
@@ -1652,11 +2556,46 @@ fn osm_changeset_upload(
             }
         }
 
-        let (mut fc, tree) = match osm::to_features(&body) {
-            Ok(fctree) => fctree,
+        let mut tree = match osm::tree_parser(&body) {
+            Ok(tree) => tree,
             Err(err) => { return Err(HecateError::new(417, err.to_string(), None)); }
         };
 
+        if let Err(err) = osm_resolve(&trans, &mut tree) {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        }
+
+        let mut fc = match osm::to_features(&tree) {
+            Ok(fc) => fc,
+            Err(err) => { return Err(HecateError::new(417, err.to_string(), None)); }
+        };
+
+        match osm_way_modifies(&fc, &tree) {
+            Ok(rebuilt) => fc.features.extend(rebuilt),
+            Err(err) => {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(err);
+            }
+        };
+
+        match osm_vertex_moves(&trans, &fc, &tree) {
+            Ok(moved) => fc.features.extend(moved),
+            Err(err) => {
+                trans.set_rollback();
+                trans.finish().unwrap();
+                return Err(err);
+            }
+        };
+
+        if let Err(err) = osm_synthesized_deletes(&fc, &tree) {
+            trans.set_rollback();
+            trans.finish().unwrap();
+            return Err(err);
+        }
+
         let mut ids: HashMap<i64, feature::Response> = HashMap::new();
 
         for feat in &mut fc.features {
@@ -1744,23 +2683,237 @@ fn osm_capabilities(
 }
 
 fn osm_user(
+    conn: web::Data<DbReplica>,
     auth: auth::Auth,
     auth_rules: web::Data<auth::AuthContainer>
 ) -> Result<String, HecateError> {
     auth::check(&auth_rules.0.osm.get, auth::RW::Read, &auth)?;
 
-    Ok(String::from("
-        <osm version=\"0.6\" generator=\"Hecate Server\">
-            <user id=\"1\" display_name=\"user\" account_created=\"2010-06-18T12:34:58Z\">
-                <description></description>
-                <languages><lang>en-US</lang><lang>en</lang></languages>
-                <messages>
-                    <recieved county=\"0\" unread=\"0\"/>
-                    <send count=\"0\"/>
-                </messages>
-            </user>
-        </osm>
-    "))
+    let uid = match auth.uid {
+        Some(uid) => uid,
+        None => { return Err(HecateError::generic(401)); }
+    };
+
+    let user = user::User::get(&*conn.get()?, uid)?;
+
+    match osm::user_details(uid, &user.username) {
+        Ok(xml_str) => Ok(xml_str),
+        Err(err) => Err(HecateError::new(500, String::from("Could not format user XML"), Some(err.to_string())))
+    }
+}
+
+fn osm_changeset_get(
+    conn: web::Data<DbReplica>,
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    delta_id: web::Path<i64>
+) -> Result<String, HecateError> {
+    auth::check(&auth_rules.0.osm.get, auth::RW::Read, &auth)?;
+
+    let deltas = delta::changesets(&*conn.get()?, Some(delta_id.into_inner()), None, None, &delta::ListFilter::default())?;
+
+    if deltas.as_array().map_or(true, |deltas| deltas.is_empty()) {
+        return Err(HecateError::new(404, String::from("Changeset Not Found"), None));
+    }
+
+    match osm::changesets(&deltas) {
+        Ok(xml_str) => Ok(xml_str),
+        Err(err) => Err(HecateError::new(500, String::from("Could not format changeset XML"), Some(err.to_string())))
+    }
+}
+
+fn osm_changeset_download(
+    conn: web::Data<DbReplica>,
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    delta_id: web::Path<i64>
+) -> Result<String, HecateError> {
+    auth::check(&auth_rules.0.osm.get, auth::RW::Read, &auth)?;
+
+    let versions = delta::versions(&*conn.get()?, delta_id.into_inner())?;
+
+    match osm::to_osmchange(&versions) {
+        Ok(xml_str) => Ok(xml_str),
+        Err(err) => Err(HecateError::new(417, String::from("Expectation Failed"), Some(err.to_string())))
+    }
+}
+
+///
+/// Query changesets by user, bbox & time, where time is either a single
+/// timestamp changesets must be created after, or a comma separated pair
+/// of timestamps changesets must be created between
+///
+fn osm_changesets(
+    conn: web::Data<DbReplica>,
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    query: web::Query<Changesets>
+) -> Result<String, HecateError> {
+    auth::check(&auth_rules.0.osm.get, auth::RW::Read, &auth)?;
+
+    let bbox: Option<Vec<f64>> = match &query.bbox {
+        None => None,
+        Some(bbox) => {
+            let bbox: Vec<f64> = match bbox.split(',').map(|s| s.parse()).collect() {
+                Ok(bbox) => bbox,
+                Err(_) => { return Err(HecateError::new(400, String::from("Invalid BBOX"), None)); }
+            };
+
+            validate::bbox(&bbox)?;
+
+            Some(bbox)
+        }
+    };
+
+    let mut times: Vec<chrono::NaiveDateTime> = Vec::new();
+    if let Some(time) = &query.time {
+        for time in time.split(',') {
+            times.push(validate::timestamp(time)?);
+        }
+
+        if times.len() > 2 {
+            return Err(HecateError::new(400, String::from("Invalid Time"), None));
+        }
+    }
+
+    let filter = delta::ListFilter {
+        uid: query.user,
+        bbox,
+        ..Default::default()
+    };
+
+    let deltas = delta::changesets(&*conn.get()?, None, times.first().cloned(), times.get(1).cloned(), &filter)?;
+
+    match osm::changesets(&deltas) {
+        Ok(xml_str) => Ok(xml_str),
+        Err(err) => Err(HecateError::new(500, String::from("Could not format changeset XML"), Some(err.to_string())))
+    }
+}
+
+///
+/// Load the features the given features are synthesized from, along with every
+/// feature that could share a vertex with them, so that nodes are shared as in /map
+///
+fn osm_features(conn: &impl postgres::GenericConnection, ids: &[i64]) -> Result<geojson::FeatureCollection, HecateError> {
+    let mut fc = geojson::FeatureCollection {
+        bbox: None,
+        features: Vec::new(),
+        foreign_members: None
+    };
+
+    for id in ids {
+        let extent = match osm::extent(&feature::get(conn, *id)?) {
+            Some(extent) => extent,
+            None => { return Err(HecateError::new(404, String::from("Not Found"), None)); }
+        };
+
+        for feat in feature::get_bbox(conn, extent)?.features {
+            let feat_id = feature::get_id(&feat)?;
+
+            if !fc.features.iter().any(|feat| feature::get_id(feat).ok() == Some(feat_id)) {
+                fc.features.push(feat);
+            }
+        }
+    }
+
+    Ok(fc)
+}
+
+fn osm_node(
+    conn: web::Data<DbReplica>,
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    id: web::Path<i64>
+) -> Result<String, HecateError> {
+    auth::check(&auth_rules.0.osm.get, auth::RW::Read, &auth)?;
+
+    let id = id.into_inner();
+
+    let fc = osm_features(&*conn.get()?, &[osm::feature_id(&osm::Value::Node, id)])?;
+
+    match osm::nodes(&fc, &[id]) {
+        Ok(Some(xml_str)) => Ok(xml_str),
+        Ok(None) => Err(HecateError::new(404, String::from("Not Found"), None)),
+        Err(err) => Err(HecateError::new(417, String::from("Expectation Failed"), Some(err.to_string())))
+    }
+}
+
+fn osm_nodes(
+    conn: web::Data<DbReplica>,
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    query: web::Query<Nodes>
+) -> Result<String, HecateError> {
+    auth::check(&auth_rules.0.osm.get, auth::RW::Read, &auth)?;
+
+    let ids: Vec<i64> = match query.nodes.split(',').map(|id| id.trim().parse()).collect() {
+        Ok(ids) => ids,
+        Err(_) => { return Err(HecateError::new(400, String::from("Invalid Node Ids"), None)); }
+    };
+
+    let mut features: Vec<i64> = ids.iter().map(|id| osm::feature_id(&osm::Value::Node, *id)).collect();
+    features.sort();
+    features.dedup();
+
+    let fc = osm_features(&*conn.get()?, &features)?;
+
+    match osm::nodes(&fc, &ids) {
+        Ok(Some(xml_str)) => Ok(xml_str),
+        Ok(None) => Err(HecateError::new(404, String::from("Not Found"), None)),
+        Err(err) => Err(HecateError::new(417, String::from("Expectation Failed"), Some(err.to_string())))
+    }
+}
+
+fn osm_way_full(
+    conn: web::Data<DbReplica>,
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    id: web::Path<i64>
+) -> Result<String, HecateError> {
+    auth::check(&auth_rules.0.osm.get, auth::RW::Read, &auth)?;
+
+    let id = id.into_inner();
+
+    let fc = osm_features(&*conn.get()?, &[osm::feature_id(&osm::Value::Way, id)])?;
+
+    match osm::way_full(&fc, id) {
+        Ok(Some(xml_str)) => Ok(xml_str),
+        Ok(None) => Err(HecateError::new(404, String::from("Not Found"), None)),
+        Err(err) => Err(HecateError::new(417, String::from("Expectation Failed"), Some(err.to_string())))
+    }
+}
+
+///
+/// Every version of a node, way or relation, synthesized from each version
+/// of the feature in geo_history. Vertices are not shared between features
+///
+fn osm_history(
+    conn: web::Data<DbReplica>,
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    path: web::Path<(String, i64)>
+) -> Result<String, HecateError> {
+    auth::check(&auth_rules.0.osm.get, auth::RW::Read, &auth)?;
+    auth::check(&auth_rules.0.feature.history, auth::RW::Read, &auth)?;
+
+    let element = match path.0.as_str() {
+        "node" => osm::Value::Node,
+        "way" => osm::Value::Way,
+        "relation" => osm::Value::Rel,
+        _ => { return Err(HecateError::new(404, String::from("Not Found"), None)); }
+    };
+
+    let history = match feature::history(&*conn.get()?, osm::feature_id(&element, path.1)) {
+        Ok(history) => history,
+        Err(ref err) if err.code == 400 => { return Err(HecateError::new(404, String::from("Not Found"), None)); },
+        Err(err) => { return Err(err); }
+    };
+
+    match osm::history(&history, &element, path.1) {
+        Ok(Some(xml_str)) => Ok(xml_str),
+        Ok(None) => Err(HecateError::new(404, String::from("Not Found"), None)),
+        Err(err) => Err(HecateError::new(417, String::from("Expectation Failed"), Some(err.to_string())))
+    }
 }
 
 fn feature_action(
@@ -1879,12 +3032,23 @@ fn feature_get(
     conn: web::Data<DbReplica>,
     auth: auth::Auth,
     auth_rules: web::Data<auth::AuthContainer>,
-    id: web::Path<i64>
+    id: web::Path<i64>,
+    opts: web::Query<FeatureAt>
 ) -> impl Future<Item = HttpResponse, Error = HecateError> {
     web::block(move || {
         auth::check(&auth_rules.0.feature.get, auth::RW::Read, &auth)?;
 
-        match feature::get(&*conn.get()?, id.into_inner()) {
+        // Past versions of a feature are part of its history
+        if opts.at.is_some() {
+            auth::check(&auth_rules.0.feature.history, auth::RW::Read, &auth)?;
+        }
+
+        let feature = match opts.at {
+            None => feature::get(&*conn.get()?, id.into_inner()),
+            Some(ref at) => feature::get_at(&*conn.get()?, id.into_inner(), &validate::at(at)?)
+        };
+
+        match feature {
             Ok(feature) => Ok(geojson::GeoJson::from(feature).to_string()),
             Err(err) => Err(err)
         }
@@ -1915,6 +3079,23 @@ fn feature_get_history(
     })
 }
 
+fn feature_get_diff(
+    conn: web::Data<DbReplica>,
+    auth: auth::Auth,
+    auth_rules: web::Data<auth::AuthContainer>,
+    id: web::Path<i64>,
+    opts: web::Query<FeatureDiff>
+) -> impl Future<Item = HttpResponse, Error = HecateError> {
+    web::block(move || {
+        auth::check(&auth_rules.0.feature.history, auth::RW::Read, &auth)?;
+
+        Ok(feature::diff(&*conn.get()?, id.into_inner(), opts.from, opts.to)?)
+    }).then(|res: Result<serde_json::Value, actix_threadpool::BlockingError<HecateError>>| match res {
+        Ok(diff) => Ok(actix_web::HttpResponse::Ok().json(diff)),
+        Err(err) => Ok(HecateError::from(err).error_response())
+    })
+}
+
 fn feature_query(
     conn: web::Data<DbReplica>,
     auth: auth::Auth,
@@ -74,6 +74,30 @@ fn main() {
         None => None
     };
 
+    let tiles: Option<hecate::mvt::Config> = match matched.value_of("tiles") {
+        Some(tiles_path) => {
+            let mut tiles_file = match File::open(Path::new(tiles_path)) {
+                Ok(file) => file,
+                Err(_) => panic!("Failed to open tile config file at: {}", tiles_path)
+            };
+
+            let mut tiles_str = String::new();
+
+            tiles_file.read_to_string(&mut tiles_str).unwrap();
+
+            let tiles_json: serde_json::Value = match serde_json::from_str(&tiles_str) {
+                Ok(tiles) => tiles,
+                Err(err) => panic!("Tile config file is not valid JSON: {}", err)
+            };
+
+            match hecate::mvt::Config::parse(&tiles_json) {
+                Ok(tiles) => Some(tiles),
+                Err(err) => panic!("{}", err.as_log())
+            }
+        },
+        None => None
+    };
+
     let port: Option<u16> = match matched.value_of("port") {
         Some(port) => match port.parse() {
             Ok(port) => Some(port),
@@ -105,7 +129,8 @@ fn main() {
         port,
         workers,
         schema,
-        auth
+        auth,
+        tiles
     );
 }
 
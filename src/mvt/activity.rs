@@ -0,0 +1,99 @@
+use crate::err::HecateError;
+use super::{Grid, MAX_ZOOM};
+
+///
+/// Number of heatmap cells along each side of a tile
+///
+pub static CELLS: i32 = 16;
+
+///
+/// Limit the edits counted towards activity to a time window and/or user
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct Filter {
+    pub start: Option<chrono::NaiveDateTime>,
+    pub end: Option<chrono::NaiveDateTime>,
+    pub uid: Option<i64>
+}
+
+///
+/// Create a web mercator tile with a single `activity` layer, dividing the tile into
+/// a grid of polygon cells, each with the number of edits (`count`) and distinct
+/// features edited (`features`) within it
+///
+/// Every version of a feature in geo_history is an edit, placed in the cell
+/// containing a point on the part of the feature within the tile.
+/// Cells without any edits are omitted
+///
+pub fn render(conn: &impl postgres::GenericConnection, z: u8, x: u32, y: u32, filter: &Filter) -> Result<Vec<u8>, HecateError> {
+    let grid = Grid::web_mercator();
+
+    if z > MAX_ZOOM {
+        return Err(HecateError::new(404, String::from("Tile Not Found"), None));
+    }
+
+    let (maxx, maxy) = grid.level_limit(z);
+    if x >= maxx || y >= maxy {
+        return Err(HecateError::new(404, String::from("Tile Not Found"), None));
+    }
+
+    let bbox = grid.tile_extent(z, x, y);
+    let cell: f64 = (bbox.maxx - bbox.minx) / f64::from(CELLS);
+
+    match conn.query("
+        WITH
+            edits AS (
+                SELECT
+                    geo_history.id,
+                    ST_Transform(ST_PointOnSurface(ST_ClipByBox2D(
+                        geo_history.geom,
+                        ST_Transform(ST_MakeEnvelope($1, $2, $3, $4, 3857), 4326)
+                    )), 3857) AS geom
+                FROM
+                    geo_history,
+                    deltas
+                WHERE
+                    geo_history.delta = deltas.id
+                    AND ST_Intersects(geo_history.geom, ST_Transform(ST_MakeEnvelope($1, $2, $3, $4, 3857), 4326))
+                    AND ($6::TIMESTAMP IS NULL OR deltas.created >= $6::TIMESTAMP)
+                    AND ($7::TIMESTAMP IS NULL OR deltas.created <= $7::TIMESTAMP)
+                    AND ($8::BIGINT IS NULL OR deltas.uid = $8::BIGINT)
+            ),
+            cells AS (
+                SELECT
+                    LEAST(GREATEST(FLOOR((ST_X(geom) - $1) / $5)::INT, 0), $9::INT - 1) AS cx,
+                    LEAST(GREATEST(FLOOR(($4 - ST_Y(geom)) / $5)::INT, 0), $9::INT - 1) AS cy,
+                    COUNT(*) AS count,
+                    COUNT(DISTINCT id) AS features
+                FROM
+                    edits
+                WHERE
+                    NOT ST_IsEmpty(geom)
+                GROUP BY
+                    cx,
+                    cy
+            )
+        SELECT
+            COALESCE((
+                SELECT
+                    ST_AsMVT(q, 'activity', 4096, 'geom')
+                FROM (
+                    SELECT
+                        count,
+                        features,
+                        ST_AsMVTGeom(
+                            ST_MakeEnvelope($1 + cx * $5, $4 - (cy + 1) * $5, $1 + (cx + 1) * $5, $4 - cy * $5, 3857),
+                            ST_MakeEnvelope($1, $2, $3, $4, 3857), 4096, 0, false
+                        ) AS geom
+                    FROM
+                        cells
+                ) q
+            ), ''::BYTEA)
+    ", &[&bbox.minx, &bbox.miny, &bbox.maxx, &bbox.maxy, &cell, &filter.start, &filter.end, &filter.uid, &CELLS]) {
+        Ok(res) => {
+            let tile: Vec<u8> = res.get(0).get(0);
+            Ok(tile)
+        },
+        Err(err) => Err(HecateError::from_db(err))
+    }
+}
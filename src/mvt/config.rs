@@ -0,0 +1,561 @@
+use crate::err::HecateError;
+use super::grid::{Extent, Grid, Origin, Unit};
+
+///
+/// Maximum zoom level that vector tiles are served at
+///
+pub static MAX_ZOOM: u8 = 17;
+
+///
+/// GeoJSON geometry types that a layer can be filtered to
+///
+static GEOMETRIES: [&str; 6] = ["Point", "MultiPoint", "LineString", "MultiLineString", "Polygon", "MultiPolygon"];
+
+///
+/// Property names that cannot be whitelisted as they are used
+/// internally when encoding a tile
+///
+static RESERVED: [&str; 3] = ["id", "geom", "count"];
+
+///
+/// Names of the tile matrix sets that are always available
+///
+static BUILTIN_GRIDS: [&str; 2] = ["WebMercator", "WGS84"];
+
+///
+/// Literal path segments of the tile routes, which a grid of the
+/// same name would be shadowed by in /api/tiles/{grid}/{z}/{x}/{y}
+///
+static ROUTE_SEGMENTS: [&str; 6] = ["activity", "seed", "export", "tilejson.json", "meta", "regen"];
+
+fn default_maxzoom() -> u8 {
+    MAX_ZOOM
+}
+
+fn default_budget() -> i64 {
+    500_000
+}
+
+fn default_ttl() -> i64 {
+    14_400
+}
+
+fn default_max_tiles() -> i64 {
+    100_000
+}
+
+///
+/// Vector tile configuration, controlling which layers are encoded into
+/// a tile at a given zoom & which feature properties they carry
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Config {
+    pub layers: Vec<Layer>,
+    #[serde(default = "default_budget")]
+    pub budget: i64,
+    #[serde(default)]
+    pub cluster: Cluster,
+    #[serde(default = "default_ttl")]
+    pub ttl: i64,
+    #[serde(default)]
+    pub grids: Vec<CustomGrid>,
+    #[serde(default = "default_max_tiles")]
+    pub max_tiles: i64
+}
+
+///
+/// Points within `radius` pixels of each other are clustered
+/// into a single point at or below `maxzoom`
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Cluster {
+    #[serde(default = "Cluster::default_maxzoom")]
+    pub maxzoom: u8,
+    #[serde(default = "Cluster::default_radius")]
+    pub radius: u8
+}
+
+impl Cluster {
+    fn default_maxzoom() -> u8 {
+        13
+    }
+
+    fn default_radius() -> u8 {
+        16
+    }
+}
+
+impl Default for Cluster {
+    fn default() -> Self {
+        Cluster {
+            maxzoom: Cluster::default_maxzoom(),
+            radius: Cluster::default_radius()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Layer {
+    pub name: String,
+    #[serde(default)]
+    pub minzoom: u8,
+    #[serde(default = "default_maxzoom")]
+    pub maxzoom: u8,
+    #[serde(default)]
+    pub geometry: Vec<String>,
+    #[serde(default)]
+    pub properties: Vec<String>
+}
+
+///
+/// A tile matrix set in a CRS other than web mercator, served
+/// under `/api/tiles/<name>/<z>/<x>/<y>`
+///
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct CustomGrid {
+    pub name: String,
+    pub srid: i32,
+    pub extent: Vec<f64>,
+    pub resolutions: Vec<f64>,
+    #[serde(default = "CustomGrid::default_origin")]
+    pub origin: String,
+    #[serde(default = "CustomGrid::default_units")]
+    pub units: String,
+    #[serde(default = "CustomGrid::default_size")]
+    pub size: u16
+}
+
+impl CustomGrid {
+    fn default_origin() -> String {
+        String::from("top-left")
+    }
+
+    fn default_units() -> String {
+        String::from("m")
+    }
+
+    fn default_size() -> u16 {
+        256
+    }
+
+    pub fn is_valid(&self) -> Result<bool, String> {
+        if self.name.is_empty() {
+            return Err(String::from("Tile grid name cannot be empty"));
+        } else if BUILTIN_GRIDS.contains(&self.name.as_str()) {
+            return Err(format!("{}: is a reserved grid name", self.name));
+        } else if ROUTE_SEGMENTS.contains(&self.name.as_str()) || self.name.contains('/') {
+            return Err(format!("{}: grid name conflicts with a tile route", self.name));
+        } else if self.srid <= 0 {
+            return Err(format!("{}: srid must be greater than 0", self.name));
+        } else if self.extent.len() != 4 || self.extent[0] >= self.extent[2] || self.extent[1] >= self.extent[3] {
+            return Err(format!("{}: extent must be minx, miny, maxx, maxy", self.name));
+        } else if self.resolutions.is_empty() || self.resolutions.len() > usize::from(MAX_ZOOM) + 1 {
+            return Err(format!("{}: must have between 1 and {} resolutions", self.name, MAX_ZOOM + 1));
+        } else if self.resolutions.iter().any(|res| *res <= 0.0) || self.resolutions.windows(2).any(|res| res[0] <= res[1]) {
+            return Err(format!("{}: resolutions must be positive & ordered from largest to smallest", self.name));
+        } else if self.origin != "top-left" && self.origin != "bottom-left" {
+            return Err(format!("{}: origin must be one of top-left or bottom-left", self.name));
+        } else if self.units != "m" && self.units != "dd" && self.units != "ft" {
+            return Err(format!("{}: units must be one of m, dd or ft", self.name));
+        } else if self.size == 0 {
+            return Err(format!("{}: size must be greater than 0", self.name));
+        }
+
+        Ok(true)
+    }
+
+    pub fn to_grid(&self) -> Grid {
+        Grid::new(
+            self.size,
+            Extent {
+                minx: self.extent[0],
+                miny: self.extent[1],
+                maxx: self.extent[2],
+                maxy: self.extent[3]
+            },
+            self.srid,
+            match self.units.as_str() {
+                "dd" => Unit::DD,
+                "ft" => Unit::Ft,
+                _ => Unit::M
+            },
+            self.resolutions.clone(),
+            match self.origin.as_str() {
+                "bottom-left" => Origin::BottomLeft,
+                _ => Origin::TopLeft
+            }
+        )
+    }
+}
+
+impl Default for Config {
+    ///
+    /// A single `data` layer containing every feature at every zoom,
+    /// with no properties
+    ///
+    fn default() -> Self {
+        Config {
+            layers: vec![Layer {
+                name: String::from("data"),
+                minzoom: 0,
+                maxzoom: MAX_ZOOM,
+                geometry: Vec::new(),
+                properties: Vec::new()
+            }],
+            budget: default_budget(),
+            cluster: Cluster::default(),
+            ttl: default_ttl(),
+            grids: Vec::new(),
+            max_tiles: default_max_tiles()
+        }
+    }
+}
+
+impl Config {
+    pub fn parse(value: &serde_json::Value) -> Result<Self, HecateError> {
+        let config: Config = match serde_json::from_value(value.clone()) {
+            Ok(config) => config,
+            Err(err) => { return Err(HecateError::new(400, String::from("Invalid tile config"), Some(err.to_string()))); }
+        };
+
+        if let Err(err) = config.is_valid() {
+            return Err(HecateError::new(400, err, None));
+        }
+
+        Ok(config)
+    }
+
+    pub fn is_valid(&self) -> Result<bool, String> {
+        if self.layers.is_empty() {
+            return Err(String::from("Tile config must contain at least one layer"));
+        } else if self.budget < 0 {
+            return Err(String::from("Tile budget cannot be negative"));
+        } else if self.ttl < 0 {
+            return Err(String::from("Tile ttl cannot be negative"));
+        } else if self.max_tiles <= 0 {
+            return Err(String::from("Tile max_tiles must be greater than 0"));
+        } else if self.cluster.maxzoom > MAX_ZOOM {
+            return Err(format!("Cluster maxzoom cannot be greater than {}", MAX_ZOOM));
+        }
+
+        for (i, grid) in self.grids.iter().enumerate() {
+            grid.is_valid()?;
+
+            if self.grids.iter().skip(i + 1).any(|other| other.name == grid.name) {
+                return Err(format!("{}: grid names must be unique", grid.name));
+            }
+        }
+
+        for (i, layer) in self.layers.iter().enumerate() {
+            if layer.name.is_empty() {
+                return Err(String::from("Tile layer name cannot be empty"));
+            } else if layer.maxzoom > MAX_ZOOM {
+                return Err(format!("{}: maxzoom cannot be greater than {}", layer.name, MAX_ZOOM));
+            } else if layer.minzoom > layer.maxzoom {
+                return Err(format!("{}: minzoom cannot be greater than maxzoom", layer.name));
+            }
+
+            for geometry in &layer.geometry {
+                if !GEOMETRIES.contains(&geometry.as_str()) {
+                    return Err(format!("{}: {} is not a valid geometry type", layer.name, geometry));
+                }
+            }
+
+            for property in &layer.properties {
+                if RESERVED.contains(&property.as_str()) {
+                    return Err(format!("{}: {} is a reserved property name", layer.name, property));
+                }
+            }
+
+            // Layers can share a name to vary their rules by zoom but
+            // a tile can only contain a single layer of a given name
+            for other in self.layers.iter().skip(i + 1) {
+                if other.name == layer.name && other.minzoom <= layer.maxzoom && layer.minzoom <= other.maxzoom {
+                    return Err(format!("{}: layers with the same name cannot have overlapping zooms", layer.name));
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    ///
+    /// TileJSON vector_layers describing each named layer, merging the zooms
+    /// & properties of layers that share a name
+    ///
+    /// The type of each property is read from the JSON schema, if any
+    ///
+    pub fn vector_layers(&self, schema: &Option<serde_json::Value>) -> serde_json::Value {
+        let mut vector_layers: Vec<serde_json::Value> = Vec::new();
+
+        for layer in &self.layers {
+            let existing = vector_layers.iter_mut().find(|vector_layer| vector_layer["id"] == json!(layer.name));
+
+            let vector_layer = match existing {
+                Some(vector_layer) => vector_layer,
+                None => {
+                    vector_layers.push(json!({
+                        "id": layer.name,
+                        "fields": {},
+                        "minzoom": layer.minzoom,
+                        "maxzoom": layer.maxzoom
+                    }));
+
+                    vector_layers.last_mut().unwrap()
+                }
+            };
+
+            if layer.minzoom < vector_layer["minzoom"].as_u64().unwrap() as u8 {
+                vector_layer["minzoom"] = json!(layer.minzoom);
+            }
+            if layer.maxzoom > vector_layer["maxzoom"].as_u64().unwrap() as u8 {
+                vector_layer["maxzoom"] = json!(layer.maxzoom);
+            }
+
+            for property in &layer.properties {
+                vector_layer["fields"][property] = json!(field_type(schema, property));
+            }
+
+            if self.cluster.radius > 0 && layer.minzoom <= self.cluster.maxzoom {
+                vector_layer["fields"]["count"] = json!("Number");
+            }
+        }
+
+        json!(vector_layers)
+    }
+
+    ///
+    /// Return the built in or custom tile grid with the given name
+    ///
+    pub fn grid(&self, name: &str) -> Result<Grid, HecateError> {
+        match name {
+            "WebMercator" => Ok(Grid::web_mercator()),
+            "WGS84" => Ok(Grid::wgs84()),
+            _ => match self.grids.iter().find(|grid| grid.name == name) {
+                Some(grid) => Ok(grid.to_grid()),
+                None => Err(HecateError::new(404, String::from("Tile Grid Not Found"), None))
+            }
+        }
+    }
+
+    ///
+    /// Return the layers that should be encoded at a given zoom
+    ///
+    pub fn layers(&self, z: u8) -> Vec<&Layer> {
+        self.layers.iter().filter(|layer| {
+            layer.minzoom <= z && z <= layer.maxzoom
+        }).collect()
+    }
+}
+
+///
+/// TileJSON field type of a property given the JSON schema type(s) it allows,
+/// properties that may be of any or of several types are described as a String
+///
+fn field_type(schema: &Option<serde_json::Value>, property: &str) -> &'static str {
+    let types: Vec<&str> = match schema {
+        Some(schema) => match schema["properties"][property]["type"] {
+            serde_json::Value::String(ref kind) => vec![kind.as_str()],
+            serde_json::Value::Array(ref kinds) => kinds.iter().filter_map(|kind| kind.as_str()).filter(|kind| *kind != "null").collect(),
+            _ => Vec::new()
+        },
+        None => Vec::new()
+    };
+
+    if !types.is_empty() && types.iter().all(|kind| *kind == "number" || *kind == "integer") {
+        "Number"
+    } else if !types.is_empty() && types.iter().all(|kind| *kind == "boolean") {
+        "Boolean"
+    } else {
+        "String"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_config() {
+        let config = Config::parse(&json!({
+            "layers": [{
+                "name": "buildings",
+                "minzoom": 14,
+                "geometry": ["Polygon", "MultiPolygon"],
+                "properties": ["building", "height"]
+            }, {
+                "name": "roads",
+                "maxzoom": 12,
+                "geometry": ["LineString"],
+                "properties": ["highway"]
+            }, {
+                "name": "roads",
+                "minzoom": 13,
+                "geometry": ["LineString"],
+                "properties": ["highway", "name"]
+            }]
+        })).unwrap();
+
+        assert_eq!(config.layers[0].maxzoom, 17);
+        assert_eq!(config.budget, 500_000);
+        assert_eq!(config.ttl, 14_400);
+        assert_eq!(config.max_tiles, 100_000);
+        assert_eq!(config.cluster, Cluster { maxzoom: 13, radius: 16 });
+        assert_eq!(config.layers(10).iter().map(|layer| layer.name.as_str()).collect::<Vec<&str>>(), vec!["roads"]);
+        assert_eq!(config.layers(15).len(), 2);
+        assert_eq!(config.layers(15)[1].properties, vec![String::from("highway"), String::from("name")]);
+    }
+
+    #[test]
+    fn grids() {
+        let config = Config::parse(&json!({
+            "layers": [{ "name": "data" }],
+            "grids": [{
+                "name": "polar",
+                "srid": 3413,
+                "extent": [-4194304, -4194304, 4194304, 4194304],
+                "resolutions": [32768, 16384, 8192]
+            }]
+        })).unwrap();
+
+        assert_eq!(config.grids[0].origin, "top-left");
+        assert_eq!(config.grid("WGS84").unwrap().srid, 4326);
+        assert_eq!(config.grid("nope").err(), Some(HecateError::new(404, String::from("Tile Grid Not Found"), None)));
+
+        let grid = config.grid("polar").unwrap();
+        assert_eq!(grid.srid, 3413);
+        assert_eq!(grid.maxzoom(), 2);
+        assert_eq!(grid.level_limit(1), (2, 2));
+        assert_eq!(grid.tile_extent(1, 0, 0), Extent {
+            minx: -4_194_304.0,
+            miny: 0.0,
+            maxx: 0.0,
+            maxy: 4_194_304.0
+        });
+    }
+
+    #[test]
+    fn vector_layers() {
+        let config = Config::parse(&json!({
+            "layers": [{
+                "name": "buildings",
+                "minzoom": 14,
+                "geometry": ["Polygon", "MultiPolygon"],
+                "properties": ["building"]
+            }, {
+                "name": "roads",
+                "maxzoom": 12,
+                "properties": ["highway"]
+            }, {
+                "name": "roads",
+                "minzoom": 13,
+                "properties": ["highway", "name"]
+            }]
+        })).unwrap();
+
+        assert_eq!(config.vector_layers(&None), json!([{
+            "id": "buildings",
+            "fields": { "building": "String" },
+            "minzoom": 14,
+            "maxzoom": 17
+        }, {
+            "id": "roads",
+            "fields": { "highway": "String", "name": "String", "count": "Number" },
+            "minzoom": 0,
+            "maxzoom": 17
+        }]));
+
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "building": { "type": "string" },
+                "highway": { "type": ["integer", "null"] },
+                "name": { "type": ["boolean"] }
+            }
+        });
+
+        assert_eq!(config.vector_layers(&Some(schema)), json!([{
+            "id": "buildings",
+            "fields": { "building": "String" },
+            "minzoom": 14,
+            "maxzoom": 17
+        }, {
+            "id": "roads",
+            "fields": { "highway": "Number", "name": "Boolean", "count": "Number" },
+            "minzoom": 0,
+            "maxzoom": 17
+        }]));
+    }
+
+    #[test]
+    fn invalid_config() {
+        assert_eq!(Config::parse(&json!({
+            "layers": []
+        })), Err(HecateError::new(400, String::from("Tile config must contain at least one layer"), None)));
+
+        assert_eq!(Config::parse(&json!({
+            "layers": [{ "name": "data" }],
+            "budget": -1
+        })), Err(HecateError::new(400, String::from("Tile budget cannot be negative"), None)));
+
+        assert_eq!(Config::parse(&json!({
+            "layers": [{ "name": "data" }],
+            "max_tiles": 0
+        })), Err(HecateError::new(400, String::from("Tile max_tiles must be greater than 0"), None)));
+
+        assert_eq!(Config::parse(&json!({
+            "layers": [{ "name": "data" }],
+            "ttl": -1
+        })), Err(HecateError::new(400, String::from("Tile ttl cannot be negative"), None)));
+
+        assert_eq!(Config::parse(&json!({
+            "layers": [{ "name": "data" }],
+            "cluster": { "maxzoom": 18 }
+        })), Err(HecateError::new(400, String::from("Cluster maxzoom cannot be greater than 17"), None)));
+
+        assert_eq!(Config::parse(&json!({
+            "layers": [{ "name": "data", "minzoom": 10, "maxzoom": 5 }]
+        })), Err(HecateError::new(400, String::from("data: minzoom cannot be greater than maxzoom"), None)));
+
+        assert_eq!(Config::parse(&json!({
+            "layers": [{ "name": "data", "maxzoom": 18 }]
+        })), Err(HecateError::new(400, String::from("data: maxzoom cannot be greater than 17"), None)));
+
+        assert_eq!(Config::parse(&json!({
+            "layers": [{ "name": "data", "geometry": ["Circle"] }]
+        })), Err(HecateError::new(400, String::from("data: Circle is not a valid geometry type"), None)));
+
+        assert_eq!(Config::parse(&json!({
+            "layers": [{ "name": "data", "properties": ["id"] }]
+        })), Err(HecateError::new(400, String::from("data: id is a reserved property name"), None)));
+
+        assert_eq!(Config::parse(&json!({
+            "layers": [{ "name": "data", "maxzoom": 10 }, { "name": "data", "minzoom": 10 }]
+        })), Err(HecateError::new(400, String::from("data: layers with the same name cannot have overlapping zooms"), None)));
+
+        assert_eq!(Config::parse(&json!({
+            "layers": [{ "name": "data" }],
+            "grids": [{ "name": "WGS84", "srid": 4326, "extent": [-180, -90, 180, 90], "resolutions": [0.703125] }]
+        })), Err(HecateError::new(400, String::from("WGS84: is a reserved grid name"), None)));
+
+        assert_eq!(Config::parse(&json!({
+            "layers": [{ "name": "data" }],
+            "grids": [{ "name": "activity", "srid": 4326, "extent": [-180, -90, 180, 90], "resolutions": [0.703125] }]
+        })), Err(HecateError::new(400, String::from("activity: grid name conflicts with a tile route"), None)));
+
+        assert_eq!(Config::parse(&json!({
+            "layers": [{ "name": "data" }],
+            "grids": [{ "name": "polar/north", "srid": 3413, "extent": [-4194304, -4194304, 4194304, 4194304], "resolutions": [16384] }]
+        })), Err(HecateError::new(400, String::from("polar/north: grid name conflicts with a tile route"), None)));
+
+        assert_eq!(Config::parse(&json!({
+            "layers": [{ "name": "data" }],
+            "grids": [{ "name": "polar", "srid": 3413, "extent": [0, 0, 0, 0], "resolutions": [16384] }]
+        })), Err(HecateError::new(400, String::from("polar: extent must be minx, miny, maxx, maxy"), None)));
+
+        assert_eq!(Config::parse(&json!({
+            "layers": [{ "name": "data" }],
+            "grids": [{ "name": "polar", "srid": 3413, "extent": [-4194304, -4194304, 4194304, 4194304], "resolutions": [8192, 16384] }]
+        })), Err(HecateError::new(400, String::from("polar: resolutions must be positive & ordered from largest to smallest"), None)));
+    }
+}
@@ -0,0 +1,248 @@
+use crate::err::HecateError;
+use protobuf::CodedInputStream;
+use protobuf::stream::wire_format::WireType;
+
+///
+/// Geometry type of a vector tile feature
+///
+#[derive(Debug, PartialEq, Clone)]
+pub enum Kind {
+    Unknown,
+    Point,
+    LineString,
+    Polygon
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Feature {
+    pub id: Option<u64>,
+    pub kind: Kind,
+    pub properties: serde_json::Map<String, serde_json::Value>,
+    ///
+    /// Points, lines or rings of the feature in tile units, a point
+    /// feature having a single "line" of every point
+    ///
+    pub geometry: Vec<Vec<(f64, f64)>>
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Layer {
+    pub name: String,
+    pub extent: u32,
+    pub features: Vec<Feature>
+}
+
+fn decode_error(err: protobuf::ProtobufError) -> HecateError {
+    HecateError::new(500, String::from("Failed to decode tile"), Some(err.to_string()))
+}
+
+///
+/// Decode each layer of a Mapbox Vector Tile
+///
+pub fn decode(tile: &[u8]) -> Result<Vec<Layer>, HecateError> {
+    let mut stream = CodedInputStream::from_bytes(tile);
+    let mut layers: Vec<Layer> = Vec::new();
+
+    while !stream.eof().map_err(decode_error)? {
+        match stream.read_tag_unpack().map_err(decode_error)? {
+            (3, WireType::WireTypeLengthDelimited) => {
+                layers.push(layer(&stream.read_bytes().map_err(decode_error)?)?);
+            },
+            (_, wire_type) => stream.skip_field(wire_type).map_err(decode_error)?
+        };
+    }
+
+    Ok(layers)
+}
+
+fn layer(bytes: &[u8]) -> Result<Layer, HecateError> {
+    let mut stream = CodedInputStream::from_bytes(bytes);
+
+    let mut name = String::new();
+    let mut extent: u32 = 4096;
+    let mut keys: Vec<String> = Vec::new();
+    let mut values: Vec<serde_json::Value> = Vec::new();
+    let mut features: Vec<Vec<u8>> = Vec::new();
+
+    while !stream.eof().map_err(decode_error)? {
+        match stream.read_tag_unpack().map_err(decode_error)? {
+            (1, WireType::WireTypeLengthDelimited) => name = stream.read_string().map_err(decode_error)?,
+            (2, WireType::WireTypeLengthDelimited) => features.push(stream.read_bytes().map_err(decode_error)?),
+            (3, WireType::WireTypeLengthDelimited) => keys.push(stream.read_string().map_err(decode_error)?),
+            (4, WireType::WireTypeLengthDelimited) => values.push(value(&stream.read_bytes().map_err(decode_error)?)?),
+            (5, WireType::WireTypeVarint) => extent = stream.read_uint32().map_err(decode_error)?,
+            (_, wire_type) => stream.skip_field(wire_type).map_err(decode_error)?
+        };
+    }
+
+    // Features reference keys & values by index so can only be
+    // decoded once the whole layer has been read
+    let features = features.iter().map(|bytes| {
+        feature(bytes, &keys, &values)
+    }).collect::<Result<Vec<Feature>, HecateError>>()?;
+
+    Ok(Layer {
+        name,
+        extent,
+        features
+    })
+}
+
+fn value(bytes: &[u8]) -> Result<serde_json::Value, HecateError> {
+    let mut stream = CodedInputStream::from_bytes(bytes);
+    let mut value = serde_json::Value::Null;
+
+    while !stream.eof().map_err(decode_error)? {
+        value = match stream.read_tag_unpack().map_err(decode_error)? {
+            (1, WireType::WireTypeLengthDelimited) => json!(stream.read_string().map_err(decode_error)?),
+            (2, WireType::WireTypeFixed32) => json!(stream.read_float().map_err(decode_error)?),
+            (3, WireType::WireTypeFixed64) => json!(stream.read_double().map_err(decode_error)?),
+            (4, WireType::WireTypeVarint) => json!(stream.read_int64().map_err(decode_error)?),
+            (5, WireType::WireTypeVarint) => json!(stream.read_uint64().map_err(decode_error)?),
+            (6, WireType::WireTypeVarint) => json!(stream.read_sint64().map_err(decode_error)?),
+            (7, WireType::WireTypeVarint) => json!(stream.read_bool().map_err(decode_error)?),
+            (_, wire_type) => {
+                stream.skip_field(wire_type).map_err(decode_error)?;
+                value
+            }
+        };
+    }
+
+    Ok(value)
+}
+
+fn feature(bytes: &[u8], keys: &[String], values: &[serde_json::Value]) -> Result<Feature, HecateError> {
+    let mut stream = CodedInputStream::from_bytes(bytes);
+
+    let mut id: Option<u64> = None;
+    let mut kind = Kind::Unknown;
+    let mut tags: Vec<u32> = Vec::new();
+    let mut commands: Vec<u32> = Vec::new();
+
+    while !stream.eof().map_err(decode_error)? {
+        match stream.read_tag_unpack().map_err(decode_error)? {
+            (1, WireType::WireTypeVarint) => id = Some(stream.read_uint64().map_err(decode_error)?),
+            (2, WireType::WireTypeLengthDelimited) => stream.read_repeated_packed_uint32_into(&mut tags).map_err(decode_error)?,
+            (3, WireType::WireTypeVarint) => {
+                kind = match stream.read_uint32().map_err(decode_error)? {
+                    1 => Kind::Point,
+                    2 => Kind::LineString,
+                    3 => Kind::Polygon,
+                    _ => Kind::Unknown
+                };
+            },
+            (4, WireType::WireTypeLengthDelimited) => stream.read_repeated_packed_uint32_into(&mut commands).map_err(decode_error)?,
+            (_, wire_type) => stream.skip_field(wire_type).map_err(decode_error)?
+        };
+    }
+
+    let mut properties = serde_json::Map::new();
+    for tag in tags.chunks(2) {
+        if tag.len() != 2 {
+            break;
+        }
+
+        if let (Some(key), Some(value)) = (keys.get(tag[0] as usize), values.get(tag[1] as usize)) {
+            properties.insert(key.clone(), value.clone());
+        }
+    }
+
+    Ok(Feature {
+        id,
+        geometry: geometry(&kind, &commands),
+        kind,
+        properties
+    })
+}
+
+fn zigzag(value: u32) -> f64 {
+    f64::from(((value >> 1) as i32) ^ -((value & 1) as i32))
+}
+
+///
+/// Decode the MoveTo, LineTo & ClosePath commands of a feature's geometry
+///
+fn geometry(kind: &Kind, commands: &[u32]) -> Vec<Vec<(f64, f64)>> {
+    let mut geometry: Vec<Vec<(f64, f64)>> = Vec::new();
+    let mut current: Vec<(f64, f64)> = Vec::new();
+    let mut cursor: (f64, f64) = (0.0, 0.0);
+
+    let mut i = 0;
+    while i < commands.len() {
+        let command = commands[i] & 0x7;
+        let count = (commands[i] >> 3) as usize;
+        i += 1;
+
+        match command {
+            // MoveTo starts a new line or ring, except for points which are all kept together
+            1 | 2 => {
+                for _ in 0..count {
+                    if i + 1 >= commands.len() {
+                        i = commands.len();
+                        break;
+                    }
+
+                    if command == 1 && *kind != Kind::Point && !current.is_empty() {
+                        geometry.push(current);
+                        current = Vec::new();
+                    }
+
+                    cursor = (cursor.0 + zigzag(commands[i]), cursor.1 + zigzag(commands[i + 1]));
+                    current.push(cursor);
+                    i += 2;
+                }
+            },
+            7 => {
+                if let Some(first) = current.first().cloned() {
+                    current.push(first);
+                }
+            },
+            _ => break
+        }
+    }
+
+    if !current.is_empty() {
+        geometry.push(current);
+    }
+
+    geometry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_geometry() {
+        // Examples from the vector tile spec
+        assert_eq!(geometry(&Kind::Point, &[9, 50, 34]), vec![vec![(25.0, 17.0)]]);
+        assert_eq!(geometry(&Kind::Point, &[17, 10, 14, 3, 9]), vec![vec![(5.0, 7.0), (3.0, 2.0)]]);
+        assert_eq!(geometry(&Kind::LineString, &[9, 4, 4, 18, 0, 16, 16, 0]), vec![vec![(2.0, 2.0), (2.0, 10.0), (10.0, 10.0)]]);
+        assert_eq!(geometry(&Kind::Polygon, &[9, 6, 12, 18, 10, 12, 24, 44, 15]), vec![vec![(3.0, 6.0), (8.0, 12.0), (20.0, 34.0), (3.0, 6.0)]]);
+    }
+
+    #[test]
+    fn decode_tile() {
+        // Single "data" layer with a point feature { id: 1, name: "a" }
+        let tile: Vec<u8> = vec![
+            0x1A, 0x25,
+                0x78, 0x02,
+                0x0A, 0x04, b'd', b'a', b't', b'a',
+                0x12, 0x0D, 0x08, 0x01, 0x12, 0x02, 0x00, 0x00, 0x18, 0x01, 0x22, 0x03, 0x09, 0x32, 0x22,
+                0x1A, 0x04, b'n', b'a', b'm', b'e',
+                0x22, 0x03, 0x0A, 0x01, b'a',
+                0x28, 0x80, 0x20
+        ];
+
+        let layers = decode(&tile).unwrap();
+        assert_eq!(layers.len(), 1);
+        assert_eq!(layers[0].name, "data");
+        assert_eq!(layers[0].extent, 4096);
+        assert_eq!(layers[0].features, vec![Feature {
+            id: Some(1),
+            kind: Kind::Point,
+            properties: json!({ "name": "a" }).as_object().unwrap().clone(),
+            geometry: vec![vec![(25.0, 17.0)]]
+        }]);
+    }
+}
@@ -0,0 +1,417 @@
+use crate::err::HecateError;
+use super::{db_create, Config};
+use flate2::{Compression, write::GzEncoder};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+
+///
+/// The root directory & header of a PMTiles archive must fit
+/// within the first 16KiB of the archive
+///
+static PMTILES_ROOT: u64 = 16_384;
+static PMTILES_HEADER: u64 = 127;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Format {
+    MBTiles,
+    PMTiles
+}
+
+impl Format {
+    pub fn parse(format: &str) -> Result<Self, HecateError> {
+        match format {
+            "mbtiles" => Ok(Format::MBTiles),
+            "pmtiles" => Ok(Format::PMTiles),
+            _ => Err(HecateError::new(400, String::from("format must be one of mbtiles or pmtiles"), None))
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::MBTiles => "mbtiles",
+            Format::PMTiles => "pmtiles"
+        }
+    }
+}
+
+///
+/// Archive wide metadata, following the TileJSON spec
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct Metadata {
+    pub bounds: Vec<f64>,
+    pub minzoom: u8,
+    pub maxzoom: u8,
+    pub vector_layers: serde_json::Value
+}
+
+impl Metadata {
+    pub fn center(&self) -> (f64, f64) {
+        ((self.bounds[0] + self.bounds[2]) / 2.0, (self.bounds[1] + self.bounds[3]) / 2.0)
+    }
+
+    pub fn to_tilejson(&self) -> serde_json::Value {
+        let center = self.center();
+
+        json!({
+            "tilejson": "3.0.0",
+            "name": "hecate",
+            "format": "pbf",
+            "bounds": self.bounds,
+            "center": [ center.0, center.1, self.minzoom ],
+            "minzoom": self.minzoom,
+            "maxzoom": self.maxzoom,
+            "vector_layers": self.vector_layers
+        })
+    }
+}
+
+///
+/// Render every given tile into an archive of the given format
+///
+/// The archive is written to a temporary file that is removed from disk as soon
+/// as it has been written, the returned file handle keeping it readable until closed
+///
+pub fn export(conn: &impl postgres::GenericConnection, config: &Config, format: &Format, tiles: &[(i32, i32, u8)], metadata: &Metadata) -> Result<File, HecateError> {
+    let path = std::env::temp_dir().join(format!("hecate-{}.{}", rand::random::<u64>(), format.extension()));
+
+    let res = match format {
+        Format::MBTiles => mbtiles(conn, config, &path, tiles, metadata),
+        Format::PMTiles => pmtiles(conn, config, &path, tiles, metadata)
+    };
+
+    let file = match res.and_then(|_| File::open(&path).map_err(|err| {
+        HecateError::new(500, String::from("Failed to open export"), Some(err.to_string()))
+    })) {
+        Ok(file) => file,
+        Err(err) => {
+            fs::remove_file(&path).ok();
+            return Err(err);
+        }
+    };
+
+    if let Err(err) = fs::remove_file(&path) {
+        println!("WARN: Failed to remove export {:?}: {}", path, err);
+    }
+
+    Ok(file)
+}
+
+fn gzip(data: &[u8]) -> Result<Vec<u8>, HecateError> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+
+    match encoder.write_all(data).and_then(|_| encoder.finish()) {
+        Ok(data) => Ok(data),
+        Err(err) => Err(HecateError::new(500, String::from("Failed to compress tile"), Some(err.to_string())))
+    }
+}
+
+fn mbtiles_error(err: rusqlite::Error) -> HecateError {
+    HecateError::new(500, String::from("Failed to write MBTiles"), Some(err.to_string()))
+}
+
+fn mbtiles(conn: &impl postgres::GenericConnection, config: &Config, path: &Path, tiles: &[(i32, i32, u8)], metadata: &Metadata) -> Result<(), HecateError> {
+    let mut sqlite = rusqlite::Connection::open(path).map_err(mbtiles_error)?;
+
+    sqlite.execute_batch("
+        CREATE TABLE metadata (name TEXT, value TEXT);
+        CREATE TABLE tiles (zoom_level INTEGER, tile_column INTEGER, tile_row INTEGER, tile_data BLOB);
+        CREATE UNIQUE INDEX tile_index ON tiles (zoom_level, tile_column, tile_row);
+    ").map_err(mbtiles_error)?;
+
+    let trans = sqlite.transaction().map_err(mbtiles_error)?;
+
+    let center = metadata.center();
+    let bounds: Vec<String> = metadata.bounds.iter().map(|coord| coord.to_string()).collect();
+
+    for (name, value) in &[
+        ("name", String::from("hecate")),
+        ("format", String::from("pbf")),
+        ("type", String::from("overlay")),
+        ("bounds", bounds.join(",")),
+        ("center", format!("{},{},{}", center.0, center.1, metadata.minzoom)),
+        ("minzoom", metadata.minzoom.to_string()),
+        ("maxzoom", metadata.maxzoom.to_string()),
+        ("json", json!({ "vector_layers": metadata.vector_layers }).to_string())
+    ] {
+        trans.execute("
+            INSERT INTO metadata (name, value) VALUES (?1, ?2)
+        ", &[name as &dyn rusqlite::ToSql, value]).map_err(mbtiles_error)?;
+    }
+
+    for tile in tiles {
+        let data = db_create(conn, config, tile.2, tile.0 as u32, tile.1 as u32)?;
+
+        if data.is_empty() {
+            continue;
+        }
+
+        // MBTiles uses the TMS tile scheme, with y increasing northward
+        let row: i32 = (1 << tile.2) - 1 - tile.1;
+
+        trans.execute("
+            INSERT INTO tiles (zoom_level, tile_column, tile_row, tile_data) VALUES (?1, ?2, ?3, ?4)
+        ", &[&i32::from(tile.2) as &dyn rusqlite::ToSql, &tile.0, &row, &gzip(&data)?]).map_err(mbtiles_error)?;
+    }
+
+    trans.commit().map_err(mbtiles_error)?;
+
+    Ok(())
+}
+
+///
+/// Convert a tile coordinate to its position along the
+/// hilbert curve ordering of all tiles in a PMTiles archive
+///
+pub fn tile_id(z: u8, x: u32, y: u32) -> u64 {
+    let mut acc: u64 = 0;
+    for zoom in 0..z {
+        acc += 1 << (2 * u64::from(zoom));
+    }
+
+    let mut x = u64::from(x);
+    let mut y = u64::from(y);
+    let mut d: u64 = 0;
+    let mut s: u64 = (1 << z) / 2;
+
+    while s > 0 {
+        let rx: u64 = if x & s > 0 { 1 } else { 0 };
+        let ry: u64 = if y & s > 0 { 1 } else { 0 };
+
+        d += s * s * ((3 * rx) ^ ry);
+
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - (x & (s - 1));
+                y = s - 1 - (y & (s - 1));
+            }
+
+            std::mem::swap(&mut x, &mut y);
+        }
+
+        s /= 2;
+    }
+
+    acc + d
+}
+
+fn varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+
+    buf.push(value as u8);
+}
+
+///
+/// A PMTiles directory entry, a run_length of 0 pointing to a leaf directory
+///
+#[derive(Debug, PartialEq, Clone)]
+struct Entry {
+    tile_id: u64,
+    offset: u64,
+    length: u64,
+    run_length: u64
+}
+
+fn directory(entries: &[Entry]) -> Vec<u8> {
+    let mut buf: Vec<u8> = Vec::new();
+
+    varint(&mut buf, entries.len() as u64);
+
+    let mut last: u64 = 0;
+    for entry in entries {
+        varint(&mut buf, entry.tile_id - last);
+        last = entry.tile_id;
+    }
+
+    for entry in entries {
+        varint(&mut buf, entry.run_length);
+    }
+
+    for entry in entries {
+        varint(&mut buf, entry.length);
+    }
+
+    for (i, entry) in entries.iter().enumerate() {
+        if i > 0 && entry.offset == entries[i - 1].offset + entries[i - 1].length {
+            varint(&mut buf, 0);
+        } else {
+            varint(&mut buf, entry.offset + 1);
+        }
+    }
+
+    buf
+}
+
+///
+/// Split entries into leaf directories until the root directory
+/// fits in the space reserved for it, returning the compressed root
+/// & the concatenated compressed leaf directories
+///
+fn directories(entries: &[Entry]) -> Result<(Vec<u8>, Vec<u8>), HecateError> {
+    let root = gzip(&directory(entries))?;
+
+    if root.len() as u64 <= PMTILES_ROOT - PMTILES_HEADER {
+        return Ok((root, Vec::new()));
+    }
+
+    let mut leaf_size: usize = 4096;
+
+    loop {
+        let mut roots: Vec<Entry> = Vec::new();
+        let mut leaves: Vec<u8> = Vec::new();
+
+        for chunk in entries.chunks(leaf_size) {
+            let leaf = gzip(&directory(chunk))?;
+
+            roots.push(Entry {
+                tile_id: chunk[0].tile_id,
+                offset: leaves.len() as u64,
+                length: leaf.len() as u64,
+                run_length: 0
+            });
+
+            leaves.extend(leaf);
+        }
+
+        let root = gzip(&directory(&roots))?;
+
+        if root.len() as u64 <= PMTILES_ROOT - PMTILES_HEADER {
+            return Ok((root, leaves));
+        }
+
+        leaf_size *= 2;
+    }
+}
+
+fn pmtiles_error(err: std::io::Error) -> HecateError {
+    HecateError::new(500, String::from("Failed to write PMTiles"), Some(err.to_string()))
+}
+
+fn pmtiles(conn: &impl postgres::GenericConnection, config: &Config, path: &Path, tiles: &[(i32, i32, u8)], metadata: &Metadata) -> Result<(), HecateError> {
+    let mut file = OpenOptions::new().read(true).write(true).create_new(true).open(path).map_err(pmtiles_error)?;
+
+    // Tiles are written in tile id order directly after the space reserved for
+    // the header & root directory, which are written once all tiles are known
+    let mut tiles: Vec<(u64, (i32, i32, u8))> = tiles.iter().map(|tile| {
+        (tile_id(tile.2, tile.0 as u32, tile.1 as u32), *tile)
+    }).collect();
+    tiles.sort_by_key(|tile| tile.0);
+    tiles.dedup_by_key(|tile| tile.0);
+
+    file.seek(SeekFrom::Start(PMTILES_ROOT)).map_err(pmtiles_error)?;
+
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut offset: u64 = 0;
+
+    for (id, tile) in tiles {
+        let data = db_create(conn, config, tile.2, tile.0 as u32, tile.1 as u32)?;
+
+        if data.is_empty() {
+            continue;
+        }
+
+        let data = gzip(&data)?;
+        file.write_all(&data).map_err(pmtiles_error)?;
+
+        entries.push(Entry {
+            tile_id: id,
+            offset,
+            length: data.len() as u64,
+            run_length: 1
+        });
+
+        offset += data.len() as u64;
+    }
+
+    let tile_data_length = offset;
+
+    let meta = gzip(metadata.to_tilejson().to_string().as_bytes())?;
+    file.write_all(&meta).map_err(pmtiles_error)?;
+
+    let (root, leaves) = directories(&entries)?;
+    file.write_all(&leaves).map_err(pmtiles_error)?;
+
+    let center = metadata.center();
+
+    let mut header: Vec<u8> = Vec::with_capacity(PMTILES_HEADER as usize);
+    header.extend_from_slice(b"PMTiles");
+    header.push(3);
+
+    for value in &[
+        PMTILES_HEADER, // root directory offset
+        root.len() as u64,
+        PMTILES_ROOT + tile_data_length, // metadata offset
+        meta.len() as u64,
+        PMTILES_ROOT + tile_data_length + meta.len() as u64, // leaf directories offset
+        leaves.len() as u64,
+        PMTILES_ROOT, // tile data offset
+        tile_data_length,
+        entries.len() as u64, // addressed tiles
+        entries.len() as u64, // tile entries
+        entries.len() as u64 // tile contents
+    ] {
+        header.extend_from_slice(&value.to_le_bytes());
+    }
+
+    header.push(1); // clustered
+    header.push(2); // internal compression: gzip
+    header.push(2); // tile compression: gzip
+    header.push(1); // tile type: mvt
+    header.push(metadata.minzoom);
+    header.push(metadata.maxzoom);
+
+    for coord in &[metadata.bounds[0], metadata.bounds[1], metadata.bounds[2], metadata.bounds[3]] {
+        header.extend_from_slice(&((coord * 10_000_000.0) as i32).to_le_bytes());
+    }
+
+    header.push(metadata.minzoom);
+    header.extend_from_slice(&((center.0 * 10_000_000.0) as i32).to_le_bytes());
+    header.extend_from_slice(&((center.1 * 10_000_000.0) as i32).to_le_bytes());
+
+    file.seek(SeekFrom::Start(0)).map_err(pmtiles_error)?;
+    file.write_all(&header).map_err(pmtiles_error)?;
+    file.write_all(&root).map_err(pmtiles_error)?;
+    file.flush().map_err(pmtiles_error)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pmtiles_tile_id() {
+        assert_eq!(tile_id(0, 0, 0), 0);
+        assert_eq!(tile_id(1, 0, 0), 1);
+        assert_eq!(tile_id(1, 0, 1), 2);
+        assert_eq!(tile_id(1, 1, 1), 3);
+        assert_eq!(tile_id(1, 1, 0), 4);
+        assert_eq!(tile_id(2, 0, 0), 5);
+        assert_eq!(tile_id(12, 3423, 1763), 19_078_479);
+    }
+
+    #[test]
+    fn pmtiles_directory() {
+        assert_eq!(directory(&[Entry {
+            tile_id: 1,
+            offset: 0,
+            length: 300,
+            run_length: 1
+        }, Entry {
+            tile_id: 3,
+            offset: 300,
+            length: 10,
+            run_length: 1
+        }, Entry {
+            tile_id: 4,
+            offset: 500,
+            length: 10,
+            run_length: 1
+        }]), vec![3, 1, 2, 1, 1, 1, 1, 0xAC, 0x02, 10, 10, 1, 0, 0xF5, 0x03]);
+    }
+}
@@ -58,6 +58,20 @@ pub struct Grid {
 }
 
 impl Grid {
+    /// Custom grid with square tiles, numbered from the given origin
+    pub fn new(size: u16, extent: Extent, srid: i32, units: Unit, resolutions: Vec<f64>, origin: Origin) -> Grid {
+        Grid {
+            width: size,
+            height: size,
+            extent,
+            srid,
+            units,
+            resolutions,
+            origin,
+            reverse_y: false,
+        }
+    }
+
     /// WGS84 grid
     pub fn wgs84() -> Grid {
         Grid {
@@ -144,6 +158,14 @@ impl Grid {
     pub fn pixel_width(&self, zoom: u8) -> f64 {
         self.resolutions[zoom as usize] //TODO: assumes grid unit 'm'
     }
+    /// Approximate size of a pixel at the given zoom in EPSG:4326 degrees at the equator
+    pub fn pixel_degrees(&self, zoom: u8) -> f64 {
+        match self.units {
+            Unit::DD => self.pixel_width(zoom),
+            Unit::M => self.pixel_width(zoom) / 111_319.490_793_273_6,
+            Unit::Ft => self.pixel_width(zoom) / 365_221.427_802_078_7,
+        }
+    }
     pub fn scale_denominator(&self, zoom: u8) -> f64 {
         let pixel_screen_width = 0.0254 / 96.0; //FIXME: assumes 96dpi - check with mapnik
         self.pixel_width(zoom) / pixel_screen_width
@@ -155,7 +177,7 @@ impl Grid {
         let ytile = if self.reverse_y {
             let res = self.resolutions[zoom as usize];
             let unitheight = self.height as f64 * res;
-            let maxy = ((self.extent.maxy - self.extent.minx - 0.01 * unitheight) / unitheight)
+            let maxy = ((self.extent.maxy - self.extent.miny - 0.01 * unitheight) / unitheight)
                 .ceil() as u32;
             maxy.saturating_sub(y).saturating_sub(1)
         } else {
@@ -193,7 +215,7 @@ impl Grid {
 
         let maxy = ((self.extent.maxy - self.extent.miny - 0.01 * unitheight) / unitheight)
             .ceil() as u32;
-        let maxx = ((self.extent.maxx - self.extent.miny - 0.01 * unitwidth) / unitwidth)
+        let maxx = ((self.extent.maxx - self.extent.minx - 0.01 * unitwidth) / unitwidth)
             .ceil() as u32;
         (maxx, maxy)
     }
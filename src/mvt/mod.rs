@@ -1,55 +1,332 @@
 #[allow(clippy::excessive_precision)]
 pub mod grid;
+pub mod config;
+pub mod export;
+pub mod decode;
+pub mod activity;
 
 use crate::err::HecateError;
+use crate::{bounds, delta, stats, validate};
+use geo::algorithm::bounding_rect::BoundingRect;
+use std::collections::HashSet;
 pub use self::grid::{Grid};
+pub use self::config::{Config, Layer, Cluster, MAX_ZOOM};
 
-pub fn db_get(conn: &impl postgres::GenericConnection, coord: String) -> Result<Option<Vec<u8>>, HecateError> {
+///
+/// A vector tile along with the time it was generated
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct Tile {
+    pub tile: Vec<u8>,
+    pub created: chrono::DateTime<chrono::Utc>
+}
+
+impl Tile {
+    ///
+    /// Entity tag for the tile, changing each time the tile is regenerated
+    ///
+    pub fn etag(&self) -> String {
+        format!("\"{}-{}\"", self.created.timestamp(), self.created.timestamp_subsec_micros())
+    }
+
+    ///
+    /// HTTP-date formatted time the tile was generated
+    ///
+    pub fn last_modified(&self) -> String {
+        self.created.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+    }
+}
+
+///
+/// Return a cached tile if it exists and is younger than the ttl (seconds)
+/// A ttl of 0 will return a cached tile regardless of age
+///
+pub fn db_get(conn: &impl postgres::GenericConnection, coord: String, ttl: i64) -> Result<Option<Tile>, HecateError> {
     match conn.query("
-        SELECT tile
+        SELECT
+            tile,
+            created::TIMESTAMPTZ
         FROM tiles
         WHERE
             ref = $1
-            AND NOW() > created + INTERVAL '4 hours'
-    ", &[&coord]) {
+            AND (
+                $2::BIGINT = 0
+                OR created + $2::BIGINT * INTERVAL '1 second' > NOW()
+            )
+    ", &[&coord, &ttl]) {
         Ok(rows) => {
             if rows.is_empty() {
                 return Ok(None);
             }
 
-            let tile: Vec<u8> = rows.get(0).get(0);
-
-            Ok(Some(tile))
+            Ok(Some(Tile {
+                tile: rows.get(0).get(0),
+                created: rows.get(0).get(1)
+            }))
         },
         Err(err) => Err(HecateError::from_db(err))
     }
 }
 
-pub fn db_create(conn: &impl postgres::GenericConnection, z: u8, x: u32, y: u32) -> Result<Vec<u8>, HecateError> {
-    let grid = Grid::web_mercator();
+///
+/// Number of tile units per pixel of a 256px tile with a 4096 extent
+///
+static UNITS_PER_PIXEL: f64 = 16.0;
+
+///
+/// Create a tile containing each layer in the tile config that is
+/// visible at the given zoom
+///
+/// Rather than truncating the features in a tile, lines & polygons are
+/// simplified to the resolution of the zoom, points are clustered into
+/// a single point with a `count` attribute at low zooms, and if the tile
+/// exceeds the configured byte budget the smallest features are dropped
+///
+pub fn db_create(conn: &impl postgres::GenericConnection, config: &Config, z: u8, x: u32, y: u32) -> Result<Vec<u8>, HecateError> {
+    render(conn, config, &Grid::web_mercator(), z, x, y, None)
+}
+
+///
+/// Create a tile in any tile grid, using the same layers & rules as db_create,
+/// optionally from the state of each feature at a given point in the edit history
+///
+/// Tiles rendered in a grid other than web mercator or at a point in history are never cached
+///
+pub fn render(conn: &impl postgres::GenericConnection, config: &Config, grid: &Grid, z: u8, x: u32, y: u32, at: Option<&delta::At>) -> Result<Vec<u8>, HecateError> {
+    if z > grid.maxzoom() || z > MAX_ZOOM {
+        return Err(HecateError::new(404, String::from("Tile Not Found"), None));
+    }
+
+    let (maxx, maxy) = grid.level_limit(z);
+    if x >= maxx || y >= maxy {
+        return Err(HecateError::new(404, String::from("Tile Not Found"), None));
+    }
+
+    if let Some(delta::At::Delta(id)) = at {
+        match conn.query("
+            SELECT 1 FROM deltas WHERE id = $1
+        ", &[id]) {
+            Err(err) => { return Err(HecateError::from_db(err)); },
+            Ok(res) => {
+                if res.is_empty() {
+                    return Err(HecateError::new(404, String::from("Delta Not Found"), None));
+                }
+            }
+        };
+    }
+
     let bbox = grid.tile_extent(z, x, y);
 
-    let mut limit: Option<i64> = None;
-    if z < 10 {
-        limit = Some(10)
-    } else if z < 14 {
-        limit = Some(100)
+    let layers = config.layers(z);
+
+    if layers.is_empty() {
+        return Ok(Vec::new());
     }
 
-    match conn.query("
-        SELECT
-            ST_AsMVT(q, 'data', 4096, 'geom')
-        FROM (
+    // Size of a single pixel at the given zoom in EPSG:4326 degrees
+    let tolerance: f64 = grid.pixel_degrees(z);
+
+    // Size of a cluster cell in tile units, 0 disabling clustering
+    let cluster: f64 = if z <= config.cluster.maxzoom {
+        f64::from(config.cluster.radius) * UNITS_PER_PIXEL
+    } else {
+        0.0
+    };
+
+    let geometries: Vec<Vec<String>> = layers.iter().map(|layer| {
+        layer.geometry.iter().map(|geometry| geometry.to_uppercase()).collect()
+    }).collect();
+
+    let mut params: Vec<&dyn postgres::types::ToSql> = vec![&bbox.minx, &bbox.miny, &bbox.maxx, &bbox.maxy, &grid.srid, &tolerance, &cluster, &config.budget];
+    let mut ctes: Vec<String> = Vec::with_capacity(layers.len() + 1);
+
+    // A tile envelope only remains a rectangle in EPSG:4326 for these grids,
+    // in any other CRS the densified envelope is transformed once to use the
+    // spatial index, and features are only transformed to recheck candidates
+    let intersects = if grid.srid == 4326 || grid.srid == 3857 {
+        "ST_Intersects(geom, ST_Transform(ST_MakeEnvelope($1, $2, $3, $4, $5), 4326))"
+    } else {
+        "geom && ST_Transform(ST_Segmentize(ST_MakeEnvelope($1, $2, $3, $4, $5), GREATEST($3 - $1, $4 - $2) / 64), 4326)
+        AND ST_Intersects(ST_Transform(geom, $5::INT), ST_MakeEnvelope($1, $2, $3, $4, $5))"
+    };
+
+    let (at_delta, at_time): (Option<i64>, Option<chrono::NaiveDateTime>) = match at {
+        Some(delta::At::Delta(delta)) => (Some(*delta), None),
+        Some(delta::At::Timestamp(time)) => (None, Some(*time)),
+        None => (None, None)
+    };
+
+    // Historical tiles shadow the geo table with the latest version of each
+    // feature at that point, limited to features that were ever in the tile
+    if at.is_some() {
+        params.push(&at_delta);
+        let at_delta = params.len();
+
+        params.push(&at_time);
+        let at_time = params.len();
+
+        ctes.push(format!("
+            geo AS (
+                SELECT
+                    id,
+                    props,
+                    geom
+                FROM (
+                    SELECT DISTINCT ON (geo_history.id)
+                        geo_history.*
+                    FROM
+                        geo_history,
+                        deltas
+                    WHERE
+                        geo_history.delta = deltas.id
+                        AND geo_history.id IN (
+                            SELECT id FROM geo_history WHERE {intersects}
+                        )
+                        AND (
+                            (${at_delta}::BIGINT IS NOT NULL AND deltas.id <= ${at_delta}::BIGINT)
+                            OR (${at_time}::TIMESTAMP IS NOT NULL AND deltas.created <= ${at_time}::TIMESTAMP)
+                        )
+                    ORDER BY
+                        geo_history.id,
+                        geo_history.version DESC
+                ) h
+                WHERE
+                    h.action != 'delete'
+            )
+        ", at_delta = at_delta, at_time = at_time, intersects = intersects));
+    }
+
+    let mut features: Vec<String> = Vec::with_capacity(layers.len());
+    let mut queries: Vec<String> = Vec::with_capacity(layers.len());
+
+    for (i, (layer, geometry)) in layers.iter().zip(geometries.iter()).enumerate() {
+        params.push(&layer.name);
+        let name = params.len();
+
+        params.push(geometry);
+        let geometry = params.len();
+
+        params.push(&layer.properties);
+        let properties = params.len();
+
+        // ST_AsMVT encodes each key of a JSONB column with the type of its
+        // value, nested objects & arrays are encoded as their JSON text
+        ctes.push(format!("
+            layer_{i} AS (
+                SELECT
+                    id,
+                    props,
+                    geom
+                FROM (
+                    SELECT
+                        id,
+                        (
+                            SELECT JSONB_Object_Agg(key, CASE
+                                WHEN JSONB_TypeOf(value) IN ('object', 'array') THEN TO_JSONB(value::TEXT)
+                                ELSE value
+                            END)
+                            FROM JSONB_Each(props)
+                            WHERE key = ANY(${properties}::TEXT[])
+                        ) AS props,
+                        ST_AsMVTGeom(
+                            ST_Transform(CASE
+                                WHEN GeometryType(geom) = 'POINT' THEN geom
+                                ELSE ST_SimplifyPreserveTopology(geom, $6::FLOAT8)
+                            END, $5::INT),
+                            ST_MakeEnvelope($1, $2, $3, $4, $5), 4096, 256, false
+                        ) AS geom
+                    FROM
+                        geo
+                    WHERE
+                        {intersects}
+                        AND (
+                            cardinality(${geometry}::TEXT[]) = 0
+                            OR GeometryType(geom) = ANY(${geometry}::TEXT[])
+                        )
+                ) f
+                WHERE
+                    geom IS NOT NULL
+            )
+        ", i = i, properties = properties, geometry = geometry, intersects = intersects));
+
+        features.push(format!("
             SELECT
+                {i} AS layer,
                 id,
-                ST_AsMVTGeom(geom, ST_Transform(ST_MakeEnvelope($1, $2, $3, $4, $5), 4326), 4096, 256, false) AS geom
+                props,
+                geom,
+                NULL::BIGINT AS count
+            FROM
+                layer_{i}
+            WHERE
+                $7::FLOAT8 = 0
+                OR GeometryType(geom) != 'POINT'
+            UNION ALL
+            SELECT
+                {i} AS layer,
+                MIN(id) AS id,
+                CASE WHEN COUNT(*) = 1 THEN (ARRAY_AGG(props))[1] END AS props,
+                ST_SnapToGrid(ST_Centroid(ST_Collect(geom)), 1) AS geom,
+                COUNT(*) AS count
             FROM
-                geo
+                layer_{i}
             WHERE
-                ST_Intersects(geom, ST_Transform(ST_MakeEnvelope($1, $2, $3, $4, $5), 4326))
-            LIMIT $6
-        ) q
-    ", &[&bbox.minx, &bbox.miny, &bbox.maxx, &bbox.maxy, &grid.srid, &limit]) {
+                $7::FLOAT8 > 0
+                AND GeometryType(geom) = 'POINT'
+            GROUP BY
+                ST_SnapToGrid(geom, $7::FLOAT8)
+        ", i = i));
+
+        queries.push(format!("
+            COALESCE((
+                SELECT
+                    ST_AsMVT(q, ${name}::TEXT, 4096, 'geom')
+                FROM (
+                    SELECT
+                        id,
+                        props,
+                        count,
+                        geom
+                    FROM
+                        budget
+                    WHERE
+                        layer = {i}
+                ) q
+            ), ''::BYTEA)
+        ", i = i, name = name));
+    }
+
+    // Features are ranked by their size in the tile so that when the
+    // estimated size exceeds the budget, the smallest are dropped first
+    match conn.query(&*format!("
+        WITH
+            {ctes},
+            features AS (
+                {features}
+            ),
+            budget AS (
+                SELECT
+                    *
+                FROM (
+                    SELECT
+                        *,
+                        SUM(ST_MemSize(geom) + COALESCE(octet_length(props::TEXT), 0)) OVER (
+                            ORDER BY
+                                ST_Area(geom) + ST_Length(geom) DESC,
+                                count DESC NULLS LAST,
+                                layer,
+                                id
+                            ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW
+                        ) AS bytes
+                    FROM
+                        features
+                ) f
+                WHERE
+                    $8::BIGINT = 0
+                    OR bytes <= $8::BIGINT
+            )
+        SELECT {queries}
+    ", ctes = ctes.join(","), features = features.join(" UNION ALL "), queries = queries.join(" || ")), &params) {
         Ok(res) => {
             let tile: Vec<u8> = res.get(0).get(0);
             Ok(tile)
@@ -58,15 +335,15 @@ pub fn db_create(conn: &impl postgres::GenericConnection, z: u8, x: u32, y: u32)
     }
 }
 
-
-pub fn db_cache(conn: &impl postgres::GenericConnection, coord: String, tile: &[u8]) -> Result<(), HecateError> {
+pub fn db_cache(conn: &impl postgres::GenericConnection, coord: String, tile: &[u8]) -> Result<chrono::DateTime<chrono::Utc>, HecateError> {
     match conn.query("
         INSERT INTO tiles (ref, tile, created)
             VALUES ($1, $2, NOW())
-                ON CONFLICT (ref) DO UPDATE SET tile = $2;
+                ON CONFLICT (ref) DO UPDATE SET tile = $2, created = NOW()
+        RETURNING created::TIMESTAMPTZ;
     ", &[&coord, &tile]) {
-        Err(err) => Err(HecateError::from_db(err)),
-        _ => Ok(())
+        Ok(rows) => Ok(rows.get(0).get(0)),
+        Err(err) => Err(HecateError::from_db(err))
     }
 }
 
@@ -108,8 +385,8 @@ pub fn meta(conn: &impl postgres::GenericConnection, z: u8, x: u32, y: u32) -> R
 /// If you only have a single database connection and want a tile regen
 /// but no tile return, this function can be used to force a regen
 ///
-pub fn regen(conn: &impl postgres::GenericConnection, z: u8, x: u32, y: u32) -> Option<HecateError> {
-    let tile = match db_create(conn, z, x, y) {
+pub fn regen(conn: &impl postgres::GenericConnection, config: &Config, z: u8, x: u32, y: u32) -> Option<HecateError> {
+    let tile = match db_create(conn, config, z, x, y) {
         Ok(tile) => tile,
         Err(err) => {
             return Some(err);
@@ -122,6 +399,197 @@ pub fn regen(conn: &impl postgres::GenericConnection, z: u8, x: u32, y: u32) ->
     }
 }
 
+///
+/// Remove every cached tile covering a feature in a delta, or the previous
+/// version of that feature, from the tile cache & regenerate it, returning the
+/// number of regenerated tiles
+///
+/// Tiles are only covered across the zooms of the configured layers, as tiles
+/// outside of them never contain features
+///
+pub fn invalidate(conn: &impl postgres::GenericConnection, config: &Config, delta: i64) -> Result<usize, HecateError> {
+    let minzoom = config.layers.iter().map(|layer| layer.minzoom).min().unwrap_or(0);
+    let maxzoom = config.layers.iter().map(|layer| layer.maxzoom).max().unwrap_or(MAX_ZOOM);
+
+    let tiles = delta::tiles(conn, delta, minzoom, maxzoom)?;
+
+    if tiles.is_empty() {
+        return Ok(0);
+    }
+
+    let refs: Vec<String> = tiles.iter().map(|tile| format!("{}/{}/{}", tile.2, tile.0, tile.1)).collect();
+
+    let cached: HashSet<String> = match conn.query("
+        DELETE FROM tiles
+            WHERE ref = ANY($1)
+        RETURNING ref
+    ", &[&refs]) {
+        Ok(rows) => rows.iter().map(|row| row.get(0)).collect(),
+        Err(err) => { return Err(HecateError::from_db(err)); }
+    };
+
+    for (tile, coord) in tiles.iter().zip(refs.iter()) {
+        if !cached.contains(coord) {
+            continue;
+        }
+
+        if let Some(err) = regen(conn, config, tile.2, tile.0 as u32, tile.1 as u32) {
+            println!("{}", err.as_log());
+        }
+    }
+
+    Ok(cached.len())
+}
+
+///
+/// Validate an optional zoom range, defaulting minzoom to 0
+///
+pub fn zooms(minzoom: Option<u8>, maxzoom: Option<u8>) -> Result<(u8, u8), HecateError> {
+    let minzoom = minzoom.unwrap_or(0);
+    let maxzoom = match maxzoom {
+        Some(maxzoom) => maxzoom,
+        None => { return Err(HecateError::new(400, String::from("maxzoom param required"), None)); }
+    };
+
+    if maxzoom > MAX_ZOOM {
+        Err(HecateError::new(400, format!("maxzoom cannot be greater than {}", MAX_ZOOM), None))
+    } else if minzoom > maxzoom {
+        Err(HecateError::new(400, String::from("minzoom cannot be greater than maxzoom"), None))
+    } else {
+        Ok((minzoom, maxzoom))
+    }
+}
+
+///
+/// The bbox of all features in the database, as last calculated by stats,
+/// None if it has not been calculated
+///
+pub fn extent(conn: &impl postgres::GenericConnection) -> Result<Option<Vec<f64>>, HecateError> {
+    match stats::get_json(conn)?["bbox"].as_array() {
+        Some(bbox) => {
+            let bbox: Vec<f64> = bbox.iter().filter_map(|coord| coord.as_f64()).collect();
+
+            if bbox.len() == 4 {
+                Ok(Some(bbox))
+            } else {
+                Ok(None)
+            }
+        },
+        None => Ok(None)
+    }
+}
+
+///
+/// TileJSON describing the vector tile source, served from the given tile url template
+///
+pub fn tilejson(conn: &impl postgres::GenericConnection, config: &Config, schema: &Option<serde_json::Value>, url: String) -> Result<serde_json::Value, HecateError> {
+    let metadata = export::Metadata {
+        bounds: extent(conn)?.unwrap_or_else(|| vec![-180.0, -85.051_128_779_806_6, 180.0, 85.051_128_779_806_6]),
+        minzoom: config.layers.iter().map(|layer| layer.minzoom).min().unwrap_or(0),
+        maxzoom: config.layers.iter().map(|layer| layer.maxzoom).max().unwrap_or(MAX_ZOOM),
+        vector_layers: config.vector_layers(schema)
+    };
+
+    let mut tilejson = metadata.to_tilejson();
+    tilejson["tiles"] = json!([ url ]);
+
+    Ok(tilejson)
+}
+
+///
+/// Resolve either a bbox or the name of a bounds to the geometry of the
+/// area it covers along with the bbox of that geometry
+///
+pub fn area(conn: &impl postgres::GenericConnection, bbox: &Option<Vec<f64>>, bounds: &Option<String>) -> Result<(geo::Geometry<f64>, Vec<f64>), HecateError> {
+    match (bbox, bounds) {
+        (Some(bbox), None) => {
+            validate::bbox(bbox)?;
+
+            let geom = geo::Geometry::Polygon(geo::Polygon::new(geo::LineString::from(vec![
+                (bbox[0], bbox[1]),
+                (bbox[2], bbox[1]),
+                (bbox[2], bbox[3]),
+                (bbox[0], bbox[3]),
+                (bbox[0], bbox[1])
+            ]), Vec::new()));
+
+            Ok((geom, bbox.clone()))
+        },
+        (None, Some(bounds)) => {
+            let geom = bounds::geom(conn, bounds)?;
+
+            match bounding_box(&geom) {
+                Some(bbox) => Ok((geom, bbox)),
+                None => Err(HecateError::new(500, String::from("Invalid bounds geometry"), None))
+            }
+        },
+        _ => Err(HecateError::new(400, String::from("Either bbox or bounds param must be specified"), None))
+    }
+}
+
+///
+/// Return the [minX, minY, maxX, maxY] bbox of an area geometry
+///
+pub fn bounding_box(geom: &geo::Geometry<f64>) -> Option<Vec<f64>> {
+    let rect = match geom {
+        geo::Geometry::MultiPolygon(ref geom) => geom.bounding_rect(),
+        geo::Geometry::Polygon(ref geom) => geom.bounding_rect(),
+        _ => None
+    };
+
+    rect.map(|rect| vec![rect.min.x, rect.min.y, rect.max.x, rect.max.y])
+}
+
+///
+/// Return the number of web mercator tiles covering a bbox between the given zooms,
+/// an upper bound on the number of tiles covering any geometry within the bbox
+///
+pub fn count(bbox: &[f64], minzoom: u8, maxzoom: u8) -> u64 {
+    // Latitude limits of web mercator
+    let lat = |lat: f64| lat.max(-85.051_128_78).min(85.051_128_78).to_radians();
+
+    (minzoom..=maxzoom).map(|zoom| {
+        let n = f64::from(1u32 << zoom);
+
+        let x = |lon: f64| (((lon + 180.0) / 360.0 * n).floor()).max(0.0).min(n - 1.0) as u64;
+        let y = |lat: f64| (((1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / std::f64::consts::PI) / 2.0 * n).floor()).max(0.0).min(n - 1.0) as u64;
+
+        (x(bbox[2]) - x(bbox[0]) + 1) * (y(lat(bbox[1])) - y(lat(bbox[3])) + 1)
+    }).sum()
+}
+
+///
+/// Ensure the number of tiles covering a bbox between the given zooms is within
+/// the configured limit, before any of them are generated
+///
+pub fn limit(config: &Config, bbox: &[f64], minzoom: u8, maxzoom: u8) -> Result<u64, HecateError> {
+    let tiles = count(bbox, minzoom, maxzoom);
+
+    if tiles > config.max_tiles as u64 {
+        return Err(HecateError::new(400, format!("Area covers up to {} tiles, more than the limit of {}", tiles, config.max_tiles), None));
+    }
+
+    Ok(tiles)
+}
+
+///
+/// Return every tile covering a geometry between the given zooms
+///
+pub fn cover(geom: &geo::Geometry<f64>, minzoom: u8, maxzoom: u8) -> Result<Vec<(i32, i32, u8)>, HecateError> {
+    let mut tiles: Vec<(i32, i32, u8)> = Vec::new();
+
+    for zoom in minzoom..=maxzoom {
+        match tilecover::tiles(geom, zoom) {
+            Ok(zoomtiles) => tiles.extend(zoomtiles),
+            Err(_err) => {
+                return Err(HecateError::new(500, String::from("Could not generate tilecover"), None));
+            }
+        };
+    }
+
+    Ok(tiles)
+}
+
 ///
 /// Database friendly connection to return a tile if it exists
 /// and if not create & cache it
@@ -132,24 +600,44 @@ pub fn regen(conn: &impl postgres::GenericConnection, z: u8, x: u32, y: u32) ->
 pub fn get(
     conn_read: &impl postgres::GenericConnection,
     conn_write: &impl postgres::GenericConnection,
+    config: &Config,
     z: u8, x: u32, y: u32,
     regen: bool
-) -> Result<Vec<u8>, HecateError> {
+) -> Result<Tile, HecateError> {
     if !regen {
-        if let Some(tile) = db_get(conn_read, format!("{}/{}/{}", z, x, y))? {
+        if let Some(tile) = db_get(conn_read, format!("{}/{}/{}", z, x, y), config.ttl)? {
             return Ok(tile);
         }
     }
 
-    let tile = db_create(conn_read, z, x, y)?;
+    let tile = db_create(conn_read, config, z, x, y)?;
 
     // A failing cache should be logged but not affect the returned response
     // since we have already generated a valid tile
     match db_cache(conn_write, format!("{}/{}/{}", z, x, y), &tile) {
-        Ok(_) => Ok(tile),
+        Ok(created) => Ok(Tile {
+            tile,
+            created
+        }),
         Err(err) => {
             println!("{}", err.as_log());
-            Ok(tile)
+            Ok(Tile {
+                tile,
+                created: chrono::Utc::now()
+            })
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tile_count() {
+        assert_eq!(count(&[-180.0, -90.0, 180.0, 90.0], 0, 1), 5);
+        assert_eq!(count(&[-180.0, -90.0, 180.0, 90.0], 0, 17), 22_906_492_245);
+        assert_eq!(count(&[0.1, 0.1, 0.2, 0.2], 0, 2), 3);
+        assert_eq!(count(&[-0.1, -0.1, 0.1, 0.1], 1, 1), 4);
+    }
+}
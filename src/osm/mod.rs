@@ -7,7 +7,7 @@ use crate::feature;
 use crate::osm::node::Node;
 use crate::osm::way::Way;
 use crate::osm::rel::Rel;
-use crate::osm::tree::OSMTree;
+pub use crate::osm::tree::OSMTree;
 
 use std::string;
 use std::num;
@@ -30,6 +30,7 @@ pub enum XMLError {
     InvalidWay(String),
     InvalidWayRef,
     InvalidRel(String),
+    InvalidRelRef,
     InvalidXML,
     InvalidFeature,
     NotFoundError,
@@ -52,6 +53,7 @@ impl XMLError {
             XMLError::InvalidWay(ref msg) => format!("Invalid Way: {}", msg),
             XMLError::InvalidWayRef => String::from("Invalid Way Reference"),
             XMLError::InvalidRel(ref msg) => format!("Invalid Relation: {}", msg),
+            XMLError::InvalidRelRef => String::from("Invalid Relation Member Reference"),
             XMLError::InvalidXML => String::from("Invalid XML"),
             XMLError::NotFoundError => String::from("Not Found"),
             XMLError::InvalidFeature => String::from("Invalid Feature"),
@@ -96,7 +98,7 @@ pub fn unescape(prop: String) -> String {
     prop.replace("&amp;", "&").replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&#39;", "'")
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum Value {
     None,
     Node,
@@ -121,24 +123,263 @@ pub trait Generic {
     fn is_valid(&self) -> Result<bool, String>;
 }
 
+///
+/// First id of the nodes synthesized for the vertices of a feature
+///
+pub static NODE_BASE: i64 = 7_000_000_000_000_000_000;
+
+///
+/// First id of the ways synthesized for the lines & rings of a feature
+///
+pub static WAY_BASE: i64 = 8_000_000_000_000_000_000;
+
+///
+/// Maximum number of vertices, or of member ways, of a single feature
+///
+pub static VERTICES: i64 = 1_000_000;
+
+///
+/// Synthesized nodes & ways have an id derived from the id of their feature
+/// & the index of the vertex or way within it, so that downloading the same
+/// feature always produces the same OSM elements
+///
+pub fn node_id(feature: i64, vertex: usize) -> Result<i64, XMLError> {
+    synthetic_id(NODE_BASE, feature, vertex)
+}
+
+pub fn way_id(feature: i64, way: usize) -> Result<i64, XMLError> {
+    synthetic_id(WAY_BASE, feature, way)
+}
+
+fn synthetic_id(base: i64, feature: i64, index: usize) -> Result<i64, XMLError> {
+    if feature < 0 || feature >= (WAY_BASE - NODE_BASE) / VERTICES || index as i64 >= VERTICES {
+        return Err(XMLError::EncodingFailed);
+    }
+
+    Ok(base + feature * VERTICES + index as i64)
+}
+
+///
+/// Return the feature id & vertex index a synthesized node id was derived from
+///
+pub fn vertex(id: i64) -> Option<(i64, usize)> {
+    if id < NODE_BASE || id >= WAY_BASE {
+        return None;
+    }
+
+    Some(((id - NODE_BASE) / VERTICES, ((id - NODE_BASE) % VERTICES) as usize))
+}
+
+///
+/// Return the feature id & way index a synthesized way id was derived from
+///
+pub fn way(id: i64) -> Option<(i64, usize)> {
+    if id < WAY_BASE || id >= WAY_BASE + (WAY_BASE - NODE_BASE) {
+        return None;
+    }
+
+    Some(((id - WAY_BASE) / VERTICES, ((id - WAY_BASE) % VERTICES) as usize))
+}
+
+///
+/// Return the id of the feature an element is synthesized from
+///
+pub fn feature_id(element: &Value, id: i64) -> i64 {
+    match element {
+        Value::Node => vertex(id).map_or(id, |(feature, _)| feature),
+        Value::Way => way(id).map_or(id, |(feature, _)| feature),
+        _ => id
+    }
+}
+
+///
+/// Positions of a geometry in the order their nodes are synthesized
+///
+fn positions(geom: &mut geojson::Value) -> Vec<&mut geojson::Position> {
+    match geom {
+        geojson::Value::Point(ref mut point) => vec![point],
+        geojson::Value::MultiPoint(ref mut points) | geojson::Value::LineString(ref mut points) => points.iter_mut().collect(),
+        geojson::Value::MultiLineString(ref mut lines) | geojson::Value::Polygon(ref mut lines) => lines.iter_mut().flatten().collect(),
+        geojson::Value::MultiPolygon(ref mut polygons) => polygons.iter_mut().flatten().flatten().collect(),
+        geojson::Value::GeometryCollection(_) => Vec::new()
+    }
+}
+
+///
+/// Return the position of the vertex of a feature with the given index
+///
+pub fn vertex_position(feat: &geojson::Feature, index: usize) -> Option<geojson::Position> {
+    let mut geom = feat.geometry.as_ref()?.value.clone();
+
+    positions(&mut geom).get(index).map(|position| position.to_vec())
+}
+
+///
+/// Return the [minX, minY, maxX, maxY] bounds of the vertices of a feature
+///
+pub fn extent(feat: &geojson::Feature) -> Option<Vec<f64>> {
+    let mut geom = feat.geometry.as_ref()?.value.clone();
+
+    positions(&mut geom).iter().fold(None, |extent: Option<Vec<f64>>, position| match extent {
+        None => Some(vec![position[0], position[1], position[0], position[1]]),
+        Some(extent) => Some(vec![
+            extent[0].min(position[0]),
+            extent[1].min(position[1]),
+            extent[2].max(position[0]),
+            extent[3].max(position[1])
+        ])
+    })
+}
+
+///
+/// Move every vertex of a feature at one position to another,
+/// returning whether any vertex was moved
+///
+pub fn move_vertex(feat: &mut geojson::Feature, from: &[f64], to: &[f64]) -> bool {
+    let geom = match feat.geometry {
+        Some(ref mut geom) => geom,
+        None => { return false; }
+    };
+
+    let mut moved = false;
+    for position in positions(&mut geom.value) {
+        if position.as_slice() == from {
+            *position = to.to_vec();
+            moved = true;
+        }
+    }
+
+    moved
+}
+
+///
+/// Return the feature id, vertex index, node version & new position of each
+/// untagged synthesized node modified in an osmChange
+///
+/// Moving a vertex in an editor only uploads its node, and not the way or
+/// relation of the feature it belongs to
+///
+pub fn vertex_moves(tree: &OSMTree) -> Vec<(i64, usize, i64, geojson::Position)> {
+    let mut moves: Vec<(i64, usize, i64, geojson::Position)> = tree.get_nodes().values().filter(|node| {
+        node.action == Some(Action::Modify) && !node.has_tags()
+    }).filter_map(|node| {
+        let (feature, index) = vertex(node.id?)?;
+
+        Some((feature, index, i64::from(node.version?), vec![f64::from(node.lon?), f64::from(node.lat?)]))
+    }).collect();
+
+    moves.sort_by_key(|(feature, index, _, _)| (*feature, *index));
+
+    moves
+}
+
+///
+/// Return the id & version of each untagged synthesized way modified in an osmChange,
+/// along with the feature of the stored relation it belongs to, rebuilt from its ways
+///
+/// Adding, moving or removing a vertex of a ring or line in an editor only uploads
+/// its way, and not the relation of the feature it belongs to
+///
+pub fn way_modifies(tree: &OSMTree) -> Result<Vec<(i64, i64, geojson::Feature)>, XMLError> {
+    let mut modifies: Vec<(i64, i64, geojson::Feature)> = Vec::new();
+
+    for w in tree.get_ways().values() {
+        if w.action != Some(Action::Modify) || w.has_tags() { continue; }
+
+        let (feature, _) = match way(w.id.unwrap()) {
+            Some(way) => way,
+            None => continue
+        };
+
+        let mut rel = match tree.get_rel(feature) {
+            Ok(rel) => rel.clone(),
+            Err(_) => { return Err(XMLError::InvalidWay(String::from("Relation of way not found in tree"))); }
+        };
+
+        // A relation that is part of the upload already includes the way
+        if rel.action.is_some() { continue; }
+
+        rel.action = Some(Action::Modify);
+
+        modifies.push((w.id.unwrap(), i64::from(w.version.unwrap()), rel.to_feat(tree)?));
+    }
+
+    modifies.sort_by_key(|(id, _, _)| *id);
+
+    Ok(modifies)
+}
+
+///
+/// Return the type, id & feature id of each synthesized node or way deleted in an osmChange
+///
+pub fn synthesized_deletes(tree: &OSMTree) -> Vec<(Value, i64, i64)> {
+    let mut deletes: Vec<(Value, i64, i64)> = Vec::new();
+
+    for node in tree.get_nodes().values() {
+        if node.action != Some(Action::Delete) { continue; }
+
+        if let Some((feature, _)) = vertex(node.id.unwrap()) {
+            deletes.push((Value::Node, node.id.unwrap(), feature));
+        }
+    }
+
+    for w in tree.get_ways().values() {
+        if w.action != Some(Action::Delete) { continue; }
+
+        if let Some((feature, _)) = way(w.id.unwrap()) {
+            deletes.push((Value::Way, w.id.unwrap(), feature));
+        }
+    }
+
+    deletes
+}
+
 pub struct OSMTypes {
-    node_it: i64,
-    way_it: i64,
-    nodes: String,
-    ways: String,
-    rels: String
+    feature: i64,
+    version: i64,
+    vertex: usize,
+    way: usize,
+    shared: HashMap<(u64, u64), i64>,
+    nodes: Vec<(i64, String)>,
+    ways: Vec<(i64, String)>,
+    rels: Vec<(i64, String)>,
+    way_nodes: HashMap<i64, Vec<i64>>
 }
 
 impl OSMTypes {
     pub fn new() -> OSMTypes {
         OSMTypes {
-            node_it: 7_000_000_000_000_000_000,
-            way_it: 8_000_000_000_000_000_000,
-            nodes: String::from(""),
-            ways: String::from(""),
-            rels: String::from("")
+            feature: 0,
+            version: 1,
+            vertex: 0,
+            way: 0,
+            shared: HashMap::new(),
+            nodes: Vec::new(),
+            ways: Vec::new(),
+            rels: Vec::new(),
+            way_nodes: HashMap::new()
         }
     }
+
+    ///
+    /// Start synthesizing the nodes & ways of a given feature
+    ///
+    fn feature(&mut self, feat: &geojson::Feature) -> Result<(), XMLError> {
+        self.feature = match feature::get_id(feat) {
+            Ok(id) => id,
+            Err(_) => { return Err(XMLError::InvalidFeature); }
+        };
+
+        self.version = match feature::get_version(feat) {
+            Ok(version) => version,
+            Err(_) => { return Err(XMLError::InvalidFeature); }
+        };
+
+        self.vertex = 0;
+        self.way = 0;
+
+        Ok(())
+    }
 }
 
 pub fn to_diffresult(ids: HashMap<i64, feature::Response>, tree: OSMTree) -> Result<String, XMLError> {
@@ -150,10 +391,15 @@ pub fn to_diffresult(ids: HashMap<i64, feature::Response>, tree: OSMTree) -> Res
                 diffres.push_str(&*format!(r#"<node old_id="{}" new_id="{}" new_version="{}"/>"#, diffid.old.unwrap(), diffid.new.unwrap(), diffid.version.unwrap()));
             }
         } else if n.action == Some(Action::Modify) {
-            if let Some(diffid) = ids.get(&n.id.unwrap()) {
+            // A moved vertex takes the version of the feature it belongs to
+            let diffid = ids.get(&n.id.unwrap()).or_else(|| {
+                vertex(n.id.unwrap()).and_then(|(feature, _)| ids.get(&feature))
+            });
+
+            if let Some(diffid) = diffid {
                 diffres.push_str(&*format!(r#"<node old_id="{}" new_id="{}" new_version="{}"/>"#, n.id.unwrap(), n.id.unwrap(), diffid.version.unwrap()));
             }
-        } else if n.action == Some(Action::Delete) && ids.get(&n.id.unwrap()).is_some() {
+        } else if n.action == Some(Action::Delete) && ids.contains_key(&feature_id(&Value::Node, n.id.unwrap())) {
             diffres.push_str(&*format!(r#"<node old_id="{}"/>"#, n.id.unwrap()));
         }
     }
@@ -164,16 +410,27 @@ pub fn to_diffresult(ids: HashMap<i64, feature::Response>, tree: OSMTree) -> Res
                 diffres.push_str(&*format!(r#"<way old_id="{}" new_id="{}" new_version="{}"/>"#, diffid.old.unwrap(), diffid.new.unwrap(), diffid.version.unwrap()));
             }
         } else if w.action == Some(Action::Modify) {
-            if let Some(diffid) = ids.get(&w.id.unwrap()) {
+            // A synthesized way takes the version of the feature it belongs to
+            if let Some(diffid) = ids.get(&feature_id(&Value::Way, w.id.unwrap())) {
                 diffres.push_str(&*format!(r#"<way old_id="{}" new_id="{}" new_version="{}"/>"#, w.id.unwrap(), w.id.unwrap(), diffid.version.unwrap()));
             }
-        } else if w.action == Some(Action::Delete) && ids.get(&w.id.unwrap()).is_some() {
+        } else if w.action == Some(Action::Delete) && ids.contains_key(&feature_id(&Value::Way, w.id.unwrap())) {
             diffres.push_str(&*format!(r#"<way old_id="{}"/>"#, w.id.unwrap()));
         }
     }
 
-    for _ in tree.get_rels().values() {
-
+    for r in tree.get_rels().values() {
+        if r.action == Some(Action::Create) {
+            if let Some(diffid) = ids.get(&r.id.unwrap()) {
+                diffres.push_str(&*format!(r#"<relation old_id="{}" new_id="{}" new_version="{}"/>"#, diffid.old.unwrap(), diffid.new.unwrap(), diffid.version.unwrap()));
+            }
+        } else if r.action == Some(Action::Modify) {
+            if let Some(diffid) = ids.get(&r.id.unwrap()) {
+                diffres.push_str(&*format!(r#"<relation old_id="{}" new_id="{}" new_version="{}"/>"#, r.id.unwrap(), r.id.unwrap(), diffid.version.unwrap()));
+            }
+        } else if r.action == Some(Action::Delete) && ids.contains_key(&r.id.unwrap()) {
+            diffres.push_str(&*format!(r#"<relation old_id="{}"/>"#, r.id.unwrap()));
+        }
     }
 
     diffres.push_str(r#"</diffResult>"#);
@@ -224,9 +481,13 @@ pub fn to_delta(body: &str) -> Result<HashMap<String, Option<String>>, XMLError>
     Ok(map)
 }
 
-pub fn to_features(body: &str) -> Result<(geojson::FeatureCollection, OSMTree), XMLError> {
-    let tree = tree_parser(&body)?;
-
+///
+/// Convert the elements of a parsed osmChange into features
+///
+/// Elements without an action were resolved from the stored features
+/// to complete the upload and are not features of it
+///
+pub fn to_features(tree: &OSMTree) -> Result<geojson::FeatureCollection, XMLError> {
     let mut fc = geojson::FeatureCollection {
         bbox: None,
         features: vec![],
@@ -234,26 +495,132 @@ pub fn to_features(body: &str) -> Result<(geojson::FeatureCollection, OSMTree),
     };
 
     for rel in tree.get_rels().values() {
-        if rel.action != Some(Action::Delete) && !rel.has_tags() { continue; }
+        if rel.action.is_none() || (rel.action != Some(Action::Delete) && !rel.has_tags()) { continue; }
 
-        fc.features.push(rel.to_feat(&tree)?);
+        fc.features.push(rel.to_feat(tree)?);
     }
 
     for way in tree.get_ways().values() {
-        if way.action != Some(Action::Delete) && !way.has_tags() { continue; }
+        if way.action.is_none() || (way.action != Some(Action::Delete) && !way.has_tags()) { continue; }
+
+        // Synthesized ways are removed by modifying the feature they belong to
+        if way.action == Some(Action::Delete) && self::way(way.id.unwrap()).is_some() { continue; }
 
-        fc.features.push(way.to_feat(&tree)?);
+        fc.features.push(way.to_feat(tree)?);
     }
 
     for node in tree.get_nodes().values() {
-        if node.action != Some(Action::Delete) && !node.has_tags() { continue; }
+        if node.action.is_none() || (node.action != Some(Action::Delete) && !node.has_tags()) { continue; }
+
+        // Synthesized nodes are removed by modifying the feature they belong to
+        if node.action == Some(Action::Delete) && vertex(node.id.unwrap()).is_some() { continue; }
 
-        let n = node.to_feat(&tree)?;
+        let n = node.to_feat(tree)?;
 
         fc.features.push(n);
     }
 
-    Ok((fc, tree))
+    Ok(fc)
+}
+
+///
+/// Return the existing nodes & ways referenced by the ways & relations of an
+/// osmChange that are not part of it, and the relation of each modified
+/// synthesized way that is not part of it
+///
+/// Editors only upload changed elements, so a modified relation or way will
+/// usually reference unchanged members & nodes
+///
+pub fn missing(tree: &OSMTree) -> Vec<(Value, i64)> {
+    let mut missing: Vec<(Value, i64)> = Vec::new();
+
+    for way in tree.get_ways().values() {
+        if way.action == Some(Action::Delete) { continue; }
+
+        if way.action == Some(Action::Modify) {
+            if let Some((feature, _)) = self::way(way.id.unwrap()) {
+                if tree.get_rel(feature).is_err() && !missing.contains(&(Value::Rel, feature)) {
+                    missing.push((Value::Rel, feature));
+                }
+            }
+        }
+
+        for nd in &way.nodes {
+            if *nd > 0 && tree.get_node(*nd).is_err() && !missing.contains(&(Value::Node, *nd)) {
+                missing.push((Value::Node, *nd));
+            }
+        }
+    }
+
+    for rel in tree.get_rels().values() {
+        if rel.action == Some(Action::Delete) { continue; }
+
+        for member in &rel.members {
+            let rref = match member.rref {
+                Some(rref) if rref > 0 => rref,
+                _ => continue
+            };
+
+            let found = match member.rtype {
+                Some(Value::Node) => tree.get_node(rref).is_ok(),
+                Some(Value::Way) => tree.get_way(rref).is_ok(),
+                _ => true
+            };
+
+            if !found {
+                let element = (member.rtype.clone().unwrap(), rref);
+
+                if !missing.contains(&element) {
+                    missing.push(element);
+                }
+            }
+        }
+    }
+
+    missing
+}
+
+///
+/// Add each of the given elements synthesized from a stored feature to an
+/// osmChange, unless it is already part of it
+///
+/// Added elements have no action, and are only used to resolve references
+///
+pub fn add_stored(tree: &mut OSMTree, feat: &geojson::Feature, elements: &[(Value, i64)]) -> Result<(), XMLError> {
+    let mut xml = String::from(r#"<osmChange version="0.6"><modify>"#);
+
+    for (element, id, element_xml) in feature_elements(feat)? {
+        if elements.contains(&(element, id)) {
+            xml.push_str(&element_xml);
+        }
+    }
+
+    xml.push_str("</modify></osmChange>");
+
+    let mut stored = tree_parser(&xml)?;
+
+    for (id, mut node) in stored.get_nodes_mut().drain() {
+        if tree.get_node(id).is_err() {
+            node.action = None;
+            tree.add_node(node)?;
+        }
+    }
+
+    for (id, mut way) in stored.get_ways_mut().drain() {
+        if tree.get_way(id).is_err() {
+            way.action = None;
+            tree.add_way(way)?;
+        }
+    }
+
+    for (id, mut rel) in stored.get_rels_mut().drain() {
+        if tree.get_rel(id).is_err() {
+            rel.action = None;
+            tree.add_rel(rel)?;
+        }
+    }
+
+    Ok(())
 }
 
 pub fn tree_parser(body: &str) -> Result<OSMTree, XMLError> {
@@ -458,11 +825,19 @@ pub fn tree_parser(body: &str) -> Result<OSMTree, XMLError> {
     }
 }
 
-pub fn from_features(fc: &geojson::FeatureCollection) -> Result<String, XMLError> {
-    let mut xml: String = String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osm version="0.6" generator="ROSM">"#);
+///
+/// Synthesize the OSM elements of each feature
+///
+fn elements(fc: &geojson::FeatureCollection) -> Result<OSMTypes, XMLError> {
     let mut osm = OSMTypes::new();
 
-    for feat in &fc.features {
+    // Vertices shared between features belong to the feature with the lowest id
+    let mut features: Vec<&geojson::Feature> = fc.features.iter().collect();
+    features.sort_by_key(|feat| feature::get_id(feat).unwrap_or(0));
+
+    for feat in features {
+        osm.feature(feat)?;
+
         match feat.geometry {
             Some(ref geom) => {
                 match geom.value {
@@ -491,14 +866,336 @@ pub fn from_features(fc: &geojson::FeatureCollection) -> Result<String, XMLError
         }
     }
 
-    xml.push_str(&*osm.nodes);
-    xml.push_str(&*osm.ways);
-    xml.push_str(&*osm.rels);
+    Ok(osm)
+}
+
+fn document(elements: &[&String]) -> String {
+    let mut xml: String = String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osm version="0.6" generator="ROSM">"#);
+
+    for element in elements {
+        xml.push_str(element);
+    }
+
     xml.push_str("</osm>");
 
+    xml
+}
+
+pub fn from_features(fc: &geojson::FeatureCollection) -> Result<String, XMLError> {
+    let osm = elements(fc)?;
+
+    let elements: Vec<&String> = osm.nodes.iter()
+        .chain(osm.ways.iter())
+        .chain(osm.rels.iter())
+        .map(|(_, element)| element)
+        .collect();
+
+    Ok(document(&elements))
+}
+
+///
+/// Return the nodes with the given ids synthesized from a list of features,
+/// or None if any of the nodes is not synthesized from them
+///
+pub fn nodes(fc: &geojson::FeatureCollection, ids: &[i64]) -> Result<Option<String>, XMLError> {
+    let osm = elements(fc)?;
+
+    let mut nodes: Vec<&String> = Vec::new();
+    for id in ids {
+        match osm.nodes.iter().find(|(node, _)| node == id) {
+            Some((_, node)) => nodes.push(node),
+            None => { return Ok(None); }
+        };
+    }
+
+    Ok(Some(document(&nodes)))
+}
+
+///
+/// Return the way with the given id synthesized from a list of features,
+/// along with every node it references, or None if the way is not synthesized from them
+///
+pub fn way_full(fc: &geojson::FeatureCollection, id: i64) -> Result<Option<String>, XMLError> {
+    let osm = elements(fc)?;
+
+    let way = match osm.ways.iter().find(|(way, _)| *way == id) {
+        Some((_, way)) => way,
+        None => { return Ok(None); }
+    };
+
+    let mut refs: Vec<i64> = osm.way_nodes.get(&id).cloned().unwrap_or_default();
+    refs.sort();
+    refs.dedup();
+
+    let mut elements: Vec<&String> = osm.nodes.iter()
+        .filter(|(node, _)| refs.binary_search(node).is_ok())
+        .map(|(_, node)| node)
+        .collect();
+    elements.push(way);
+
+    Ok(Some(document(&elements)))
+}
+
+///
+/// Return every version of an element, given every version of the feature it is
+/// synthesized from, as returned by feature::history, or None if no version of
+/// the feature synthesizes the element
+///
+/// Versions which delete the element are included with visible="false"
+///
+pub fn history(history: &serde_json::Value, element: &Value, id: i64) -> Result<Option<String>, XMLError> {
+    let name = match element {
+        Value::Node => "node",
+        Value::Way => "way",
+        Value::Rel => "relation",
+        Value::None => { return Ok(None); }
+    };
+
+    let mut versions: Vec<&serde_json::Value> = match history.as_array() {
+        Some(versions) => versions.iter().map(|version| &version["feat"]).collect(),
+        None => { return Ok(None); }
+    };
+    versions.sort_by_key(|feat| feat["version"].as_i64());
+
+    let mut output: Vec<String> = Vec::new();
+    let mut visible = false;
+
+    for feat in versions {
+        let synthesized: Option<String> = if feat["action"] == json!("delete") {
+            None
+        } else {
+            let feat: geojson::Feature = match feat.to_string().parse::<geojson::GeoJson>() {
+                Ok(geojson::GeoJson::Feature(feat)) => feat,
+                _ => { return Err(XMLError::InvalidFeature); }
+            };
+
+            feature_elements(&feat)?.into_iter().find(|(synthesized, synthesized_id, _)| {
+                synthesized == element && *synthesized_id == id
+            }).map(|(_, _, xml)| xml)
+        };
+
+        match synthesized {
+            Some(synthesized) => {
+                output.push(synthesized);
+                visible = true;
+            },
+            None => {
+                if visible {
+                    output.push(format!(r#"<{} id="{}" version="{}" visible="false"/>"#, name, id, json2str(&feat["version"])));
+                }
+                visible = false;
+            }
+        };
+    }
+
+    if output.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(document(&output.iter().collect::<Vec<&String>>())))
+}
+
+///
+/// Synthesize the elements of a single feature, without sharing vertices
+/// with other features. A deleted feature has no elements
+///
+fn feature_elements(feat: &geojson::Feature) -> Result<Vec<(Value, i64, String)>, XMLError> {
+    if feat.geometry.is_none() {
+        return Ok(Vec::new());
+    }
+
+    let osm = elements(&geojson::FeatureCollection {
+        bbox: None,
+        features: vec![feat.clone()],
+        foreign_members: None
+    })?;
+
+    Ok(osm.nodes.into_iter().map(|(id, node)| (Value::Node, id, node))
+        .chain(osm.ways.into_iter().map(|(id, way)| (Value::Way, id, way)))
+        .chain(osm.rels.into_iter().map(|(id, rel)| (Value::Rel, id, rel)))
+        .collect())
+}
+
+///
+/// Remove the version attribute from the XML of an element
+///
+fn unversioned(xml: &str, version: i64) -> String {
+    xml.replacen(&format!(r#" version="{}""#, version), "", 1)
+}
+
+///
+/// Describe the changes made by a delta as an osmChange document, given each version
+/// of a feature created by the delta & the version preceding it, as returned by delta::versions
+///
+/// Elements only synthesized from the new version are created, elements synthesized
+/// from both versions are modified if they changed & elements only synthesized from the
+/// preceding version are deleted
+///
+/// Vertices are not shared between features, as the neighbouring features at the time of
+/// the delta are not known, so vertex node ids may differ from those served by /api/0.6/map
+///
+pub fn to_osmchange(versions: &[(Option<geojson::Feature>, geojson::Feature)]) -> Result<String, XMLError> {
+    let mut create: Vec<(Value, String)> = Vec::new();
+    let mut modify: Vec<(Value, String)> = Vec::new();
+    let mut delete: Vec<(Value, String)> = Vec::new();
+
+    for (from, to) in versions {
+        let version = match feature::get_version(to) {
+            Ok(version) => version,
+            Err(_) => { return Err(XMLError::InvalidFeature); }
+        };
+
+        let (old, old_version) = match from {
+            Some(from) => match feature::get_version(from) {
+                Ok(old_version) => (feature_elements(from)?, old_version),
+                Err(_) => { return Err(XMLError::InvalidFeature); }
+            },
+            None => (Vec::new(), 0)
+        };
+        let new = feature_elements(to)?;
+
+        for (element, id, xml) in new.iter() {
+            match old.iter().find(|(old_element, old_id, _)| old_element == element && old_id == id) {
+                Some((_, _, old_xml)) => {
+                    // Every element takes the version of its feature, so only
+                    // coordinates, tags & members are compared
+                    if unversioned(old_xml, old_version) != unversioned(xml, version) {
+                        modify.push((element.clone(), xml.clone()));
+                    }
+                },
+                None => create.push((element.clone(), xml.clone()))
+            };
+        }
+
+        for (element, id, _) in old.iter() {
+            if !new.iter().any(|(new_element, new_id, _)| new_element == element && new_id == id) {
+                let name = match element {
+                    Value::Node => "node",
+                    Value::Way => "way",
+                    _ => "relation"
+                };
+
+                delete.push((element.clone(), format!(r#"<{} id="{}" version="{}"/>"#, name, id, version)));
+            }
+        }
+    }
+
+    // Elements are created & modified before the elements referencing them,
+    // and deleted after the elements referencing them
+    let rank = |element: &Value| match element {
+        Value::Node => 0,
+        Value::Way => 1,
+        _ => 2
+    };
+    create.sort_by_key(|(element, _)| rank(element));
+    modify.sort_by_key(|(element, _)| rank(element));
+    delete.sort_by_key(|(element, _)| -rank(element));
+
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osmChange version="0.6" generator="Hecate Server">"#);
+
+    for (block, elements) in [("create", create), ("modify", modify), ("delete", delete)].iter() {
+        if elements.is_empty() {
+            continue;
+        }
+
+        xml.push_str(&format!("<{}>", block));
+        for (_, element) in elements {
+            xml.push_str(element);
+        }
+        xml.push_str(&format!("</{}>", block));
+    }
+
+    xml.push_str("</osmChange>");
+
     Ok(xml)
 }
 
+///
+/// Describe a hecate user as the details of an OSM user
+///
+pub fn user_details(uid: i64, username: &str) -> Result<String, XMLError> {
+    let mut writer = Writer::new(Cursor::new(Vec::new()));
+
+    let mut xml_osm = XMLEvents::BytesStart::owned(b"osm".to_vec(), 3);
+    xml_osm.push_attribute(("version", "0.6"));
+    xml_osm.push_attribute(("generator", "Hecate Server"));
+    writer.write_event(XMLEvents::Event::Start(xml_osm))?;
+
+    let mut xml_user = XMLEvents::BytesStart::owned(b"user".to_vec(), 4);
+    xml_user.push_attribute(("id", &*uid.to_string()));
+    xml_user.push_attribute(("display_name", username));
+    writer.write_event(XMLEvents::Event::Start(xml_user))?;
+
+    writer.write_event(XMLEvents::Event::Empty(XMLEvents::BytesStart::borrowed(b"description", 11)))?;
+
+    writer.write_event(XMLEvents::Event::Start(XMLEvents::BytesStart::borrowed(b"messages", 8)))?;
+
+    let mut xml_received = XMLEvents::BytesStart::owned(b"received".to_vec(), 8);
+    xml_received.push_attribute(("count", "0"));
+    xml_received.push_attribute(("unread", "0"));
+    writer.write_event(XMLEvents::Event::Empty(xml_received))?;
+
+    let mut xml_sent = XMLEvents::BytesStart::owned(b"sent".to_vec(), 4);
+    xml_sent.push_attribute(("count", "0"));
+    writer.write_event(XMLEvents::Event::Empty(xml_sent))?;
+
+    writer.write_event(XMLEvents::Event::End(XMLEvents::BytesEnd::borrowed(b"messages")))?;
+    writer.write_event(XMLEvents::Event::End(XMLEvents::BytesEnd::borrowed(b"user")))?;
+    writer.write_event(XMLEvents::Event::End(XMLEvents::BytesEnd::borrowed(b"osm")))?;
+
+    Ok(String::from_utf8(writer.into_inner().into_inner())?)
+}
+
+///
+/// Describe a list of deltas, as returned by delta::changesets, as OSM changesets,
+/// with the props of each delta as changeset tags
+///
+pub fn changesets(deltas: &serde_json::Value) -> Result<String, XMLError> {
+    let mut writer = Writer::new(Cursor::new(Vec::new()));
+
+    let mut xml_osm = XMLEvents::BytesStart::owned(b"osm".to_vec(), 3);
+    xml_osm.push_attribute(("version", "0.6"));
+    xml_osm.push_attribute(("generator", "Hecate Server"));
+    writer.write_event(XMLEvents::Event::Start(xml_osm))?;
+
+    let empty: Vec<serde_json::Value> = Vec::new();
+    for delta in deltas.as_array().unwrap_or(&empty) {
+        let mut xml_changeset = XMLEvents::BytesStart::owned(b"changeset".to_vec(), 9);
+        xml_changeset.push_attribute(("id", &*json2str(&delta["id"])));
+        xml_changeset.push_attribute(("user", &*json2str(&delta["username"])));
+        xml_changeset.push_attribute(("uid", &*json2str(&delta["uid"])));
+        xml_changeset.push_attribute(("created_at", &*json2str(&delta["created"])));
+        xml_changeset.push_attribute(("open", if delta["open"] == json!(true) { "true" } else { "false" }));
+        xml_changeset.push_attribute(("changes_count", &*json2str(&delta["changes"])));
+
+        if let Some(bbox) = delta["bbox"].as_array() {
+            if bbox.len() == 4 {
+                xml_changeset.push_attribute(("min_lon", &*json2str(&bbox[0])));
+                xml_changeset.push_attribute(("min_lat", &*json2str(&bbox[1])));
+                xml_changeset.push_attribute(("max_lon", &*json2str(&bbox[2])));
+                xml_changeset.push_attribute(("max_lat", &*json2str(&bbox[3])));
+            }
+        }
+
+        writer.write_event(XMLEvents::Event::Start(xml_changeset))?;
+
+        if let Some(props) = delta["props"].as_object() {
+            for (k, v) in props.iter() {
+                let mut xml_tag = XMLEvents::BytesStart::owned(b"tag".to_vec(), 3);
+                xml_tag.push_attribute(("k", k.as_str()));
+                xml_tag.push_attribute(("v", &*json2str(&v)));
+                writer.write_event(XMLEvents::Event::Empty(xml_tag))?;
+            }
+        }
+
+        writer.write_event(XMLEvents::Event::End(XMLEvents::BytesEnd::borrowed(b"changeset")))?;
+    }
+
+    writer.write_event(XMLEvents::Event::End(XMLEvents::BytesEnd::borrowed(b"osm")))?;
+
+    Ok(String::from_utf8(writer.into_inner().into_inner())?)
+}
+
 pub fn point(feat: &geojson::Feature, coords: &geojson::PointType, osm: &mut OSMTypes) -> Result<bool, XMLError> {
     let mut writer = Writer::new(Cursor::new(Vec::new()));
 
@@ -527,7 +1224,7 @@ pub fn point(feat: &geojson::Feature, coords: &geojson::PointType, osm: &mut OSM
 
     writer.write_event(XMLEvents::Event::End(XMLEvents::BytesEnd::borrowed(b"node"))).unwrap();
 
-    osm.nodes.push_str(&*String::from_utf8(writer.into_inner().into_inner()).unwrap());
+    osm.nodes.push((osm.feature, String::from_utf8(writer.into_inner().into_inner()).unwrap()));
 
     Ok(true)
 }
@@ -574,7 +1271,7 @@ pub fn multipoint(feat: &geojson::Feature, coords: &Vec<geojson::PointType>, osm
 
     writer.write_event(XMLEvents::Event::End(XMLEvents::BytesEnd::borrowed(b"relation"))).unwrap();
 
-    osm.rels.push_str(&*String::from_utf8(writer.into_inner().into_inner()).unwrap());
+    osm.rels.push((osm.feature, String::from_utf8(writer.into_inner().into_inner()).unwrap()));
 
     Ok(true)
 }
@@ -591,23 +1288,15 @@ pub fn linestring(feat: &geojson::Feature, coords: &geojson::LineStringType, osm
     let mut n_refs: Vec<i64> = Vec::new();
 
     for nd in coords {
-        let n_ref: i64;
-
-        if n_refs.len() > 1 && *nd == coords[0] {
-            n_ref = n_refs[0];
-        } else {
-            let node_id = match add_node(&nd, osm) {
-                Ok(node) => node,
-                Err(_) => { return Err(XMLError::EncodingFailed); }
-            };
-
-            n_ref = node_id;
-        }
+        let node_id = match add_node(&nd, osm) {
+            Ok(node) => node,
+            Err(_) => { return Err(XMLError::EncodingFailed); }
+        };
 
-        n_refs.push(n_ref);
+        n_refs.push(node_id);
     }
 
-    for n_ref in n_refs {
+    for n_ref in &n_refs {
         let mut xml_nd = XMLEvents::BytesStart::owned(b"nd".to_vec(), 2);
         xml_nd.push_attribute(("ref", &*n_ref.to_string()));
         writer.write_event(XMLEvents::Event::Empty(xml_nd)).unwrap();
@@ -628,7 +1317,8 @@ pub fn linestring(feat: &geojson::Feature, coords: &geojson::LineStringType, osm
 
     writer.write_event(XMLEvents::Event::End(XMLEvents::BytesEnd::borrowed(b"way"))).unwrap();
 
-    osm.ways.push_str(&*String::from_utf8(writer.into_inner().into_inner()).unwrap());
+    osm.ways.push((osm.feature, String::from_utf8(writer.into_inner().into_inner()).unwrap()));
+    osm.way_nodes.insert(osm.feature, n_refs);
 
     Ok(true)
 }
@@ -677,7 +1367,7 @@ pub fn multilinestring(feat: &geojson::Feature, coords: &Vec<geojson::LineString
 
     writer.write_event(XMLEvents::Event::End(XMLEvents::BytesEnd::borrowed(b"relation"))).unwrap();
 
-    osm.rels.push_str(&*String::from_utf8(writer.into_inner().into_inner()).unwrap());
+    osm.rels.push((osm.feature, String::from_utf8(writer.into_inner().into_inner()).unwrap()));
 
     Ok(true)
 }
@@ -736,7 +1426,7 @@ pub fn polygon(feat: &geojson::Feature, coords: &geojson::PolygonType, osm: &mut
 
     writer.write_event(XMLEvents::Event::End(XMLEvents::BytesEnd::borrowed(b"relation"))).unwrap();
 
-    osm.rels.push_str(&*String::from_utf8(writer.into_inner().into_inner()).unwrap());
+    osm.rels.push((osm.feature, String::from_utf8(writer.into_inner().into_inner()).unwrap()));
 
     Ok(true)
 }
@@ -791,7 +1481,7 @@ pub fn multipolygon(feat: &geojson::Feature, coords: &Vec<geojson::PolygonType>,
 
     writer.write_event(XMLEvents::Event::End(XMLEvents::BytesEnd::borrowed(b"relation"))).unwrap();
 
-    osm.rels.push_str(&*String::from_utf8(writer.into_inner().into_inner()).unwrap());
+    osm.rels.push((osm.feature, String::from_utf8(writer.into_inner().into_inner()).unwrap()));
 
     Ok(true)
 
@@ -818,33 +1508,25 @@ pub fn add_way(coords: &geojson::LineStringType, osm: &mut OSMTypes) -> Result<i
 
     let mut xml_way = XMLEvents::BytesStart::owned(b"way".to_vec(), 3);
 
-    osm.way_it += 1;
-    let id = osm.way_it;
+    let id = way_id(osm.feature, osm.way)?;
+    osm.way += 1;
 
     xml_way.push_attribute(("id", &*id.to_string()));
-    xml_way.push_attribute(("version", "1"));
+    xml_way.push_attribute(("version", &*osm.version.to_string()));
     writer.write_event(XMLEvents::Event::Start(xml_way)).unwrap();
 
     let mut n_refs: Vec<i64> = Vec::new();
 
     for nd in coords {
-        let n_ref: i64;
-
-        if n_refs.len() > 1 && *nd == coords[0] {
-            n_ref = n_refs[0];
-        } else {
-            let node_id = match add_node(&nd, osm) {
-                Ok(node) => node,
-                Err(_) => { return Err(XMLError::EncodingFailed); }
-            };
-
-            n_ref = node_id;
-        }
+        let node_id = match add_node(&nd, osm) {
+            Ok(node) => node,
+            Err(_) => { return Err(XMLError::EncodingFailed); }
+        };
 
-        n_refs.push(n_ref);
+        n_refs.push(node_id);
     }
 
-    for n_ref in n_refs {
+    for n_ref in &n_refs {
         let mut xml_nd = XMLEvents::BytesStart::owned(b"nd".to_vec(), 2);
         xml_nd.push_attribute(("ref", &*n_ref.to_string()));
         writer.write_event(XMLEvents::Event::Empty(xml_nd)).unwrap();
@@ -853,29 +1535,40 @@ pub fn add_way(coords: &geojson::LineStringType, osm: &mut OSMTypes) -> Result<i
     writer.write_event(XMLEvents::Event::End(XMLEvents::BytesEnd::borrowed(b"way"))).unwrap();
 
     let way = String::from_utf8(writer.into_inner().into_inner()).unwrap();
-    osm.ways.push_str(&*way);
+    osm.ways.push((id, way));
+    osm.way_nodes.insert(id, n_refs);
 
     Ok(id)
 }
 
 
+///
+/// Add the node for a vertex of the current feature, returning the id of an
+/// existing node instead if a vertex has already been added at the same coordinates
+///
 pub fn add_node(coords: &geojson::PointType, osm: &mut OSMTypes) -> Result<i64, XMLError> {
+    let id = node_id(osm.feature, osm.vertex)?;
+    osm.vertex += 1;
+
+    let coordinate = (coords[0].to_bits(), coords[1].to_bits());
+    if let Some(shared) = osm.shared.get(&coordinate) {
+        return Ok(*shared);
+    }
+    osm.shared.insert(coordinate, id);
+
     let mut writer = Writer::new(Cursor::new(Vec::new()));
 
     let mut xml_node = XMLEvents::BytesStart::owned(b"node".to_vec(), 4);
 
-    osm.node_it +=  1;
-    let id = osm.node_it;
-
     xml_node.push_attribute(("id", &*id.to_string()));
-    xml_node.push_attribute(("version", "1"));
+    xml_node.push_attribute(("version", &*osm.version.to_string()));
     xml_node.push_attribute(("lat", &*coords[1].to_string()));
     xml_node.push_attribute(("lon", &*coords[0].to_string()));
 
     writer.write_event(XMLEvents::Event::Empty(xml_node)).unwrap();
 
     let node = String::from_utf8(writer.into_inner().into_inner()).unwrap();
-    osm.nodes.push_str(&*node);
+    osm.nodes.push((id, node));
 
     Ok(id)
 }
@@ -1013,11 +1706,320 @@ pub fn parse_member(xml_node: &XMLEvents::BytesStart) -> Result<(Option<Value>,
                 "relation" => Value::Rel,
                 _ => { return Err(XMLError::InternalError(String::from("invalid type"))); }
             }),
-            b"rref" => rref = Some(String::from_utf8(attr.value.into_owned()).unwrap().parse()?),
-            b"rrole" => rrole = Some(String::from_utf8(attr.value.into_owned()).unwrap().parse()?),
+            b"ref" => rref = Some(String::from_utf8(attr.value.into_owned()).unwrap().parse()?),
+            b"role" => rrole = Some(String::from_utf8(attr.value.into_owned()).unwrap().parse()?),
             _ => ()
         }
     }
 
     Ok((rtype, rref, rrole))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feature(id: i64, geom: serde_json::Value) -> geojson::Feature {
+        match json!({
+            "id": id,
+            "type": "Feature",
+            "version": 2,
+            "properties": { "highway": "residential" },
+            "geometry": geom
+        }).to_string().parse::<geojson::GeoJson>().unwrap() {
+            geojson::GeoJson::Feature(feat) => feat,
+            _ => panic!("not a feature")
+        }
+    }
+
+    #[test]
+    fn ids() {
+        assert_eq!(node_id(1, 0), Ok(7_000_000_000_001_000_000));
+        assert_eq!(node_id(12, 34), Ok(7_000_000_000_012_000_034));
+        assert_eq!(way_id(12, 3), Ok(8_000_000_000_012_000_003));
+        assert_eq!(node_id(1, 1_000_000), Err(XMLError::EncodingFailed));
+        assert_eq!(node_id(1_000_000_000_000, 0), Err(XMLError::EncodingFailed));
+
+        assert_eq!(vertex(7_000_000_000_012_000_034), Some((12, 34)));
+        assert_eq!(vertex(12), None);
+        assert_eq!(vertex(8_000_000_000_012_000_003), None);
+    }
+
+    #[test]
+    fn shared_nodes() {
+        let fc = geojson::FeatureCollection {
+            bbox: None,
+            foreign_members: None,
+            features: vec![
+                feature(4, json!({ "type": "LineString", "coordinates": [[1.0, 1.0], [2.0, 2.0]] })),
+                feature(3, json!({ "type": "LineString", "coordinates": [[0.0, 0.0], [1.0, 1.0]] }))
+            ]
+        };
+
+        let xml = from_features(&fc).unwrap();
+
+        // Feature 3 has the lower id so owns the shared vertex
+        assert_eq!(xml, String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osm version="0.6" generator="ROSM">"#)
+            + r#"<node id="7000000000003000000" version="2" lat="0" lon="0"/>"#
+            + r#"<node id="7000000000003000001" version="2" lat="1" lon="1"/>"#
+            + r#"<node id="7000000000004000001" version="2" lat="2" lon="2"/>"#
+            + r#"<way id="3" version="2"><nd ref="7000000000003000000"/><nd ref="7000000000003000001"/><tag k="highway" v="residential"/></way>"#
+            + r#"<way id="4" version="2"><nd ref="7000000000003000001"/><nd ref="7000000000004000001"/><tag k="highway" v="residential"/></way>"#
+            + r#"</osm>"#);
+
+        // Downloads are stable regardless of feature order
+        let mut reversed = fc.clone();
+        reversed.features.reverse();
+        assert_eq!(from_features(&reversed).unwrap(), xml);
+    }
+
+    #[test]
+    fn vertices() {
+        let mut feat = feature(6, json!({
+            "type": "Polygon",
+            "coordinates": [
+                [[0.0, 0.0], [4.0, 0.0], [4.0, 4.0], [0.0, 0.0]],
+                [[1.0, 1.0], [2.0, 1.0], [2.0, 2.0], [1.0, 1.0]]
+            ]
+        }));
+
+        assert_eq!(vertex_position(&feat, 1), Some(vec![4.0, 0.0]));
+        assert_eq!(vertex_position(&feat, 5), Some(vec![2.0, 1.0]));
+        assert_eq!(vertex_position(&feat, 8), None);
+
+        // Moving the first vertex of a ring also moves the vertex closing it
+        assert!(move_vertex(&mut feat, &[0.0, 0.0], &[-1.0, -1.0]));
+        assert!(!move_vertex(&mut feat, &[9.0, 9.0], &[-1.0, -1.0]));
+        assert_eq!(feat.geometry.unwrap().value, geojson::Value::Polygon(vec![
+            vec![vec![-1.0, -1.0], vec![4.0, 0.0], vec![4.0, 4.0], vec![-1.0, -1.0]],
+            vec![vec![1.0, 1.0], vec![2.0, 1.0], vec![2.0, 2.0], vec![1.0, 1.0]]
+        ]));
+    }
+
+    #[test]
+    fn element_ids() {
+        assert_eq!(way(8_000_000_000_012_000_003), Some((12, 3)));
+        assert_eq!(way(7_000_000_000_012_000_034), None);
+        assert_eq!(way(12), None);
+
+        assert_eq!(feature_id(&Value::Node, 7_000_000_000_012_000_034), 12);
+        assert_eq!(feature_id(&Value::Node, 12), 12);
+        assert_eq!(feature_id(&Value::Way, 8_000_000_000_012_000_003), 12);
+        assert_eq!(feature_id(&Value::Rel, 12), 12);
+
+        assert_eq!(extent(&feature(1, json!({ "type": "LineString", "coordinates": [[1.0, 3.0], [2.0, -1.0]] }))), Some(vec![1.0, -1.0, 2.0, 3.0]));
+    }
+
+    #[test]
+    fn elements_by_id() {
+        let fc = geojson::FeatureCollection {
+            bbox: None,
+            foreign_members: None,
+            features: vec![
+                feature(4, json!({ "type": "LineString", "coordinates": [[1.0, 1.0], [2.0, 2.0]] })),
+                feature(3, json!({ "type": "LineString", "coordinates": [[0.0, 0.0], [1.0, 1.0]] })),
+                feature(5, json!({ "type": "Point", "coordinates": [5.0, 5.0] }))
+            ]
+        };
+
+        assert_eq!(nodes(&fc, &[5, 7_000_000_000_004_000_001]).unwrap(), Some(String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osm version="0.6" generator="ROSM">"#)
+            + r#"<node id="5" version="2" lon="5" lat="5"><tag k="highway" v="residential"/></node>"#
+            + r#"<node id="7000000000004000001" version="2" lat="2" lon="2"/>"#
+            + r#"</osm>"#));
+
+        // The first vertex of feature 4 is shared with feature 3
+        assert_eq!(nodes(&fc, &[7_000_000_000_004_000_000]).unwrap(), None);
+
+        assert_eq!(way_full(&fc, 4).unwrap(), Some(String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osm version="0.6" generator="ROSM">"#)
+            + r#"<node id="7000000000003000001" version="2" lat="1" lon="1"/>"#
+            + r#"<node id="7000000000004000001" version="2" lat="2" lon="2"/>"#
+            + r#"<way id="4" version="2"><nd ref="7000000000003000001"/><nd ref="7000000000004000001"/><tag k="highway" v="residential"/></way>"#
+            + r#"</osm>"#));
+
+        assert_eq!(way_full(&fc, 5).unwrap(), None);
+    }
+
+    #[test]
+    fn stored_members() {
+        let mut tree = tree_parser(r#"<osmChange version="0.6"><modify>
+            <relation id="2" version="2">
+                <member type="way" ref="8000000000002000000" role="outer"/>
+                <member type="way" ref="8000000000002000001" role="inner"/>
+                <tag k="type" v="multipolygon"/>
+                <tag k="building" v="yes"/>
+            </relation>
+        </modify></osmChange>"#).unwrap();
+
+        assert_eq!(missing(&tree), vec![(Value::Way, 8_000_000_000_002_000_000), (Value::Way, 8_000_000_000_002_000_001)]);
+
+        let stored = feature(2, json!({ "type": "Polygon", "coordinates": [
+            [[0.0, 0.0], [4.0, 0.0], [4.0, 4.0], [0.0, 0.0]],
+            [[1.0, 0.5], [3.0, 0.5], [3.0, 2.5], [1.0, 0.5]]
+        ] }));
+
+        let ways = missing(&tree);
+        add_stored(&mut tree, &stored, &ways).unwrap();
+
+        let nodes = missing(&tree);
+        assert_eq!(nodes.len(), 6);
+
+        add_stored(&mut tree, &stored, &nodes).unwrap();
+        assert_eq!(missing(&tree), Vec::new());
+
+        let fc = to_features(&tree).unwrap();
+        assert_eq!(fc.features.len(), 1, "only the relation is a feature");
+        assert_eq!(serde_json::to_value(&fc.features[0]).unwrap(), json!({
+            "id": 2,
+            "type": "Feature",
+            "action": "modify",
+            "version": 2,
+            "properties": { "building": "yes" },
+            "geometry": { "type": "MultiPolygon", "coordinates": [[
+                [[0.0, 0.0], [4.0, 0.0], [4.0, 4.0], [0.0, 0.0]],
+                [[1.0, 0.5], [3.0, 0.5], [3.0, 2.5], [1.0, 0.5]]
+            ]] }
+        }));
+    }
+
+    #[test]
+    fn stored_way_modify() {
+        let mut tree = tree_parser(r#"<osmChange version="0.6"><create>
+            <node id="-1" version="1" lat="2" lon="2"/>
+        </create><modify>
+            <way id="8000000000002000001" version="2">
+                <nd ref="7000000000002000004"/>
+                <nd ref="7000000000002000005"/>
+                <nd ref="7000000000002000006"/>
+                <nd ref="-1"/>
+                <nd ref="7000000000002000004"/>
+            </way>
+        </modify><delete>
+            <node id="7000000000002000009" version="2"/>
+        </delete></osmChange>"#).unwrap();
+
+        assert_eq!(missing(&tree), vec![(Value::Rel, 2), (Value::Node, 7_000_000_000_002_000_004), (Value::Node, 7_000_000_000_002_000_005), (Value::Node, 7_000_000_000_002_000_006)]);
+
+        let stored = feature(2, json!({ "type": "Polygon", "coordinates": [
+            [[0.0, 0.0], [4.0, 0.0], [4.0, 4.0], [0.0, 0.0]],
+            [[1.0, 0.5], [3.0, 0.5], [3.0, 2.5], [1.0, 0.5]]
+        ] }));
+
+        while !missing(&tree).is_empty() {
+            let elements = missing(&tree);
+            add_stored(&mut tree, &stored, &elements).unwrap();
+        }
+
+        assert_eq!(to_features(&tree).unwrap().features.len(), 0, "untagged ways & synthesized deletes are not features");
+        assert_eq!(synthesized_deletes(&tree), vec![(Value::Node, 7_000_000_000_002_000_009, 2)]);
+
+        let modifies = way_modifies(&tree).unwrap();
+        assert_eq!(modifies.len(), 1);
+        assert_eq!((modifies[0].0, modifies[0].1), (8_000_000_000_002_000_001, 2));
+        assert_eq!(serde_json::to_value(&modifies[0].2).unwrap(), json!({
+            "id": 2,
+            "type": "Feature",
+            "action": "modify",
+            "version": 2,
+            "properties": { "highway": "residential" },
+            "geometry": { "type": "MultiPolygon", "coordinates": [[
+                [[0.0, 0.0], [4.0, 0.0], [4.0, 4.0], [0.0, 0.0]],
+                [[1.0, 0.5], [3.0, 0.5], [3.0, 2.5], [2.0, 2.0], [1.0, 0.5]]
+            ]] }
+        }));
+    }
+
+    #[test]
+    fn element_history() {
+        let versions = json!([{
+            "id": 3,
+            "uid": 1,
+            "username": "ingalls",
+            "feat": { "id": 6, "action": "delete", "key": null, "type": "Feature", "version": 3, "geometry": null, "properties": null }
+        }, {
+            "id": 2,
+            "uid": 1,
+            "username": "ingalls",
+            "feat": { "id": 6, "action": "modify", "key": null, "type": "Feature", "version": 2, "geometry": { "type": "LineString", "coordinates": [[0, 0], [1, 1]] }, "properties": {} }
+        }, {
+            "id": 1,
+            "uid": 1,
+            "username": "ingalls",
+            "feat": { "id": 6, "action": "create", "key": null, "type": "Feature", "version": 1, "geometry": { "type": "Point", "coordinates": [0, 0] }, "properties": {} }
+        }]);
+
+        assert_eq!(history(&versions, &Value::Way, 6).unwrap(), Some(String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osm version="0.6" generator="ROSM">"#)
+            + r#"<way id="6" version="2"><nd ref="7000000000006000000"/><nd ref="7000000000006000001"/></way>"#
+            + r#"<way id="6" version="3" visible="false"/>"#
+            + r#"</osm>"#));
+
+        assert_eq!(history(&versions, &Value::Node, 6).unwrap(), Some(String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osm version="0.6" generator="ROSM">"#)
+            + r#"<node id="6" version="1" lon="0" lat="0"></node>"#
+            + r#"<node id="6" version="2" visible="false"/>"#
+            + r#"</osm>"#));
+
+        assert_eq!(history(&versions, &Value::Rel, 6).unwrap(), None);
+    }
+
+    #[test]
+    fn osmchange() {
+        let deleted = match json!({
+            "id": 3,
+            "type": "Feature",
+            "version": 2,
+            "properties": null,
+            "geometry": null
+        }).to_string().parse::<geojson::GeoJson>().unwrap() {
+            geojson::GeoJson::Feature(feat) => feat,
+            _ => panic!("not a feature")
+        };
+
+        assert_eq!(to_osmchange(&[
+            (None, feature(1, json!({ "type": "Point", "coordinates": [0.0, 0.0] }))),
+            (
+                Some(feature(2, json!({ "type": "LineString", "coordinates": [[0.0, 0.0], [1.0, 1.0], [2.0, 2.0]] }))),
+                feature(2, json!({ "type": "LineString", "coordinates": [[0.0, 0.0], [1.0, 1.0]] }))
+            ),
+            (Some(feature(3, json!({ "type": "MultiPoint", "coordinates": [[0.0, 0.0]] }))), deleted)
+        ]).unwrap(), String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osmChange version="0.6" generator="Hecate Server">"#)
+            + r#"<create><node id="1" version="2" lon="0" lat="0"><tag k="highway" v="residential"/></node></create>"#
+            + r#"<modify>"#
+            + r#"<way id="2" version="2"><nd ref="7000000000002000000"/><nd ref="7000000000002000001"/><tag k="highway" v="residential"/></way>"#
+            + r#"</modify>"#
+            + r#"<delete>"#
+            + r#"<relation id="3" version="2"/>"#
+            + r#"<node id="7000000000002000002" version="2"/>"#
+            + r#"<node id="7000000000003000000" version="2"/>"#
+            + r#"</delete>"#
+            + r#"</osmChange>"#);
+    }
+
+    #[test]
+    fn changesets_xml() {
+        assert_eq!(user_details(1, "ingalls & co").unwrap(), String::from(r#"<osm version="0.6" generator="Hecate Server">"#)
+            + r#"<user id="1" display_name="ingalls &amp; co"><description/><messages><received count="0" unread="0"/><sent count="0"/></messages></user>"#
+            + r#"</osm>"#);
+
+        assert_eq!(changesets(&json!([{
+            "id": 2,
+            "uid": 1,
+            "username": "ingalls",
+            "created": "2019-01-01T00:00:00Z",
+            "props": { "comment": "Add a street" },
+            "open": false,
+            "changes": 1,
+            "bbox": [1.0, 2.0, 3.5, 4.0]
+        }, {
+            "id": 1,
+            "uid": 1,
+            "username": "ingalls",
+            "created": "2019-01-01T00:00:00Z",
+            "props": {},
+            "open": true,
+            "changes": 0,
+            "bbox": null
+        }])).unwrap(), String::from(r#"<osm version="0.6" generator="Hecate Server">"#)
+            + r#"<changeset id="2" user="ingalls" uid="1" created_at="2019-01-01T00:00:00Z" open="false" changes_count="1" min_lon="1" min_lat="2" max_lon="3.5" max_lat="4"><tag k="comment" v="Add a street"/></changeset>"#
+            + r#"<changeset id="1" user="ingalls" uid="1" created_at="2019-01-01T00:00:00Z" open="true" changes_count="0"></changeset>"#
+            + r#"</osm>"#);
+    }
+}
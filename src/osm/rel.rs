@@ -1,6 +1,7 @@
 use std::fmt;
 use crate::osm::*;
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Rel {
     pub id: Option<i64>,
     pub user: Option<String>,
@@ -13,6 +14,7 @@ pub struct Rel {
     pub members: Vec<RelMem>
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct RelMem {
     pub rtype: Option<Value>,
     pub rref: Option<i64>,
@@ -20,10 +22,126 @@ pub struct RelMem {
 }
 
 impl Rel {
-    pub fn set_member(&mut self, _rtype: Option<Value>, _rref: Option<i64>, _rrole: Option<String>) {
+    pub fn set_member(&mut self, rtype: Option<Value>, rref: Option<i64>, rrole: Option<String>) {
+        self.members.push(RelMem::new(rtype, rref, rrole));
+    }
+
+    ///
+    /// Return the ids of the way members with one of the given roles,
+    /// an empty role matching members without a role
+    ///
+    fn ways(&self, roles: &[&str]) -> Vec<i64> {
+        self.members.iter().filter(|member| {
+            member.rtype == Some(Value::Way)
+                && roles.contains(&member.rrole.as_ref().map(String::as_str).unwrap_or(""))
+        }).filter_map(|member| member.rref).collect()
+    }
+
+    fn coords(&self, tree: &OSMTree, nodes: &[i64]) -> Result<Vec<geojson::Position>, XMLError> {
+        let mut coords: Vec<geojson::Position> = Vec::with_capacity(nodes.len());
+
+        for nid in nodes {
+            let node = match tree.get_node(*nid) {
+                Err(_) => { return Err(XMLError::InvalidRel(String::from("Node reference not found in tree"))); },
+                Ok(n) => n
+            };
+
+            coords.push(vec![node.lon.unwrap() as f64, node.lat.unwrap() as f64]);
+        }
+
+        Ok(coords)
+    }
+
+    ///
+    /// Join member ways end to end into closed rings of node ids,
+    /// reversing ways as needed
+    ///
+    fn rings(&self, tree: &OSMTree, ways: &[i64]) -> Result<Vec<Vec<i64>>, XMLError> {
+        let mut segments: Vec<Vec<i64>> = Vec::with_capacity(ways.len());
+
+        for wid in ways {
+            match tree.get_way(*wid) {
+                Err(_) => { return Err(XMLError::InvalidRel(String::from("Way reference not found in tree"))); },
+                Ok(way) => segments.push(way.nodes.clone())
+            };
+        }
+
+        let mut rings: Vec<Vec<i64>> = Vec::new();
+
+        while !segments.is_empty() {
+            let mut ring = segments.remove(0);
+
+            while ring[0] != ring[ring.len() - 1] {
+                let last = ring[ring.len() - 1];
+
+                let next = match segments.iter().position(|segment| segment[0] == last || segment[segment.len() - 1] == last) {
+                    None => { return Err(XMLError::InvalidRel(String::from("Multipolygon rings must be closed"))); },
+                    Some(next) => next
+                };
+
+                let mut segment = segments.remove(next);
+                if segment[0] != last {
+                    segment.reverse();
+                }
+
+                ring.extend_from_slice(&segment[1..]);
+            }
+
+            if ring.len() < 4 {
+                return Err(XMLError::InvalidRel(String::from("Multipolygon rings must have at least 3 nodes")));
+            }
+
+            rings.push(ring);
+        }
 
+        Ok(rings)
     }
 
+    ///
+    /// Assemble the outer & inner ways of a multipolygon relation into polygons,
+    /// each inner ring belonging to the outer ring that contains it
+    ///
+    fn multipolygon(&self, tree: &OSMTree) -> Result<geojson::Value, XMLError> {
+        let mut polygons: Vec<geojson::PolygonType> = Vec::new();
+
+        for ring in self.rings(tree, &self.ways(&["outer", ""]))? {
+            polygons.push(vec![self.coords(tree, &ring)?]);
+        }
+
+        if polygons.is_empty() {
+            return Err(XMLError::InvalidRel(String::from("Multipolygon must have an outer way")));
+        }
+
+        for ring in self.rings(tree, &self.ways(&["inner"]))? {
+            let inner = self.coords(tree, &ring)?;
+
+            let polygon = match polygons.iter_mut().find(|polygon| contains(&polygon[0], &inner[0])) {
+                None => { return Err(XMLError::InvalidRel(String::from("Inner way must be within an outer way"))); },
+                Some(polygon) => polygon
+            };
+
+            polygon.push(inner);
+        }
+
+        Ok(geojson::Value::MultiPolygon(polygons))
+    }
+}
+
+///
+/// Even-odd test of whether a position lies within a ring
+///
+fn contains(ring: &[geojson::Position], point: &[f64]) -> bool {
+    let mut inside = false;
+
+    for edge in ring.windows(2) {
+        let (x0, y0, x1, y1) = (edge[0][0], edge[0][1], edge[1][0], edge[1][1]);
+
+        if (y0 > point[1]) != (y1 > point[1]) && point[0] < (x1 - x0) * (point[1] - y0) / (y1 - y0) + x0 {
+            inside = !inside;
+        }
+    }
+
+    inside
 }
 
 impl Generic for Rel {
@@ -59,13 +177,65 @@ impl Generic for Rel {
         !self.tags.is_empty()
     }
 
-    fn to_feat(&self, _tree: &OSMTree) -> Result<geojson::Feature, XMLError> {
+    fn to_feat(&self, tree: &OSMTree) -> Result<geojson::Feature, XMLError> {
+        let mut foreign = serde_json::Map::new();
+
+        if let Err(err) = self.is_valid() {
+            return Err(XMLError::InvalidRel(err));
+        }
+
+        foreign.insert(String::from("action"), serde_json::Value::String(match self.action {
+            Some(Action::Create) => String::from("create"),
+            Some(Action::Modify) => String::from("modify"),
+            Some(Action::Delete) => String::from("delete"),
+            _ => { return Err(XMLError::InvalidRel(String::from("Missing or invalid action"))); }
+        }));
+
+        foreign.insert(String::from("version"), json!(self.version));
+
+        // The relation type describes the geometry & is not a property of the feature
+        let mut properties = self.tags.clone();
+        let rtype = properties.remove("type");
+
+        let mut geometry: Option<geojson::Geometry> = None;
+
+        if self.action != Some(Action::Delete) {
+            geometry = Some(geojson::Geometry::new(match rtype.as_ref().and_then(|rtype| rtype.as_str()) {
+                Some("multipolygon") => self.multipolygon(tree)?,
+                Some("multilinestring") => {
+                    let mut lines: Vec<geojson::LineStringType> = Vec::new();
+
+                    for wid in self.ways(&["line", ""]) {
+                        match tree.get_way(wid) {
+                            Err(_) => { return Err(XMLError::InvalidRel(String::from("Way reference not found in tree"))); },
+                            Ok(way) => lines.push(self.coords(tree, &way.nodes)?)
+                        };
+                    }
+
+                    geojson::Value::MultiLineString(lines)
+                },
+                Some("multipoint") => {
+                    let nodes: Vec<i64> = self.members.iter().filter(|member| {
+                        member.rtype == Some(Value::Node)
+                    }).filter_map(|member| member.rref).collect();
+
+                    geojson::Value::MultiPoint(self.coords(tree, &nodes)?)
+                },
+                _ => { return Err(XMLError::InvalidRel(String::from("Only multipolygon, multilinestring & multipoint relations are supported"))); }
+            }));
+        }
+
+        let id: Option<geojson::feature::Id> = match self.id {
+            None => None,
+            Some(ref id) => Some(geojson::feature::Id::Number(serde_json::Number::from(*id)))
+        };
+
         Ok(geojson::Feature {
             bbox: None,
-            geometry: None,
-            id: None,
-            properties: None,
-            foreign_members: None
+            geometry,
+            id,
+            properties: Some(properties),
+            foreign_members: Some(foreign)
         })
     }
 
@@ -79,13 +249,30 @@ impl Generic for Rel {
             Some(_) => ()
         }
 
+        if self.action != Some(Action::Delete) && self.members.is_empty() {
+            return Err(String::from("Members cannot be empty"));
+        }
+
+        for member in &self.members {
+            if member.rtype.is_none() {
+                return Err(String::from("Member missing type"));
+            }
+
+            if member.rref.is_none() {
+                return Err(String::from("Member missing ref"));
+            }
+        }
+
         Ok(true)
     }
 }
 
 impl fmt::Display for Rel {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "[Rel: id={}]", self.id.unwrap())
+        write!(f, "[Rel: id={}]", match self.id {
+            None => String::from("None"),
+            Some(ref id) => id.to_string()
+        })
     }
 }
 
@@ -98,3 +285,98 @@ impl RelMem {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(tree: &mut OSMTree, id: i64, lon: f32, lat: f32) {
+        let mut n = Node::new();
+        n.id = Some(id);
+        n.lon = Some(lon);
+        n.lat = Some(lat);
+        n.version = Some(1);
+        n.action = Some(Action::Create);
+
+        tree.add_node(n).unwrap();
+    }
+
+    fn way(tree: &mut OSMTree, id: i64, nodes: Vec<i64>) {
+        let mut w = Way::new();
+        w.id = Some(id);
+        w.version = Some(1);
+        w.action = Some(Action::Create);
+        w.nodes = nodes;
+
+        tree.add_way(w).unwrap();
+    }
+
+    #[test]
+    fn validity() {
+        let mut r = Rel::new();
+        assert_eq!(r.is_valid().is_err(), true);
+        r.id = Some(1);
+        assert_eq!(r.is_valid().is_err(), true);
+        r.version = Some(1);
+        assert_eq!(r.is_valid(), Err(String::from("Members cannot be empty")));
+        r.set_member(Some(Value::Way), None, Some(String::from("outer")));
+        assert_eq!(r.is_valid(), Err(String::from("Member missing ref")));
+        r.members[0].rref = Some(1);
+        assert_eq!(r.is_valid(), Ok(true));
+
+        assert_eq!(format!("{}", Rel::new()), "[Rel: id=None]");
+    }
+
+    #[test]
+    fn to_feat() {
+        let mut tree = OSMTree::default();
+
+        // Outer ring split over two ways, with a single inner ring
+        node(&mut tree, -1, 0.0, 0.0);
+        node(&mut tree, -2, 4.0, 0.0);
+        node(&mut tree, -3, 4.0, 4.0);
+        node(&mut tree, -4, 0.0, 4.0);
+        node(&mut tree, -5, 1.0, 1.0);
+        node(&mut tree, -6, 2.0, 1.0);
+        node(&mut tree, -7, 2.0, 2.0);
+        way(&mut tree, -8, vec![-1, -2, -3]);
+        way(&mut tree, -9, vec![-1, -4, -3]);
+        way(&mut tree, -10, vec![-5, -6, -7, -5]);
+
+        let mut r = Rel::new();
+        r.id = Some(-11);
+        r.version = Some(1);
+        r.action = Some(Action::Create);
+        r.set_tag(String::from("type"), String::from("multipolygon"));
+        r.set_tag(String::from("building"), String::from("yes"));
+        r.set_member(Some(Value::Way), Some(-8), Some(String::from("outer")));
+        r.set_member(Some(Value::Way), Some(-10), Some(String::from("inner")));
+
+        assert_eq!(r.to_feat(&tree).err(), Some(XMLError::InvalidRel(String::from("Multipolygon rings must be closed"))));
+
+        r.set_member(Some(Value::Way), Some(-9), Some(String::from("outer")));
+
+        let mut fmem = serde_json::Map::new();
+        fmem.insert(String::from("action"), json!(String::from("create")));
+        fmem.insert(String::from("version"), json!(1));
+
+        assert_eq!(r.to_feat(&tree).ok(), Some(geojson::Feature {
+            bbox: None,
+            id: Some(geojson::feature::Id::Number(serde_json::Number::from(-11))),
+            properties: Some(json!({ "building": "yes" }).as_object().unwrap().clone()),
+            geometry: Some(geojson::Geometry::new(geojson::Value::MultiPolygon(vec![vec![
+                vec![vec![0.0, 0.0], vec![4.0, 0.0], vec![4.0, 4.0], vec![0.0, 4.0], vec![0.0, 0.0]],
+                vec![vec![1.0, 1.0], vec![2.0, 1.0], vec![2.0, 2.0], vec![1.0, 1.0]]
+            ]]))),
+            foreign_members: Some(fmem.clone())
+        }));
+
+        r.action = Some(Action::Delete);
+        fmem.insert(String::from("action"), json!(String::from("delete")));
+        assert_eq!(r.to_feat(&tree).ok().unwrap().geometry, None);
+
+        r.action = Some(Action::Modify);
+        r.set_tag(String::from("type"), String::from("route"));
+        assert_eq!(r.to_feat(&tree).is_err(), true);
+    }
+}
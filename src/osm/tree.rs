@@ -10,6 +10,7 @@ pub struct OSMTree {
 }
 
 impl OSMTree {
+    #[allow(clippy::should_implement_trait)]
     pub fn default() -> OSMTree {
         OSMTree {
             meta: HashMap::new(),
@@ -61,9 +62,9 @@ impl OSMTree {
                 return Err(XMLError::InvalidNodeRef);
             }
 
-            match self.nodes.get_mut(&nd) {
-                Some(nd) => nd.parents.push(way.id.unwrap()),
-                None => { return Err(XMLError::InternalError(String::from("unable to get node"))) }
+            // Existing nodes that are not part of the upload are resolved from the stored features
+            if let Some(nd) = self.nodes.get_mut(&nd) {
+                nd.parents.push(way.id.unwrap());
             }
         }
 
@@ -98,6 +99,22 @@ impl OSMTree {
             return Err(XMLError::InvalidRel(err));
         }
 
+        for member in &rel.members {
+            //Tree must contain member refs below 0 as these are being created in the same transaction
+            let parents = match member.rtype {
+                Some(Value::Node) => self.nodes.get_mut(&member.rref.unwrap()).map(|node| &mut node.parents),
+                Some(Value::Way) => self.ways.get_mut(&member.rref.unwrap()).map(|way| &mut way.parents),
+                _ => self.rels.get_mut(&member.rref.unwrap()).map(|rel| &mut rel.parents)
+            };
+
+            match parents {
+                Some(parents) => parents.push(rel.id.unwrap()),
+                None => if member.rref.unwrap() < 0 {
+                    return Err(XMLError::InvalidRelRef);
+                }
+            };
+        }
+
         self.rels.insert(rel.id.unwrap(), rel);
         Ok(true)
     }
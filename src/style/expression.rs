@@ -0,0 +1,475 @@
+use crate::mvt::decode::{Feature, Kind};
+use serde_json::Value;
+
+///
+/// An RGBA color with each component between 0 & 1
+///
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Color {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+    pub a: f64
+}
+
+impl Color {
+    pub fn new(r: f64, g: f64, b: f64, a: f64) -> Self {
+        Color {
+            r: r.max(0.0).min(1.0),
+            g: g.max(0.0).min(1.0),
+            b: b.max(0.0).min(1.0),
+            a: a.max(0.0).min(1.0)
+        }
+    }
+
+    ///
+    /// Parse a CSS color string, supporting hex, rgb(a), hsl(a)
+    /// & a small set of common named colors
+    ///
+    pub fn parse(color: &str) -> Option<Self> {
+        let color = color.trim().to_lowercase();
+
+        if color.starts_with('#') {
+            let hex = &color[1..];
+            let hex: Vec<f64> = hex.chars().map(|c| c.to_digit(16).map(f64::from)).collect::<Option<Vec<f64>>>()?;
+
+            return match hex.len() {
+                3 => Some(Color::new(hex[0] * 17.0 / 255.0, hex[1] * 17.0 / 255.0, hex[2] * 17.0 / 255.0, 1.0)),
+                4 => Some(Color::new(hex[0] * 17.0 / 255.0, hex[1] * 17.0 / 255.0, hex[2] * 17.0 / 255.0, hex[3] * 17.0 / 255.0)),
+                6 => Some(Color::new((hex[0] * 16.0 + hex[1]) / 255.0, (hex[2] * 16.0 + hex[3]) / 255.0, (hex[4] * 16.0 + hex[5]) / 255.0, 1.0)),
+                8 => Some(Color::new((hex[0] * 16.0 + hex[1]) / 255.0, (hex[2] * 16.0 + hex[3]) / 255.0, (hex[4] * 16.0 + hex[5]) / 255.0, (hex[6] * 16.0 + hex[7]) / 255.0)),
+                _ => None
+            };
+        }
+
+        if let (Some(open), Some(close)) = (color.find('('), color.rfind(')')) {
+            let args: Vec<f64> = color[open + 1..close].split(',').map(|arg| {
+                let arg = arg.trim();
+
+                if arg.ends_with('%') {
+                    arg.trim_end_matches('%').parse::<f64>().map(|arg| arg / 100.0).ok()
+                } else {
+                    arg.parse::<f64>().ok()
+                }
+            }).collect::<Option<Vec<f64>>>()?;
+
+            return match (&color[..open], args.len()) {
+                ("rgb", 3) => Some(Color::new(args[0] / 255.0, args[1] / 255.0, args[2] / 255.0, 1.0)),
+                ("rgba", 4) => Some(Color::new(args[0] / 255.0, args[1] / 255.0, args[2] / 255.0, args[3])),
+                ("hsl", 3) => Some(Color::hsl(args[0], args[1], args[2], 1.0)),
+                ("hsla", 4) => Some(Color::hsl(args[0], args[1], args[2], args[3])),
+                _ => None
+            };
+        }
+
+        match color.as_str() {
+            "transparent" => Some(Color::new(0.0, 0.0, 0.0, 0.0)),
+            "black" => Some(Color::new(0.0, 0.0, 0.0, 1.0)),
+            "white" => Some(Color::new(1.0, 1.0, 1.0, 1.0)),
+            "gray" | "grey" => Some(Color::new(0.502, 0.502, 0.502, 1.0)),
+            "red" => Some(Color::new(1.0, 0.0, 0.0, 1.0)),
+            "green" => Some(Color::new(0.0, 0.502, 0.0, 1.0)),
+            "blue" => Some(Color::new(0.0, 0.0, 1.0, 1.0)),
+            "yellow" => Some(Color::new(1.0, 1.0, 0.0, 1.0)),
+            "orange" => Some(Color::new(1.0, 0.647, 0.0, 1.0)),
+            "purple" => Some(Color::new(0.502, 0.0, 0.502, 1.0)),
+            _ => None
+        }
+    }
+
+    fn hsl(h: f64, s: f64, l: f64, a: f64) -> Self {
+        let h = ((h % 360.0) + 360.0) % 360.0 / 360.0;
+
+        let q = if l < 0.5 { l * (1.0 + s) } else { l + s - l * s };
+        let p = 2.0 * l - q;
+
+        let channel = |t: f64| {
+            let t = if t < 0.0 { t + 1.0 } else if t > 1.0 { t - 1.0 } else { t };
+
+            if t < 1.0 / 6.0 {
+                p + (q - p) * 6.0 * t
+            } else if t < 0.5 {
+                q
+            } else if t < 2.0 / 3.0 {
+                p + (q - p) * (2.0 / 3.0 - t) * 6.0
+            } else {
+                p
+            }
+        };
+
+        Color::new(channel(h + 1.0 / 3.0), channel(h), channel(h - 1.0 / 3.0), a)
+    }
+
+    pub fn to_value(&self) -> Value {
+        json!(format!("rgba({},{},{},{})", self.r * 255.0, self.g * 255.0, self.b * 255.0, self.a))
+    }
+}
+
+///
+/// Name of a feature's geometry type as used by style filters
+///
+fn geometry_type(feature: &Feature) -> Value {
+    match feature.kind {
+        Kind::Point => json!("Point"),
+        Kind::LineString => json!("LineString"),
+        Kind::Polygon => json!("Polygon"),
+        Kind::Unknown => json!("Unknown")
+    }
+}
+
+///
+/// Value of a feature property as referenced by a legacy filter,
+/// including the special `$type` & `$id` keys
+///
+fn legacy_get(key: &str, feature: &Feature) -> Value {
+    match key {
+        "$type" => geometry_type(feature),
+        "$id" => json!(feature.id),
+        _ => feature.properties.get(key).cloned().unwrap_or(Value::Null)
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(value) => *value,
+        _ => true
+    }
+}
+
+fn compare(op: &str, left: &Value, right: &Value) -> bool {
+    match op {
+        "==" => left == right || (left.is_number() && left.as_f64() == right.as_f64()),
+        "!=" => !compare("==", left, right),
+        _ => {
+            let ordering = match (left, right) {
+                (Value::Number(left), Value::Number(right)) => left.as_f64().partial_cmp(&right.as_f64()),
+                (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+                _ => None
+            };
+
+            match (op, ordering) {
+                ("<", Some(ordering)) => ordering == std::cmp::Ordering::Less,
+                ("<=", Some(ordering)) => ordering != std::cmp::Ordering::Greater,
+                (">", Some(ordering)) => ordering == std::cmp::Ordering::Greater,
+                (">=", Some(ordering)) => ordering != std::cmp::Ordering::Less,
+                _ => false
+            }
+        }
+    }
+}
+
+///
+/// Evaluate a style layer filter, either in the legacy filter syntax or as an expression
+///
+pub fn filter(filter: &Value, z: f64, feature: &Feature) -> bool {
+    let args = match filter.as_array() {
+        Some(args) if !args.is_empty() => args,
+        _ => { return filter.is_null() || truthy(filter); }
+    };
+
+    let op = args[0].as_str().unwrap_or("");
+    let legacy = args.len() > 1 && args[1].is_string();
+
+    match op {
+        "all" => args[1..].iter().all(|arg| self::filter(arg, z, feature)),
+        "any" => args[1..].iter().any(|arg| self::filter(arg, z, feature)),
+        "none" => !args[1..].iter().any(|arg| self::filter(arg, z, feature)),
+        "!" => args.len() == 2 && !self::filter(&args[1], z, feature),
+        "has" if legacy => legacy_get(args[1].as_str().unwrap(), feature) != Value::Null,
+        "!has" if legacy => legacy_get(args[1].as_str().unwrap(), feature) == Value::Null,
+        "in" if legacy => {
+            let value = legacy_get(args[1].as_str().unwrap(), feature);
+            args[2..].iter().any(|arg| compare("==", &value, arg))
+        },
+        "!in" if legacy => {
+            let value = legacy_get(args[1].as_str().unwrap(), feature);
+            !args[2..].iter().any(|arg| compare("==", &value, arg))
+        },
+        "==" | "!=" | "<" | "<=" | ">" | ">=" if legacy && args.len() == 3 => {
+            compare(op, &legacy_get(args[1].as_str().unwrap(), feature), &args[2])
+        },
+        _ => truthy(&eval(filter, z, feature))
+    }
+}
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(value) => value.as_f64(),
+        Value::String(value) => value.parse().ok(),
+        Value::Bool(value) => Some(if *value { 1.0 } else { 0.0 }),
+        _ => None
+    }
+}
+
+///
+/// Interpolate between two numbers or two colors
+///
+fn interpolate(t: f64, from: &Value, to: &Value) -> Value {
+    if let (Some(from), Some(to)) = (from.as_f64(), to.as_f64()) {
+        return json!(from + (to - from) * t);
+    }
+
+    match (from.as_str().and_then(Color::parse), to.as_str().and_then(Color::parse)) {
+        (Some(from), Some(to)) => Color::new(
+            from.r + (to.r - from.r) * t,
+            from.g + (to.g - from.g) * t,
+            from.b + (to.b - from.b) * t,
+            from.a + (to.a - from.a) * t
+        ).to_value(),
+        _ => if t < 1.0 { from.clone() } else { to.clone() }
+    }
+}
+
+///
+/// Interpolate an input over a list of (stop, output) pairs
+///
+fn stops(input: f64, base: f64, stops: &[(f64, Value)]) -> Value {
+    if stops.is_empty() {
+        return Value::Null;
+    } else if input <= stops[0].0 {
+        return stops[0].1.clone();
+    }
+
+    for pair in stops.windows(2) {
+        let ((lower, from), (upper, to)) = (&pair[0], &pair[1]);
+
+        if input <= *upper {
+            let t = if (base - 1.0).abs() < std::f64::EPSILON {
+                (input - lower) / (upper - lower)
+            } else {
+                (base.powf(input - lower) - 1.0) / (base.powf(upper - lower) - 1.0)
+            };
+
+            return interpolate(if t.is_finite() { t } else { 1.0 }, from, to);
+        }
+    }
+
+    stops[stops.len() - 1].1.clone()
+}
+
+///
+/// Evaluate a paint property, which may be a constant, a legacy
+/// zoom or property function, or an expression
+///
+pub fn paint(value: &Value, z: f64, feature: &Feature) -> Value {
+    match value {
+        Value::Object(function) => {
+            let input = match function.get("property").and_then(Value::as_str) {
+                Some(property) => feature.properties.get(property).cloned().unwrap_or(Value::Null),
+                None => json!(z)
+            };
+
+            let pairs: Vec<(Value, Value)> = function.get("stops").and_then(Value::as_array).map(|stops| {
+                stops.iter().filter_map(|stop| match stop.as_array() {
+                    Some(stop) if stop.len() == 2 => Some((stop[0].clone(), stop[1].clone())),
+                    _ => None
+                }).collect()
+            }).unwrap_or_else(Vec::new);
+
+            let default = function.get("default").cloned().unwrap_or(Value::Null);
+
+            match function.get("type").and_then(Value::as_str) {
+                Some("categorical") => pairs.into_iter().find(|(stop, _)| compare("==", &input, stop)).map(|(_, output)| output).unwrap_or(default),
+                Some("identity") => input,
+                Some("interval") => match number(&input) {
+                    Some(input) => pairs.iter().rev().find(|(stop, _)| number(stop).map_or(false, |stop| stop <= input)).map(|(_, output)| output.clone()).unwrap_or(default),
+                    None => default
+                },
+                _ => match number(&input) {
+                    Some(input) => {
+                        let pairs: Vec<(f64, Value)> = pairs.into_iter().filter_map(|(stop, output)| number(&stop).map(|stop| (stop, output))).collect();
+                        stops(input, function.get("base").and_then(Value::as_f64).unwrap_or(1.0), &pairs)
+                    },
+                    None => default
+                }
+            }
+        },
+        Value::Array(_) => eval(value, z, feature),
+        _ => value.clone()
+    }
+}
+
+///
+/// Evaluate the subset of the style expression language needed to style features
+///
+pub fn eval(expr: &Value, z: f64, feature: &Feature) -> Value {
+    let args = match expr.as_array() {
+        Some(args) if !args.is_empty() => args,
+        _ => { return expr.clone(); }
+    };
+
+    let op = match args[0].as_str() {
+        Some(op) => op,
+        None => { return expr.clone(); }
+    };
+
+    let arg = |i: usize| -> Value {
+        match args.get(i) {
+            Some(arg) => eval(arg, z, feature),
+            None => Value::Null
+        }
+    };
+
+    match op {
+        "literal" => args.get(1).cloned().unwrap_or(Value::Null),
+        "zoom" => json!(z),
+        "geometry-type" => geometry_type(feature),
+        "id" => json!(feature.id),
+        "get" => match arg(1).as_str() {
+            Some(key) => feature.properties.get(key).cloned().unwrap_or(Value::Null),
+            None => Value::Null
+        },
+        "has" => match arg(1).as_str() {
+            Some(key) => json!(feature.properties.contains_key(key)),
+            None => json!(false)
+        },
+        "!" => json!(!truthy(&arg(1))),
+        "all" => json!((1..args.len()).all(|i| truthy(&arg(i)))),
+        "any" => json!((1..args.len()).any(|i| truthy(&arg(i)))),
+        "==" | "!=" | "<" | "<=" | ">" | ">=" => json!(compare(op, &arg(1), &arg(2))),
+        "in" => match arg(2) {
+            Value::Array(values) => json!(values.iter().any(|value| compare("==", &arg(1), value))),
+            Value::String(haystack) => json!(arg(1).as_str().map_or(false, |needle| haystack.contains(needle))),
+            _ => json!(false)
+        },
+        "to-number" => number(&arg(1)).map_or(json!(0), |value| json!(value)),
+        "to-string" => match arg(1) {
+            Value::String(value) => json!(value),
+            Value::Null => json!(""),
+            value => json!(value.to_string())
+        },
+        "coalesce" => (1..args.len()).map(&arg).find(|value| !value.is_null()).unwrap_or(Value::Null),
+        "case" => {
+            let mut i = 1;
+            while i + 1 < args.len() {
+                if truthy(&arg(i)) {
+                    return arg(i + 1);
+                }
+                i += 2;
+            }
+
+            arg(args.len() - 1)
+        },
+        "match" => {
+            let input = arg(1);
+
+            let mut i = 2;
+            while i + 1 < args.len() {
+                let matches = match &args[i] {
+                    Value::Array(labels) => labels.iter().any(|label| compare("==", &input, label)),
+                    label => compare("==", &input, label)
+                };
+
+                if matches {
+                    return arg(i + 1);
+                }
+                i += 2;
+            }
+
+            arg(args.len() - 1)
+        },
+        "step" => {
+            let input = match number(&arg(1)) {
+                Some(input) => input,
+                None => { return arg(2); }
+            };
+
+            let mut output = arg(2);
+            let mut i = 3;
+            while i + 1 < args.len() {
+                match number(&args[i]) {
+                    Some(stop) if stop <= input => output = arg(i + 1),
+                    _ => break
+                }
+                i += 2;
+            }
+
+            output
+        },
+        "interpolate" => {
+            let base = match args.get(1).and_then(Value::as_array) {
+                Some(kind) if kind.first() == Some(&json!("exponential")) => kind.get(1).and_then(Value::as_f64).unwrap_or(1.0),
+                _ => 1.0
+            };
+
+            let input = match number(&arg(2)) {
+                Some(input) => input,
+                None => { return Value::Null; }
+            };
+
+            let mut pairs: Vec<(f64, Value)> = Vec::new();
+            let mut i = 3;
+            while i + 1 < args.len() {
+                if let Some(stop) = number(&args[i]) {
+                    pairs.push((stop, arg(i + 1)));
+                }
+                i += 2;
+            }
+
+            stops(input, base, &pairs)
+        },
+        "rgb" | "rgba" => {
+            let channel = |i: usize| number(&arg(i)).unwrap_or(0.0);
+            let alpha = if op == "rgba" { number(&arg(4)).unwrap_or(1.0) } else { 1.0 };
+
+            Color::new(channel(1) / 255.0, channel(2) / 255.0, channel(3) / 255.0, alpha).to_value()
+        },
+        "+" => json!((1..args.len()).filter_map(|i| number(&arg(i))).sum::<f64>()),
+        "*" => json!((1..args.len()).filter_map(|i| number(&arg(i))).product::<f64>()),
+        "-" => json!(number(&arg(1)).unwrap_or(0.0) - number(&arg(2)).unwrap_or(0.0)),
+        "/" => json!(number(&arg(1)).unwrap_or(0.0) / number(&arg(2)).unwrap_or(1.0)),
+        _ => Value::Null
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feature() -> Feature {
+        Feature {
+            id: Some(1),
+            kind: Kind::Polygon,
+            properties: json!({ "building": "yes", "height": 12 }).as_object().unwrap().clone(),
+            geometry: Vec::new()
+        }
+    }
+
+    #[test]
+    fn parse_color() {
+        assert_eq!(Color::parse("#fff"), Some(Color::new(1.0, 1.0, 1.0, 1.0)));
+        assert_eq!(Color::parse("#FF0000"), Some(Color::new(1.0, 0.0, 0.0, 1.0)));
+        assert_eq!(Color::parse("rgba(0, 0, 255, 0.5)"), Some(Color::new(0.0, 0.0, 1.0, 0.5)));
+        assert_eq!(Color::parse("hsl(0, 100%, 50%)"), Some(Color::new(1.0, 0.0, 0.0, 1.0)));
+        assert_eq!(Color::parse("transparent"), Some(Color::new(0.0, 0.0, 0.0, 0.0)));
+        assert_eq!(Color::parse("not a color"), None);
+    }
+
+    #[test]
+    fn filters() {
+        let feature = feature();
+
+        assert!(filter(&Value::Null, 1.0, &feature));
+        assert!(filter(&json!(["==", "building", "yes"]), 1.0, &feature));
+        assert!(filter(&json!(["==", "$type", "Polygon"]), 1.0, &feature));
+        assert!(filter(&json!(["all", ["has", "building"], [">", "height", 10]]), 1.0, &feature));
+        assert!(!filter(&json!(["in", "building", "no", "garage"]), 1.0, &feature));
+        assert!(filter(&json!(["==", ["get", "building"], "yes"]), 1.0, &feature));
+        assert!(!filter(&json!(["<", ["get", "height"], 10]), 1.0, &feature));
+        assert!(filter(&json!(["==", ["geometry-type"], "Polygon"]), 1.0, &feature));
+    }
+
+    #[test]
+    fn paint_properties() {
+        let feature = feature();
+
+        assert_eq!(paint(&json!(2), 10.0, &feature), json!(2));
+        assert_eq!(paint(&json!({ "stops": [[10, 1], [20, 11]] }), 15.0, &feature), json!(6.0));
+        assert_eq!(paint(&json!({ "property": "building", "type": "categorical", "stops": [["yes", "#f00"]], "default": "#000" }), 15.0, &feature), json!("#f00"));
+        assert_eq!(paint(&json!(["interpolate", ["linear"], ["zoom"], 0, 0, 10, 5]), 4.0, &feature), json!(2.0));
+        assert_eq!(paint(&json!(["step", ["get", "height"], "small", 10, "large"]), 4.0, &feature), json!("large"));
+        assert_eq!(paint(&json!(["match", ["get", "building"], ["yes", "house"], "#f00", "#000"]), 4.0, &feature), json!("#f00"));
+        assert_eq!(paint(&json!(["case", ["has", "name"], 1, 2]), 4.0, &feature), json!(2));
+    }
+}
@@ -1,3 +1,6 @@
+pub mod expression;
+pub mod render;
+
 use serde_json::Value;
 use crate::err::HecateError;
 
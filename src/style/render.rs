@@ -0,0 +1,371 @@
+use crate::err::HecateError;
+use crate::mvt::decode::{self, Feature, Kind};
+use super::expression::{self, Color};
+use serde_json::Value;
+
+///
+/// Width & height of a rendered raster tile in pixels
+///
+pub static SIZE: usize = 256;
+
+///
+/// An RGBA raster with each component between 0 & 1
+///
+struct Canvas {
+    pixels: Vec<Color>
+}
+
+///
+/// The set of pixels covered by a single feature, built up before being
+/// painted so overlapping parts of a feature are only blended once
+///
+struct Mask {
+    covered: Vec<bool>
+}
+
+impl Mask {
+    fn new() -> Self {
+        Mask {
+            covered: vec![false; SIZE * SIZE]
+        }
+    }
+
+    ///
+    /// Set every pixel whose center is within the bounds for which the
+    /// given function, called with the pixel center, returns true
+    ///
+    fn cover<F: Fn(f64, f64) -> bool>(&mut self, minx: f64, miny: f64, maxx: f64, maxy: f64, inside: F) {
+        let minx = minx.floor().max(0.0) as usize;
+        let miny = miny.floor().max(0.0) as usize;
+        let maxx = (maxx.ceil().max(0.0) as usize).min(SIZE);
+        let maxy = (maxy.ceil().max(0.0) as usize).min(SIZE);
+
+        for y in miny..maxy {
+            for x in minx..maxx {
+                if inside(x as f64 + 0.5, y as f64 + 0.5) {
+                    self.covered[y * SIZE + x] = true;
+                }
+            }
+        }
+    }
+
+    ///
+    /// Fill a set of rings using the even-odd rule, so that
+    /// interior rings cut holes in their exterior ring
+    ///
+    fn fill(&mut self, rings: &[Vec<(f64, f64)>]) {
+        for row in 0..SIZE {
+            let y = row as f64 + 0.5;
+
+            let mut crossings: Vec<f64> = Vec::new();
+            for ring in rings {
+                for edge in ring.windows(2) {
+                    let ((x0, y0), (x1, y1)) = (edge[0], edge[1]);
+
+                    if (y0 <= y && y1 > y) || (y1 <= y && y0 > y) {
+                        crossings.push(x0 + (y - y0) / (y1 - y0) * (x1 - x0));
+                    }
+                }
+            }
+
+            crossings.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+            for span in crossings.chunks(2) {
+                if span.len() != 2 {
+                    continue;
+                }
+
+                let start = (span[0] - 0.5).ceil().max(0.0) as usize;
+                let end = ((span[1] - 0.5).ceil().max(0.0) as usize).min(SIZE);
+
+                for x in start..end {
+                    self.covered[row * SIZE + x] = true;
+                }
+            }
+        }
+    }
+
+    ///
+    /// Stroke a line with round joins & caps
+    ///
+    fn stroke(&mut self, line: &[(f64, f64)], width: f64) {
+        let half = width / 2.0;
+
+        for segment in line.windows(2) {
+            let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+
+            self.cover(x0.min(x1) - half, y0.min(y1) - half, x0.max(x1) + half, y0.max(y1) + half, |x, y| {
+                distance((x, y), (x0, y0), (x1, y1)) <= half
+            });
+        }
+    }
+
+    ///
+    /// Cover a ring around a point from an inner to an outer radius
+    ///
+    fn circle(&mut self, center: (f64, f64), inner: f64, outer: f64) {
+        self.cover(center.0 - outer, center.1 - outer, center.0 + outer, center.1 + outer, |x, y| {
+            let d = ((x - center.0).powi(2) + (y - center.1).powi(2)).sqrt();
+
+            d <= outer && d >= inner
+        });
+    }
+}
+
+///
+/// Distance from a point to a line segment
+///
+fn distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+
+    let t = if length == 0.0 {
+        0.0
+    } else {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length).max(0.0).min(1.0)
+    };
+
+    ((p.0 - (a.0 + t * dx)).powi(2) + (p.1 - (a.1 + t * dy)).powi(2)).sqrt()
+}
+
+impl Canvas {
+    fn new() -> Self {
+        Canvas {
+            pixels: vec![Color::new(0.0, 0.0, 0.0, 0.0); SIZE * SIZE]
+        }
+    }
+
+    ///
+    /// Composite a color over every pixel in the mask
+    ///
+    fn paint(&mut self, mask: &Mask, color: Color) {
+        if color.a <= 0.0 {
+            return;
+        }
+
+        for (pixel, covered) in self.pixels.iter_mut().zip(mask.covered.iter()) {
+            if !covered {
+                continue;
+            }
+
+            let a = color.a + pixel.a * (1.0 - color.a);
+
+            *pixel = Color::new(
+                (color.r * color.a + pixel.r * pixel.a * (1.0 - color.a)) / a,
+                (color.g * color.a + pixel.g * pixel.a * (1.0 - color.a)) / a,
+                (color.b * color.a + pixel.b * pixel.a * (1.0 - color.a)) / a,
+                a
+            );
+        }
+    }
+
+    fn to_png(&self) -> Result<Vec<u8>, HecateError> {
+        let data: Vec<u8> = self.pixels.iter().flat_map(|pixel| {
+            vec![
+                (pixel.r * 255.0).round() as u8,
+                (pixel.g * 255.0).round() as u8,
+                (pixel.b * 255.0).round() as u8,
+                (pixel.a * 255.0).round() as u8
+            ]
+        }).collect();
+
+        let mut png: Vec<u8> = Vec::new();
+
+        {
+            let mut encoder = png::Encoder::new(&mut png, SIZE as u32, SIZE as u32);
+            encoder.set_color(png::ColorType::RGBA);
+            encoder.set_depth(png::BitDepth::Eight);
+
+            let res = encoder.write_header().and_then(|mut writer| writer.write_image_data(&data));
+
+            if let Err(err) = res {
+                return Err(HecateError::new(500, String::from("Failed to encode PNG"), Some(err.to_string())));
+            }
+        }
+
+        Ok(png)
+    }
+}
+
+fn number(layer: &Value, key: &str, default: f64, z: f64, feature: &Feature) -> f64 {
+    match expression::paint(&layer["paint"][key], z, feature) {
+        Value::Null => default,
+        value => value.as_f64().unwrap_or(default)
+    }
+}
+
+fn color(layer: &Value, key: &str, opacity: &str, z: f64, feature: &Feature) -> Option<Color> {
+    let color = match expression::paint(&layer["paint"][key], z, feature) {
+        Value::String(color) => Color::parse(&color)?,
+        _ => Color::new(0.0, 0.0, 0.0, 1.0)
+    };
+
+    let opacity = number(layer, opacity, 1.0, z, feature);
+
+    Some(Color::new(color.r, color.g, color.b, color.a * opacity))
+}
+
+///
+/// Render a raster tile by painting the fill, line & circle layers of a style,
+/// in order, over the features of a vector tile
+///
+/// Layer types without a CPU implementation, such as symbols, are skipped
+///
+pub fn render(style: &Value, tile: &[u8], z: u8) -> Result<Vec<u8>, HecateError> {
+    let layers = decode::decode(tile)?;
+    let zoom = f64::from(z);
+
+    let mut canvas = Canvas::new();
+
+    let empty: Vec<Value> = Vec::new();
+    for style_layer in style["layers"].as_array().unwrap_or(&empty) {
+        let hidden = style_layer["layout"]["visibility"] == json!("none")
+            || style_layer["minzoom"].as_f64().map_or(false, |minzoom| zoom < minzoom)
+            || style_layer["maxzoom"].as_f64().map_or(false, |maxzoom| zoom >= maxzoom);
+
+        if hidden {
+            continue;
+        }
+
+        let kind = style_layer["type"].as_str().unwrap_or("");
+
+        if kind == "background" {
+            let none = Feature {
+                id: None,
+                kind: Kind::Unknown,
+                properties: serde_json::Map::new(),
+                geometry: Vec::new()
+            };
+
+            if let Some(color) = color(style_layer, "background-color", "background-opacity", zoom, &none) {
+                let mut mask = Mask::new();
+                mask.covered = vec![true; SIZE * SIZE];
+                canvas.paint(&mask, color);
+            }
+
+            continue;
+        }
+
+        for layer in &layers {
+            if let Some(source_layer) = style_layer["source-layer"].as_str() {
+                if source_layer != layer.name {
+                    continue;
+                }
+            }
+
+            let scale = SIZE as f64 / f64::from(layer.extent.max(1));
+
+            for feature in &layer.features {
+                if !expression::filter(&style_layer["filter"], zoom, feature) {
+                    continue;
+                }
+
+                let geometry: Vec<Vec<(f64, f64)>> = feature.geometry.iter().map(|line| {
+                    line.iter().map(|coord| (coord.0 * scale, coord.1 * scale)).collect()
+                }).collect();
+
+                match (kind, &feature.kind) {
+                    ("fill", Kind::Polygon) => {
+                        let mut mask = Mask::new();
+                        mask.fill(&geometry);
+
+                        if let Some(fill) = color(style_layer, "fill-color", "fill-opacity", zoom, feature) {
+                            canvas.paint(&mask, fill);
+                        }
+
+                        if style_layer["paint"]["fill-outline-color"].is_null() {
+                            continue;
+                        }
+
+                        let mut outline = Mask::new();
+                        for ring in &geometry {
+                            outline.stroke(ring, 1.0);
+                        }
+
+                        if let Some(stroke) = color(style_layer, "fill-outline-color", "fill-opacity", zoom, feature) {
+                            canvas.paint(&outline, stroke);
+                        }
+                    },
+                    ("line", Kind::LineString) | ("line", Kind::Polygon) => {
+                        let width = number(style_layer, "line-width", 1.0, zoom, feature);
+
+                        let mut mask = Mask::new();
+                        for line in &geometry {
+                            mask.stroke(line, width);
+                        }
+
+                        if let Some(line) = color(style_layer, "line-color", "line-opacity", zoom, feature) {
+                            canvas.paint(&mask, line);
+                        }
+                    },
+                    ("circle", Kind::Point) => {
+                        let radius = number(style_layer, "circle-radius", 5.0, zoom, feature);
+                        let stroke = number(style_layer, "circle-stroke-width", 0.0, zoom, feature);
+
+                        let mut fill = Mask::new();
+                        let mut outline = Mask::new();
+                        for point in geometry.iter().flatten() {
+                            fill.circle(*point, 0.0, radius);
+
+                            if stroke > 0.0 {
+                                outline.circle(*point, radius, radius + stroke);
+                            }
+                        }
+
+                        if let Some(circle) = color(style_layer, "circle-color", "circle-opacity", zoom, feature) {
+                            canvas.paint(&fill, circle);
+                        }
+
+                        if stroke > 0.0 {
+                            if let Some(circle) = color(style_layer, "circle-stroke-color", "circle-stroke-opacity", zoom, feature) {
+                                canvas.paint(&outline, circle);
+                            }
+                        }
+                    },
+                    _ => ()
+                };
+            }
+        }
+    }
+
+    canvas.to_png()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks() {
+        let mut mask = Mask::new();
+        mask.fill(&[
+            vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0), (0.0, 0.0)],
+            vec![(2.0, 2.0), (2.0, 8.0), (8.0, 8.0), (8.0, 2.0), (2.0, 2.0)]
+        ]);
+
+        assert_eq!(mask.covered.iter().filter(|covered| **covered).count(), 64);
+        assert!(mask.covered[SIZE + 1]);
+        assert!(!mask.covered[5 * SIZE + 5]);
+
+        let mut mask = Mask::new();
+        mask.stroke(&[(10.0, 10.5), (20.0, 10.5)], 1.0);
+        assert_eq!(mask.covered.iter().filter(|covered| **covered).count(), 12);
+
+        let mut mask = Mask::new();
+        mask.circle((100.0, 100.0), 0.0, 2.0);
+        assert_eq!(mask.covered.iter().filter(|covered| **covered).count(), 12);
+    }
+
+    #[test]
+    fn empty_tile() {
+        let png = render(&json!({
+            "layers": [{
+                "id": "background",
+                "type": "background",
+                "paint": { "background-color": "#fff" }
+            }]
+        }), &[], 0).unwrap();
+
+        assert!(png.starts_with(&[0x89, b'P', b'N', b'G']));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::err::HecateError;
+use crate::delta;
 
 ///
 /// Validate a password against hecate's basic password rules
@@ -66,10 +67,65 @@ pub fn bbox(bbox: &[f64]) -> Result<(), HecateError> {
     Ok(())
 }
 
+///
+/// Validate a point in history given either as a numeric delta id
+/// or an ISO 8601 timestamp
+///
+pub fn at(at: &str) -> Result<delta::At, HecateError> {
+    if let Ok(delta_id) = at.parse::<i64>() {
+        if delta_id < 1 {
+            return Err(HecateError::new(400, String::from("Delta id must be greater than 0"), None));
+        }
+
+        return Ok(delta::At::Delta(delta_id));
+    }
+
+    match timestamp(at) {
+        Ok(timestamp) => Ok(delta::At::Timestamp(timestamp)),
+        Err(_) => Err(HecateError::new(400, String::from("at must be a delta id or ISO 8601 timestamp"), None))
+    }
+}
+
+///
+/// Validate an ISO 8601 timestamp, normalizing any offset to UTC
+///
+pub fn timestamp(timestamp: &str) -> Result<chrono::NaiveDateTime, HecateError> {
+    if let Ok(timestamp) = chrono::DateTime::parse_from_rfc3339(timestamp) {
+        return Ok(timestamp.naive_utc());
+    }
+
+    match timestamp.parse::<chrono::NaiveDateTime>() {
+        Ok(timestamp) => Ok(timestamp),
+        Err(_) => Err(HecateError::new(400, String::from("Timestamp must be a valid ISO 8601 timestamp"), None))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn valid_at() {
+        assert_eq!(at("5678").ok(), Some(delta::At::Delta(5678)), "ok - delta id is valid.");
+        assert_eq!(at("2019-01-01T12:30:00").ok(), Some(delta::At::Timestamp(
+            chrono::NaiveDate::from_ymd(2019, 1, 1).and_hms(12, 30, 0)
+        )), "ok - naive timestamp is valid.");
+        assert_eq!(at("2019-01-01T12:30:00Z").ok(), Some(delta::At::Timestamp(
+            chrono::NaiveDate::from_ymd(2019, 1, 1).and_hms(12, 30, 0)
+        )), "ok - rfc3339 timestamp is valid.");
+    }
+
+    #[test]
+    fn invalid_at() {
+        assert_eq!(at("0"), Err(HecateError::new(400, String::from("Delta id must be greater than 0"), None)));
+        assert_eq!(at("yesterday"), Err(HecateError::new(400, String::from("at must be a delta id or ISO 8601 timestamp"), None)));
+    }
+
+    #[test]
+    fn invalid_timestamp() {
+        assert_eq!(timestamp("2019-13-01T00:00:00"), Err(HecateError::new(400, String::from("Timestamp must be a valid ISO 8601 timestamp"), None)));
+    }
+
     #[test]
     fn valid_pw() {
         assert!(password(&String::from("yeahehImapass")).is_ok());
@@ -1,7 +1,10 @@
 use crossbeam;
 use postgres;
 use std::thread;
-use crate::{delta, mvt, webhooks};
+use crate::{mvt, webhooks};
+
+pub mod seed;
+pub use self::seed::{Seed, Seeder};
 
 #[derive(Debug,PartialEq)]
 pub enum TaskType {
@@ -26,19 +29,25 @@ impl Task {
 
 #[derive(Clone)]
 pub struct Worker {
-    sender: crossbeam::Sender<Task>
+    sender: crossbeam::Sender<Task>,
+    pub seeder: Seeder
 }
 
 impl Worker {
-    pub fn new(database: String) -> Self {
+    pub fn new(database: String, config: mvt::Config) -> Self {
         let (tx, rx) = crossbeam::channel::unbounded();
 
+        // Seeding runs in its own thread so that long running jobs
+        // do not delay webhooks & tile invalidation
+        let seeder = Seeder::new(database.clone(), config.clone());
+
         thread::Builder::new().name(String::from("Hecate Daemon")).spawn(move || {
-            worker(rx, database);
+            worker(rx, database, config);
         }).unwrap();
 
         Worker {
-            sender: tx
+            sender: tx,
+            seeder
         }
     }
 
@@ -52,7 +61,7 @@ impl Worker {
 ///
 /// Main logic for web worker
 ///
-fn worker(rx: crossbeam::Receiver<Task>, database: String) {
+fn worker(rx: crossbeam::Receiver<Task>, database: String, config: mvt::Config) {
     let conn = postgres::Connection::connect(format!("postgres://{}", database), postgres::TlsMode::None).unwrap();
 
     loop {
@@ -69,16 +78,8 @@ fn worker(rx: crossbeam::Receiver<Task>, database: String) {
         }
 
         if let TaskType::Delta(delta_id) = task.job {
-            let tiles = delta::tiles(&conn, delta_id, 14, 17).unwrap();
-
-            if tiles.is_empty() {
-                continue;
-            }
-
-            for tile in tiles {
-                if mvt::regen(&conn, tile.2, tile.0 as u32, tile.1 as u32).is_some() {
-                    println!("Daemon: Failed to generate tile: {:?}", tile);
-                }
+            if let Err(err) = mvt::invalidate(&conn, &config, delta_id) {
+                println!("Daemon: Failed to invalidate tiles for delta {}: {}", delta_id, err.as_log());
             }
         }
     }
@@ -0,0 +1,255 @@
+use crate::err::HecateError;
+use crate::mvt;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::thread;
+
+///
+/// Number of tiles to generate between progress log messages
+///
+static PROGRESS: usize = 1_000;
+
+///
+/// Number of finished jobs to keep, so their status can still be retrieved
+///
+static RETAIN: usize = 100;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Queued,
+    Running,
+    Complete,
+    Cancelled,
+    Failed
+}
+
+///
+/// A job to generate & cache every tile covering an area
+/// over a range of zooms
+///
+#[derive(Serialize, Debug, Clone)]
+pub struct Seed {
+    pub id: i64,
+    pub status: Status,
+    pub bbox: Option<Vec<f64>>,
+    pub bounds: Option<String>,
+    pub minzoom: u8,
+    pub maxzoom: u8,
+    pub total: usize,
+    pub complete: usize,
+    pub failed: usize,
+    pub error: Option<String>,
+    #[serde(skip)]
+    geom: geo::Geometry<f64>
+}
+
+impl Seed {
+    pub fn to_value(&self) -> serde_json::Value {
+        json!(self)
+    }
+}
+
+#[derive(Clone)]
+pub struct Seeder {
+    seeds: Arc<Mutex<HashMap<i64, Seed>>>,
+    next: Arc<AtomicI64>,
+    config: mvt::Config,
+    sender: crossbeam::Sender<i64>
+}
+
+impl Seeder {
+    pub fn new(database: String, config: mvt::Config) -> Self {
+        let (tx, rx) = crossbeam::channel::unbounded();
+
+        let seeds: Arc<Mutex<HashMap<i64, Seed>>> = Arc::new(Mutex::new(HashMap::new()));
+        let thread_seeds = seeds.clone();
+        let thread_config = config.clone();
+
+        thread::Builder::new().name(String::from("Hecate Seeder")).spawn(move || {
+            seeder(rx, thread_seeds, database, thread_config);
+        }).unwrap();
+
+        Seeder {
+            seeds,
+            next: Arc::new(AtomicI64::new(1)),
+            config,
+            sender: tx
+        }
+    }
+
+    ///
+    /// Queue a new seed job, returning it so the caller can obtain its id
+    ///
+    /// Jobs covering more than the configured max_tiles are rejected
+    ///
+    pub fn queue(&self, geom: geo::Geometry<f64>, bbox: Option<Vec<f64>>, bounds: Option<String>, minzoom: u8, maxzoom: u8) -> Result<Seed, HecateError> {
+        match mvt::bounding_box(&geom) {
+            Some(extent) => { mvt::limit(&self.config, &extent, minzoom, maxzoom)?; },
+            None => { return Err(HecateError::new(400, String::from("Invalid seed area"), None)); }
+        };
+
+        let seed = {
+            let mut seeds = self.seeds.lock().unwrap();
+
+            prune(&mut seeds);
+
+            let seed = Seed {
+                id: self.next.fetch_add(1, Ordering::SeqCst),
+                status: Status::Queued,
+                bbox,
+                bounds,
+                minzoom,
+                maxzoom,
+                total: 0,
+                complete: 0,
+                failed: 0,
+                error: None,
+                geom
+            };
+
+            seeds.insert(seed.id, seed.clone());
+
+            seed
+        };
+
+        if self.sender.send(seed.id).is_err() {
+            return Err(HecateError::new(500, String::from("Failed to queue seed job"), None));
+        }
+
+        Ok(seed)
+    }
+
+    pub fn list(&self) -> Vec<Seed> {
+        let seeds = self.seeds.lock().unwrap();
+
+        let mut list: Vec<Seed> = seeds.values().cloned().collect();
+        list.sort_by_key(|seed| seed.id);
+
+        list
+    }
+
+    pub fn get(&self, id: i64) -> Result<Seed, HecateError> {
+        match self.seeds.lock().unwrap().get(&id) {
+            Some(seed) => Ok(seed.clone()),
+            None => Err(HecateError::new(404, String::from("Seed Job Not Found"), None))
+        }
+    }
+
+    ///
+    /// Cancel a queued or running seed job, tiles already generated
+    /// by the job will remain in the tile cache
+    ///
+    pub fn cancel(&self, id: i64) -> Result<Seed, HecateError> {
+        match self.seeds.lock().unwrap().get_mut(&id) {
+            Some(seed) => match seed.status {
+                Status::Queued | Status::Running => {
+                    seed.status = Status::Cancelled;
+                    Ok(seed.clone())
+                },
+                _ => Err(HecateError::new(400, String::from("Seed Job has already finished"), None))
+            },
+            None => Err(HecateError::new(404, String::from("Seed Job Not Found"), None))
+        }
+    }
+}
+
+///
+/// Remove the oldest finished jobs beyond the number retained
+///
+fn prune(seeds: &mut HashMap<i64, Seed>) {
+    let mut finished: Vec<i64> = seeds.values().filter(|seed| {
+        seed.status != Status::Queued && seed.status != Status::Running
+    }).map(|seed| seed.id).collect();
+
+    if finished.len() <= RETAIN {
+        return;
+    }
+
+    finished.sort();
+
+    for id in &finished[..finished.len() - RETAIN] {
+        seeds.remove(id);
+    }
+}
+
+///
+/// Main logic for the seeding thread, jobs are run one at a time
+/// in the order they were queued
+///
+fn seeder(rx: crossbeam::Receiver<i64>, seeds: Arc<Mutex<HashMap<i64, Seed>>>, database: String, config: mvt::Config) {
+    let conn = postgres::Connection::connect(format!("postgres://{}", database), postgres::TlsMode::None).unwrap();
+
+    loop {
+        let id = match rx.recv() {
+            Ok(id) => id,
+            Err(err) => {
+                println!("Seeder: Failed to obtain job: {}", err);
+                continue;
+            }
+        };
+
+        let seed = match seeds.lock().unwrap().get_mut(&id) {
+            Some(seed) => {
+                if seed.status != Status::Queued {
+                    continue;
+                }
+
+                seed.status = Status::Running;
+                seed.clone()
+            },
+            None => continue
+        };
+
+        let tiles = match mvt::cover(&seed.geom, seed.minzoom, seed.maxzoom) {
+            Ok(tiles) => tiles,
+            Err(err) => {
+                if let Some(seed) = seeds.lock().unwrap().get_mut(&id) {
+                    seed.status = Status::Failed;
+                    seed.error = Some(err.as_log());
+                }
+
+                continue;
+            }
+        };
+
+        if let Some(seed) = seeds.lock().unwrap().get_mut(&id) {
+            seed.total = tiles.len();
+        }
+
+        println!("Seeder: Job {}: seeding {} tiles", id, tiles.len());
+
+        for (i, tile) in tiles.iter().enumerate() {
+            // A cancelled job may be pruned before it stops running
+            match seeds.lock().unwrap().get(&id) {
+                Some(seed) if seed.status != Status::Cancelled => (),
+                _ => break
+            };
+
+            let err = mvt::regen(&conn, &config, tile.2, tile.0 as u32, tile.1 as u32);
+
+            if let Some(seed) = seeds.lock().unwrap().get_mut(&id) {
+                match err {
+                    Some(err) => {
+                        println!("Seeder: Job {}: Failed to generate tile {:?}: {}", id, tile, err.as_log());
+                        seed.failed += 1;
+                    },
+                    None => seed.complete += 1
+                };
+            }
+
+            if (i + 1) % PROGRESS == 0 {
+                println!("Seeder: Job {}: {}/{} tiles processed", id, i + 1, tiles.len());
+            }
+        }
+
+        if let Some(seed) = seeds.lock().unwrap().get_mut(&id) {
+            if seed.status == Status::Running {
+                seed.status = Status::Complete;
+            }
+
+            println!("Seeder: Job {}: {:?}, {} tiles generated, {} failed", id, seed.status, seed.complete, seed.failed);
+        }
+    }
+}
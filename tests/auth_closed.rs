@@ -166,7 +166,9 @@ mod test {
                     "get": "user",
                     "regen": "user",
                     "delete": "user",
-                    "meta": "user"
+                    "meta": "user",
+                    "seed": "admin",
+                    "export": "user"
                 },
                 "user": {
                     "info": "self",
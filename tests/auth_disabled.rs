@@ -77,7 +77,9 @@ mod test {
                     "get": "disabled",
                     "regen": "disabled",
                     "delete": "disabled",
-                    "meta": "disabled"
+                    "meta": "disabled",
+                    "seed": "disabled",
+                    "export": "disabled"
                 },
                 "user": {
                     "info": "disabled",
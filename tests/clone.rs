@@ -83,6 +83,113 @@ mod test {
             assert!(resp.status().is_success());
         }
 
+        { //Modify Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "version": 1,
+                    "type": "Feature",
+                    "action": "modify",
+                    "message": "Modify Point",
+                    "properties": { "indc": false },
+                    "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Delete Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "version": 2,
+                    "type": "Feature",
+                    "action": "delete",
+                    "message": "Delete Point",
+                    "properties": null,
+                    "geometry": null
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Get Clone as of Delta 1
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/clone?delta=1")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            let mut body_str = String::from(resp.text().unwrap());
+            body_str.pop();
+            body_str.pop();
+            assert_eq!(&*body_str, r#"{"id":1,"key":null,"type":"Feature","version":1,"geometry":{"type":"Point","coordinates":[-77.0121002197266,38.9257632323745]},"properties":{"indc": true}}"#);
+            assert!(resp.status().is_success());
+        }
+
+        { //Get Clone as of Delta 2
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/clone?delta=2")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            let mut body_str = String::from(resp.text().unwrap());
+            body_str.pop();
+            body_str.pop();
+            assert_eq!(&*body_str, r#"{"id":1,"key":null,"type":"Feature","version":2,"geometry":{"type":"Point","coordinates":[1,1]},"properties":{"indc": false}}"#);
+            assert!(resp.status().is_success());
+        }
+
+        { //Get Clone as of Delta 3 excludes the deleted point
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/clone?delta=3")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            let mut body_str = String::from(resp.text().unwrap());
+            body_str.pop();
+            assert_eq!(&*body_str, "");
+            assert!(resp.status().is_success());
+        }
+
+        { //Get Clone before any edits
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/clone?timestamp=2000-01-01T00:00:00Z")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            let mut body_str = String::from(resp.text().unwrap());
+            body_str.pop();
+            assert_eq!(&*body_str, "");
+            assert!(resp.status().is_success());
+        }
+
+        { //Get Clone with both delta & timestamp
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/data/clone?delta=1&timestamp=2000-01-01T00:00:00Z")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_client_error());
+            assert_eq!(resp.text().unwrap(), "{\"code\":400,\"reason\":\"delta and timestamp params cannot be used together\",\"status\":\"Bad Request\"}");
+        }
+
         server.kill().unwrap();
     }
 }
@@ -0,0 +1,174 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn delta_diff() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Points
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Creating Points",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "name": "main st" },
+                        "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                    },{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "name": "first ave" },
+                        "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Modify & Delete Points
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Modify Points",
+                    "features": [{
+                        "id": 1,
+                        "version": 1,
+                        "type": "Feature",
+                        "action": "modify",
+                        "properties": { "name": "Main St" },
+                        "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                    },{
+                        "id": 2,
+                        "version": 1,
+                        "type": "Feature",
+                        "action": "delete",
+                        "properties": null,
+                        "geometry": null
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let mut resp = reqwest::get("http://localhost:8000/api/delta/1/diff").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["id"], json!(1));
+            assert_eq!(json_body["features"][0]["id"], json!(1));
+            assert_eq!(json_body["features"][0]["action"], json!("create"));
+            assert_eq!(json_body["features"][0]["version"], json!(1));
+            assert_eq!(json_body["features"][0]["properties"], json!({
+                "added": { "name": "main st" },
+                "removed": {},
+                "changed": {}
+            }));
+            assert_eq!(json_body["features"][0]["geometry"]["changed"], json!(true));
+            assert_eq!(json_body["features"][0]["geometry"]["type"], json!({ "from": null, "to": "Point", "changed": true }));
+            assert_eq!(json_body["features"][0]["geometry"]["vertices"], json!({ "from": null, "to": 1, "delta": 1 }));
+            assert_eq!(json_body["features"][1]["id"], json!(2));
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let mut resp = reqwest::get("http://localhost:8000/api/delta/2/diff").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["features"][0], json!({
+                "id": 1,
+                "action": "modify",
+                "version": 2,
+                "properties": {
+                    "added": {},
+                    "removed": {},
+                    "changed": {
+                        "name": { "from": "main st", "to": "Main St" }
+                    }
+                },
+                "geometry": {
+                    "changed": false,
+                    "type": { "from": "Point", "to": "Point", "changed": false },
+                    "vertices": { "from": 1, "to": 1, "delta": 0 },
+                    "hausdorff": 0,
+                    "bbox": {
+                        "from": [ 0, 0, 0, 0 ],
+                        "to": [ 0, 0, 0, 0 ]
+                    }
+                }
+            }));
+            assert_eq!(json_body["features"][1]["id"], json!(2));
+            assert_eq!(json_body["features"][1]["action"], json!("delete"));
+            assert_eq!(json_body["features"][1]["properties"], json!({
+                "added": {},
+                "removed": { "name": "first ave" },
+                "changed": {}
+            }));
+            assert_eq!(json_body["features"][1]["geometry"]["vertices"], json!({ "from": 1, "to": null, "delta": -1 }));
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let resp = reqwest::get("http://localhost:8000/api/delta/3/diff").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        server.kill().unwrap();
+    }
+}
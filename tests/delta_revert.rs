@@ -0,0 +1,252 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn delta_revert() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Modify Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "type": "Feature",
+                    "version": 1,
+                    "action": "modify",
+                    "message": "Modify a Point",
+                    "properties": { "number": "321" },
+                    "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Revert requires authentication
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/delta/2/revert")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_client_error());
+        }
+
+        { //Revert a delta that does not exist
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/delta/100/revert")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Revert Modify
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/delta/2/revert")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "3");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "id": 1,
+                "key": null,
+                "type": "Feature",
+                "version": 3,
+                "properties": { "number": "123" },
+                "geometry": { "type": "Point", "coordinates": [ 0.0, 0.0 ] }
+            }));
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let mut resp = reqwest::get("http://localhost:8000/api/delta/3").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["props"], json!({
+                "message": "Revert delta 2",
+                "revert": "2"
+            }));
+            assert_eq!(json_body["affected"], json!([ 1 ]));
+            assert!(resp.status().is_success());
+        }
+
+        { //Revert Modify again conflicts with the revert delta
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/delta/2/revert")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 409);
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 409,
+                "status": "Conflict",
+                "reason": "Revert Conflict",
+                "conflicts": [{
+                    "id": 1,
+                    "version": 2,
+                    "current_version": 3,
+                    "deltas": [ 3 ]
+                }]
+            }));
+        }
+
+        { //Revert the Revert
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/delta/3/revert")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "4");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["version"], json!(4));
+            assert_eq!(json_body["properties"], json!({ "number": "321" }));
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Second Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "456" },
+                    "geometry": { "type": "Point", "coordinates": [ 2, 2 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Revert Create of Second Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/delta/5/revert")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "6");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/2").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Revert Delete of Second Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/delta/6/revert")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "7");
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/2").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "id": 2,
+                "key": null,
+                "type": "Feature",
+                "version": 3,
+                "properties": { "number": "456" },
+                "geometry": { "type": "Point", "coordinates": [ 2.0, 2.0 ] }
+            }));
+            assert!(resp.status().is_success());
+        }
+
+        server.kill().unwrap();
+    }
+}
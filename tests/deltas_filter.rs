@@ -0,0 +1,219 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn deltas_filter() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Points
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Add Shops",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": {
+                            "shop": true
+                        },
+                        "geometry": {
+                            "type": "Point",
+                            "coordinates": [ 1,1 ]
+                        }
+                    }, {
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": {
+                            "shop": true
+                        },
+                        "geometry": {
+                            "type": "Point",
+                            "coordinates": [ 1.1,1.1 ]
+                        }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Create Distant Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Add a Building",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": {
+                            "building": true
+                        },
+                        "geometry": {
+                            "type": "Point",
+                            "coordinates": [ 50,50 ]
+                        }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Delete Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "type": "Feature",
+                    "version": 1,
+                    "action": "delete",
+                    "message": "Remove closed shop",
+                    "properties": null,
+                    "geometry": null
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), "true");
+        }
+
+        { //Filter by feature
+            let mut resp = reqwest::get("http://localhost:8000/api/deltas?feature=1").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+
+            assert_eq!(json_body.as_array().unwrap().len(), 2);
+            assert_eq!(json_body[0]["id"], json!(3));
+            assert_eq!(json_body[1]["id"], json!(1));
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Filter by message - case insensitive substring
+            let mut resp = reqwest::get("http://localhost:8000/api/deltas?message=BUILDING").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+
+            assert_eq!(json_body.as_array().unwrap().len(), 1);
+            assert_eq!(json_body[0]["id"], json!(2));
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Filter by bbox - includes deleted features
+            let mut resp = reqwest::get("http://localhost:8000/api/deltas?bbox=0,0,2,2").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+
+            assert_eq!(json_body.as_array().unwrap().len(), 2);
+            assert_eq!(json_body[0]["id"], json!(3));
+            assert_eq!(json_body[1]["id"], json!(1));
+
+            assert!(resp.status().is_success());
+        }
+
+        { //Filter by uid
+            let mut resp = reqwest::get("http://localhost:8000/api/deltas?uid=1").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body.as_array().unwrap().len(), 3);
+            assert!(resp.status().is_success());
+
+            let mut resp = reqwest::get("http://localhost:8000/api/deltas?uid=2").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body.as_array().unwrap().len(), 0);
+            assert!(resp.status().is_success());
+        }
+
+        { //Combined filters with pagination
+            let mut resp = reqwest::get("http://localhost:8000/api/deltas?uid=1&bbox=0,0,2,2&limit=1&offset=1").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+
+            assert_eq!(json_body.as_array().unwrap().len(), 1);
+            assert_eq!(json_body[0]["id"], json!(1));
+
+            assert!(resp.status().is_success());
+        }
+
+        { //bbox & bounds are exclusive
+            let mut resp = reqwest::get("http://localhost:8000/api/deltas?bbox=0,0,2,2&bounds=world").unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "bbox and bounds params cannot be used together",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //unknown bounds
+            let mut resp = reqwest::get("http://localhost:8000/api/deltas?bounds=unknown").unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 404,
+                "reason": "Bounds Not Found",
+                "status": "Not Found"
+            }));
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,189 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn feature_diff() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "name": "main st", "lanes": 2, "oneway": true },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Modify Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "type": "Feature",
+                    "version": 1,
+                    "action": "modify",
+                    "message": "Modify a Point",
+                    "properties": { "name": "Main St", "lanes": 2, "surface": "paved" },
+                    "geometry": { "type": "Point", "coordinates": [ 3, 4 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Modify Point into a Line
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "type": "Feature",
+                    "version": 2,
+                    "action": "modify",
+                    "message": "Modify a Point",
+                    "properties": { "name": "Main St", "lanes": 2, "surface": "paved" },
+                    "geometry": { "type": "LineString", "coordinates": [ [ 3, 4 ], [ 3, 5 ] ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Diff defaults to the latest version
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/diff").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["from"], json!({ "version": 2, "action": "modify" }));
+            assert_eq!(json_body["to"], json!({ "version": 3, "action": "modify" }));
+            assert_eq!(json_body["properties"], json!({ "added": {}, "removed": {}, "changed": {} }));
+            assert_eq!(json_body["geometry"]["changed"], json!(true));
+            assert_eq!(json_body["geometry"]["type"], json!({ "from": "Point", "to": "LineString", "changed": true }));
+            assert_eq!(json_body["geometry"]["vertices"], json!({ "from": 1, "to": 2, "delta": 1 }));
+            assert_eq!(json_body["geometry"]["hausdorff"], json!(1));
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/diff?from=1&to=2").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "id": 1,
+                "from": { "version": 1, "action": "create" },
+                "to": { "version": 2, "action": "modify" },
+                "properties": {
+                    "added": { "surface": "paved" },
+                    "removed": { "oneway": true },
+                    "changed": {
+                        "name": { "from": "main st", "to": "Main St" }
+                    }
+                },
+                "geometry": {
+                    "changed": true,
+                    "type": { "from": "Point", "to": "Point", "changed": false },
+                    "vertices": { "from": 1, "to": 1, "delta": 0 },
+                    "hausdorff": 5,
+                    "bbox": {
+                        "from": [ 0, 0, 0, 0 ],
+                        "to": [ 3, 4, 3, 4 ]
+                    }
+                }
+            }));
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/diff?from=2&to=1").unwrap();
+            assert!(resp.status().is_client_error());
+            assert_eq!(resp.text().unwrap(), "{\"code\":400,\"reason\":\"from version must be less than to version\",\"status\":\"Bad Request\"}");
+        }
+
+        { //The first version is diffed against the empty state
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1/diff?to=1").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["from"], json!({ "version": 0, "action": null }));
+            assert_eq!(json_body["to"], json!({ "version": 1, "action": "create" }));
+            assert_eq!(json_body["properties"], json!({
+                "added": { "name": "main st", "lanes": 2, "oneway": true },
+                "removed": {},
+                "changed": {}
+            }));
+            assert_eq!(json_body["geometry"]["changed"], json!(true));
+            assert_eq!(json_body["geometry"]["type"], json!({ "from": null, "to": "Point", "changed": true }));
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/1/diff?from=1&to=10").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        {
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/2/diff").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,171 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn feature_version_at() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Modify Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "type": "Feature",
+                    "version": 1,
+                    "action": "modify",
+                    "message": "Modify a Point",
+                    "properties": { "number": "321" },
+                    "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Delete Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "type": "Feature",
+                    "version": 2,
+                    "action": "delete",
+                    "message": "Delete a Point",
+                    "properties": null,
+                    "geometry": null
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Current state of deleted point is 404
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Point as of delta 1
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1?at=1").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "id": 1,
+                "key": null,
+                "type": "Feature",
+                "version": 1,
+                "properties": { "number": "123" },
+                "geometry": { "type": "Point", "coordinates": [ 0.0, 0.0 ] }
+            }));
+            assert!(resp.status().is_success());
+        }
+
+        { //Point as of delta 2
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1?at=2").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "id": 1,
+                "key": null,
+                "type": "Feature",
+                "version": 2,
+                "properties": { "number": "321" },
+                "geometry": { "type": "Point", "coordinates": [ 1.0, 1.0 ] }
+            }));
+            assert!(resp.status().is_success());
+        }
+
+        { //Point as of delta 3 has been deleted
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/1?at=3").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Point did not exist before it was created
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/1?at=2000-01-01T00:00:00Z").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Point as of now is still deleted
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/1?at=2100-01-01T00:00:00").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Invalid at param
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1?at=yesterday").unwrap();
+            assert!(resp.status().is_client_error());
+            assert_eq!(resp.text().unwrap(), "{\"code\":400,\"reason\":\"at must be a delta id or ISO 8601 timestamp\",\"status\":\"Bad Request\"}");
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,173 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn features_atomic() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "number": "123" },
+                    "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Non-Atomic Upload commits successful features
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features?atomic=false")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Partial Upload",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": { "number": "456" },
+                        "geometry": { "type": "Point", "coordinates": [ 1, 1 ] }
+                    },{
+                        "id": 1,
+                        "version": 5,
+                        "type": "Feature",
+                        "action": "modify",
+                        "properties": { "number": "321" },
+                        "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                    },{
+                        "id": 1,
+                        "version": 1,
+                        "type": "Feature",
+                        "action": "modify",
+                        "properties": { "number": "321" },
+                        "geometry": { "type": "Point", "coordinates": [ 0, 0 ] }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body[0], json!({ "index": 0, "outcome": "success", "id": 2, "version": 1 }));
+
+            assert_eq!(json_body[1]["index"], json!(1));
+            assert_eq!(json_body[1]["outcome"], json!("failure"));
+            assert_eq!(json_body[1]["error"]["message"], json!("Modify Version Mismatch"));
+
+            assert_eq!(json_body[2], json!({ "index": 2, "outcome": "success", "id": 1, "version": 2 }));
+        }
+
+        {
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["version"], json!(2));
+            assert_eq!(json_body["properties"], json!({ "number": "321" }));
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/2").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["version"], json!(1));
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let mut resp = reqwest::get("http://localhost:8000/api/delta/2").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["affected"], json!([ 2, 1 ]));
+            assert!(resp.status().is_success());
+        }
+
+        { //Non-Atomic Upload with no successful features does not create a delta
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features?atomic=false")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Failed Upload",
+                    "features": [{
+                        "id": 1,
+                        "version": 1,
+                        "type": "Feature",
+                        "action": "delete",
+                        "properties": null,
+                        "geometry": null
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body[0]["outcome"], json!("failure"));
+            assert_eq!(json_body[0]["error"]["message"], json!("Delete Version Mismatch"));
+        }
+
+        {
+            let mut resp = reqwest::get("http://localhost:8000/api/delta/3").unwrap();
+            assert_eq!(resp.text().unwrap(), "false");
+        }
+
+        server.kill().unwrap();
+    }
+}
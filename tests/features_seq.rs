@@ -0,0 +1,216 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+    use serde_json;
+
+    #[test]
+    fn features_seq() {
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Line-Delimited Upload requires a message
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body("{\"type\":\"Feature\",\"action\":\"create\",\"properties\":{\"number\":\"1\"},\"geometry\":{\"type\":\"Point\",\"coordinates\":[0,0]}}\n")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/geo+json-seq")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_client_error());
+            assert_eq!(resp.text().unwrap(), "{\"code\":400,\"reason\":\"message param required for line-delimited uploads\",\"status\":\"Bad Request\"}");
+        }
+
+        { //Line-Delimited Upload
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features?message=Line%20Delimited")
+                .body(concat!(
+                    "{\"type\":\"Feature\",\"action\":\"create\",\"properties\":{\"number\":\"1\"},\"geometry\":{\"type\":\"Point\",\"coordinates\":[0,0]}}\n",
+                    "\n",
+                    "{\"type\":\"Feature\",\"action\":\"create\",\"properties\":{\"number\":\"2\"},\"geometry\":{\"type\":\"Point\",\"coordinates\":[1,1]}}\n",
+                    "{\"type\":\"Feature\",\"action\":\"create\",\"properties\":{\"number\":\"3\"},\"geometry\":{\"type\":\"Point\",\"coordinates\":[2,2]}}"
+                ))
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/geo+json-seq")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "delta": 1,
+                "total": 3,
+                "success": 3,
+                "failures": []
+            }));
+        }
+
+        {
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/3").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["properties"], json!({ "number": "3" }));
+            assert!(resp.status().is_success());
+        }
+
+        {
+            let mut resp = reqwest::get("http://localhost:8000/api/delta/1").unwrap();
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["props"], json!({ "message": "Line Delimited" }));
+            assert_eq!(json_body["affected"], json!([ 1, 2, 3 ]));
+            assert!(resp.status().is_success());
+        }
+
+        { //Atomic Line-Delimited Upload stops at first failure
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features?message=Line%20Delimited")
+                .body(concat!(
+                    "{\"type\":\"Feature\",\"action\":\"create\",\"properties\":{\"number\":\"4\"},\"geometry\":{\"type\":\"Point\",\"coordinates\":[3,3]}}\n",
+                    "{\"id\":1,\"version\":5,\"type\":\"Feature\",\"action\":\"delete\",\"properties\":null,\"geometry\":null}\n"
+                ))
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/geo+json-seq")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_client_error());
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["index"], json!(1));
+            assert_eq!(json_body["message"], json!("Delete Version Mismatch"));
+        }
+
+        {
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/4").unwrap();
+            assert!(resp.status().is_client_error());
+        }
+
+        { //Non-Atomic Line-Delimited Upload reports failures
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features?message=Line%20Delimited&atomic=false")
+                .body(concat!(
+                    "{\"id\":1,\"version\":5,\"type\":\"Feature\",\"action\":\"delete\",\"properties\":null,\"geometry\":null}\n",
+                    "not a feature\n",
+                    "{\"id\":1,\"version\":1,\"type\":\"Feature\",\"action\":\"delete\",\"properties\":null,\"geometry\":null}\n"
+                ))
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/geo+json-seq")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["total"], json!(3));
+            assert_eq!(json_body["success"], json!(1));
+            assert_eq!(json_body["failures"][0]["index"], json!(0));
+            assert_eq!(json_body["failures"][0]["error"]["message"], json!("Delete Version Mismatch"));
+            assert_eq!(json_body["failures"][1], json!({
+                "index": 1,
+                "outcome": "failure",
+                "error": {
+                    "code": 400,
+                    "status": "Bad Request",
+                    "reason": "Each line must be a valid GeoJSON Feature"
+                }
+            }));
+        }
+
+        {
+            let resp = reqwest::get("http://localhost:8000/api/data/feature/1").unwrap();
+            assert!(resp.status().is_client_error());
+        }
+
+        { //Progress of a finished Line-Delimited Upload
+            let mut resp = reqwest::Client::new().get("http://localhost:8000/api/data/features/uploads/3")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["delta"], json!(3));
+            assert_eq!(json_body["status"], json!("complete"));
+            assert_eq!(json_body["total"], json!(3));
+            assert_eq!(json_body["success"], json!(1));
+            assert_eq!(json_body["failures"].as_array().unwrap().len(), 2);
+            assert_eq!(json_body["error"], json!(null));
+        }
+
+        { //Progress of a failed Atomic Line-Delimited Upload
+            let mut resp = reqwest::Client::new().get("http://localhost:8000/api/data/features/uploads/2")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["status"], json!("failed"));
+            assert_eq!(json_body["total"], json!(2));
+            assert_eq!(json_body["success"], json!(1));
+            assert_eq!(json_body["error"]["index"], json!(1));
+        }
+
+        { //List Line-Delimited Uploads
+            let mut resp = reqwest::Client::new().get("http://localhost:8000/api/data/features/uploads")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            let deltas: Vec<i64> = json_body.as_array().unwrap().iter().map(|upload| upload["delta"].as_i64().unwrap()).collect();
+            assert_eq!(deltas, vec![1, 2, 3]);
+        }
+
+        { //Unknown Line-Delimited Upload
+            let resp = reqwest::Client::new().get("http://localhost:8000/api/data/features/uploads/100")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,140 @@
+extern crate reqwest;
+extern crate postgres;
+extern crate png;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+    #[test]
+    fn styles_raster() {
+        { // Reset Database:
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("DROP DATABASE IF EXISTS hecate;", &[]).unwrap();
+            conn.execute("CREATE DATABASE hecate;", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": {
+                        "string": "123",
+                        "number": 123,
+                        "array": [ 1, 2, 3 ]
+                    },
+                    "geometry": { "type": "Point", "coordinates": [ -97.734375,56.559482483762245 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Style
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/style")
+                .body(r##"{
+                    "name": "Raster Style",
+                    "style": {
+                        "version": 8,
+                        "layers": [{
+                            "id": "points",
+                            "type": "circle",
+                            "source": "hecate",
+                            "source-layer": "data",
+                            "paint": {
+                                "circle-color": "#ff0000",
+                                "circle-radius": 4
+                            }
+                        }]
+                    }
+                }"##)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "1");
+            assert!(resp.status().is_success());
+        }
+
+        { //Private styles cannot be rendered without auth
+            let resp = reqwest::get("http://localhost:8000/api/style/1/tiles/1/0/0.png").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Render a raster tile
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/style/1/tiles/1/0/0.png")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get(reqwest::header::CONTENT_TYPE).unwrap(), "image/png");
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+
+            let decoder = png::Decoder::new(body.as_slice());
+            let (info, mut reader) = decoder.read_info().unwrap();
+            assert_eq!((info.width, info.height), (256, 256));
+
+            let mut pixels = vec![0; info.buffer_size()];
+            reader.next_frame(&mut pixels).unwrap();
+
+            // The point is painted as a red circle on an otherwise transparent tile
+            assert!(pixels.chunks(4).any(|pixel| pixel == [255, 0, 0, 255]));
+        }
+
+        { //Tile coordinates are validated
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/style/1/tiles/18/0/0.png")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        server.kill().unwrap();
+    }
+}
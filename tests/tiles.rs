@@ -75,7 +75,7 @@ mod test {
             let mut body: Vec<u8> = Vec::new();
             resp.read_to_end(&mut body).unwrap();
 
-            assert_eq!(body.len(), 36);
+            assert_eq!(body.len(), 45);
             assert!(resp.status().is_success());
         }
 
@@ -91,14 +91,14 @@ mod test {
                     ref
             ", &[]).unwrap();
 
-            assert_eq!(res.len(), 5);
+            assert_eq!(res.len(), 1);
 
             let tile_ref: String = res.get(0).get(0);
             assert_eq!(tile_ref, String::from("1/0/0"));
 
             let tile: Vec<u8> = res.get(0).get(1);
 
-            assert_eq!(tile.len(), 36);
+            assert_eq!(tile.len(), 45);
         }
 
         { //Request a tile via API
@@ -108,7 +108,7 @@ mod test {
             let mut body: Vec<u8> = Vec::new();
             resp.read_to_end(&mut body).unwrap();
 
-            assert_eq!(body.len(), 36);
+            assert_eq!(body.len(), 45);
             assert!(resp.status().is_success());
         }
 
@@ -138,7 +138,7 @@ mod test {
             let mut body: Vec<u8> = Vec::new();
             resp.read_to_end(&mut body).unwrap();
 
-            assert_eq!(body.len(), 36);
+            assert_eq!(body.len(), 45);
             assert!(resp.status().is_success());
         }
 
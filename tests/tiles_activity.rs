@@ -0,0 +1,169 @@
+extern crate reqwest;
+extern crate postgres;
+extern crate hecate;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+    #[test]
+    fn tiles_activity() {
+        { // Reset Database:
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("DROP DATABASE IF EXISTS hecate;", &[]).unwrap();
+            conn.execute("CREATE DATABASE hecate;", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": {
+                        "string": "123",
+                        "number": 123,
+                        "array": [ 1, 2, 3 ]
+                    },
+                    "geometry": { "type": "Point", "coordinates": [ -97.734375,56.559482483762245 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Modify Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "version": 1,
+                    "type": "Feature",
+                    "action": "modify",
+                    "message": "Modifying a Point",
+                    "properties": {
+                        "string": "1234"
+                    },
+                    "geometry": { "type": "Point", "coordinates": [ -97.734375,56.559482483762245 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Request an activity tile
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/activity/1/0/0").unwrap();
+            assert!(resp.status().is_success());
+            assert_eq!(resp.headers().get(reqwest::header::CONTENT_TYPE).unwrap(), "application/x-protobuf");
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+
+            let layers = hecate::mvt::decode::decode(&body).unwrap();
+            assert_eq!(layers.len(), 1);
+            assert_eq!(layers[0].name, "activity");
+            assert_eq!(layers[0].features.len(), 1);
+            assert_eq!(layers[0].features[0].kind, hecate::mvt::decode::Kind::Polygon);
+            assert_eq!(layers[0].features[0].properties["count"], 2);
+            assert_eq!(layers[0].features[0].properties["features"], 1);
+        }
+
+        { //Activity can be filtered by user
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/activity/1/0/0?uid=1").unwrap();
+            assert!(resp.status().is_success());
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+            assert_eq!(hecate::mvt::decode::decode(&body).unwrap()[0].features[0].properties["count"], 2);
+
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/activity/1/0/0?uid=2").unwrap();
+            assert!(resp.status().is_success());
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+            assert!(body.is_empty());
+        }
+
+        { //Activity can be filtered by time
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/activity/1/0/0?start=3000-01-01T00:00:00").unwrap();
+            assert!(resp.status().is_success());
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+            assert!(body.is_empty());
+
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/activity/1/0/0?start=2000-01-01T00:00:00&end=3000-01-01T00:00:00").unwrap();
+            assert!(resp.status().is_success());
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+            assert_eq!(hecate::mvt::decode::decode(&body).unwrap()[0].features[0].properties["count"], 2);
+
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/activity/1/0/0?start=2000-01-01T00:00:00Z&end=3000-01-01T01:00:00%2B01:00").unwrap();
+            assert!(resp.status().is_success());
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+            assert_eq!(hecate::mvt::decode::decode(&body).unwrap()[0].features[0].properties["count"], 2);
+
+            let resp = reqwest::get("http://localhost:8000/api/tiles/activity/1/0/0?start=yesterday").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        { //Activity tiles outside the grid are not found
+            let resp = reqwest::get("http://localhost:8000/api/tiles/activity/1/2/0").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Edits outside of a tile are not counted
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/activity/1/1/1").unwrap();
+            assert!(resp.status().is_success());
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+            assert!(body.is_empty());
+        }
+
+        server.kill().unwrap();
+    }
+}
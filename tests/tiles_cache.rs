@@ -0,0 +1,159 @@
+extern crate reqwest;
+extern crate postgres;
+extern crate hecate;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+
+    #[test]
+    fn tiles_cache() {
+        { // Reset Database:
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("DROP DATABASE IF EXISTS hecate;", &[]).unwrap();
+            conn.execute("CREATE DATABASE hecate;", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": {
+                        "shop": true
+                    },
+                    "geometry": { "type": "Point", "coordinates": [ -97.734375,56.559482483762245 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        let etag: String;
+        let last_modified: String;
+
+        { //Request a tile, caching it
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/tiles/1/0/0").send().unwrap();
+
+            assert!(resp.status().is_success());
+
+            etag = String::from(resp.headers().get(reqwest::header::ETAG).unwrap().to_str().unwrap());
+            last_modified = String::from(resp.headers().get(reqwest::header::LAST_MODIFIED).unwrap().to_str().unwrap());
+
+            assert!(last_modified.ends_with("GMT"));
+        }
+
+        { //Revalidate with ETag
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/tiles/1/0/0")
+                .header(reqwest::header::IF_NONE_MATCH, etag.clone())
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 304);
+            assert_eq!(resp.headers().get(reqwest::header::ETAG).unwrap().to_str().unwrap(), etag);
+        }
+
+        { //Revalidate with Last-Modified
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/tiles/1/0/0")
+                .header(reqwest::header::IF_MODIFIED_SINCE, last_modified.clone())
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 304);
+        }
+
+        { //Modify Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "version": 1,
+                    "type": "Feature",
+                    "action": "modify",
+                    "message": "Modifying a Point",
+                    "properties": {
+                        "shop": false
+                    },
+                    "geometry": { "type": "Point", "coordinates": [ -97.734375,56.559482483762245 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        thread::sleep(Duration::from_secs(1));
+
+        { //Only the previously cached tile was regenerated
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let res = conn.query("
+                SELECT
+                    ref
+                FROM
+                    tiles
+            ", &[]).unwrap();
+
+            assert_eq!(res.len(), 1);
+
+            let tile_ref: String = res.get(0).get(0);
+            assert_eq!(tile_ref, String::from("1/0/0"));
+        }
+
+        { //Stale ETag is no longer valid
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/tiles/1/0/0")
+                .header(reqwest::header::IF_NONE_MATCH, etag.clone())
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 200);
+            assert_ne!(resp.headers().get(reqwest::header::ETAG).unwrap().to_str().unwrap(), etag);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,125 @@
+extern crate reqwest;
+extern crate postgres;
+extern crate hecate;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use std::env;
+    use reqwest;
+
+    #[test]
+    fn tiles_config() {
+        { // Reset Database:
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("DROP DATABASE IF EXISTS hecate;", &[]).unwrap();
+            conn.execute("CREATE DATABASE hecate;", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--tiles", env::current_dir().unwrap().join("tests/fixtures/tiles.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Features
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/features")
+                .body(r#"{
+                    "type": "FeatureCollection",
+                    "message": "Shops & Roads",
+                    "features": [{
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": {
+                            "shop": "bakery",
+                            "name": "Pain Quotidien",
+                            "secret": "croissant"
+                        },
+                        "geometry": { "type": "Point", "coordinates": [ 0.001, 0.001 ] }
+                    }, {
+                        "type": "Feature",
+                        "action": "create",
+                        "properties": {
+                            "highway": "residential",
+                            "name": "Rue Cler"
+                        },
+                        "geometry": { "type": "LineString", "coordinates": [ [ 0.001, 0.001 ], [ 0.002, 0.002 ] ] }
+                    }]
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Low zoom tiles only contain the shops layer with whitelisted properties
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/tiles/1/1/0").send().unwrap();
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+            assert!(resp.status().is_success());
+
+            let body = String::from_utf8_lossy(&body);
+
+            assert!(body.contains("shops"));
+            assert!(body.contains("count"));
+            assert!(body.contains("bakery"));
+            assert!(body.contains("Pain Quotidien"));
+            assert!(!body.contains("croissant"));
+            assert!(!body.contains("roads"));
+            assert!(!body.contains("residential"));
+        }
+
+        { //High zoom tiles contain both layers
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/tiles/14/8192/8191").send().unwrap();
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+            assert!(resp.status().is_success());
+
+            let body = String::from_utf8_lossy(&body);
+
+            assert!(body.contains("shops"));
+            assert!(body.contains("roads"));
+            assert!(body.contains("residential"));
+            assert!(!body.contains("count"));
+            assert!(!body.contains("Rue Cler"));
+        }
+
+        server.kill().unwrap();
+    }
+}
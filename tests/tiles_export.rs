@@ -0,0 +1,172 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+    #[test]
+    fn tiles_export() {
+        { // Reset Database:
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("DROP DATABASE IF EXISTS hecate;", &[]).unwrap();
+            conn.execute("CREATE DATABASE hecate;", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": {
+                        "string": "123"
+                    },
+                    "geometry": { "type": "Point", "coordinates": [ -77.05, 38.85 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //maxzoom is required
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/tiles/export?bbox=-77.1,38.8,-77.0,38.9")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "maxzoom param required",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //Unknown format
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/tiles/export?bbox=-77.1,38.8,-77.0,38.9&maxzoom=2&format=zip")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "format must be one of mbtiles or pmtiles",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //Authentication is checked before the format
+            let resp = reqwest::get("http://localhost:8000/api/tiles/export?bbox=-77.1,38.8,-77.0,38.9&maxzoom=2&format=zip").unwrap();
+
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        { //Too many tiles
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/tiles/export?bbox=-180,-90,180,90&maxzoom=17")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Area covers up to 22906492245 tiles, more than the limit of 100000",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //Invalid bbox
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/tiles/export?bbox=-77.1,north&maxzoom=2")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Invalid BBOX",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //Export MBTiles
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/tiles/export?bbox=-77.1,38.8,-77.0,38.9&maxzoom=2")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+
+            assert!(body.starts_with(b"SQLite format 3"));
+        }
+
+        { //Export PMTiles
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/tiles/export?bbox=-77.1,38.8,-77.0,38.9&maxzoom=2&format=pmtiles")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+
+            assert!(body.starts_with(b"PMTiles"));
+            assert_eq!(body[7], 3);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,152 @@
+extern crate reqwest;
+extern crate postgres;
+extern crate hecate;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use std::env;
+    use reqwest;
+
+    #[test]
+    fn tiles_grid() {
+        { // Reset Database:
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("DROP DATABASE IF EXISTS hecate;", &[]).unwrap();
+            conn.execute("CREATE DATABASE hecate;", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[
+            "run",
+            "--",
+            "--tiles", env::current_dir().unwrap().join("tests/fixtures/tiles.grids.json").to_str().unwrap()
+        ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": {},
+                    "geometry": { "type": "Point", "coordinates": [ -90.0, 80.0 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //WGS84 tile containing the point
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/tiles/WGS84/0/0/0").send().unwrap();
+
+            assert!(resp.status().is_success());
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+
+            assert!(body.len() > 0);
+        }
+
+        { //WGS84 tile without the point
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/tiles/WGS84/0/1/0").send().unwrap();
+
+            assert!(resp.status().is_success());
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+
+            assert_eq!(body.len(), 0);
+        }
+
+        { //WGS84 tile outside of the grid
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/tiles/WGS84/0/0/1").send().unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Custom grid tile containing the point
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/tiles/polar/1/0/1").send().unwrap();
+
+            assert!(resp.status().is_success());
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+
+            assert!(body.len() > 0);
+        }
+
+        { //Custom grid tile without the point
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/tiles/polar/1/1/0").send().unwrap();
+
+            assert!(resp.status().is_success());
+
+            let mut body: Vec<u8> = Vec::new();
+            resp.read_to_end(&mut body).unwrap();
+
+            assert_eq!(body.len(), 0);
+        }
+
+        { //Custom grid zoom outside of the grid
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/tiles/polar/3/0/0").send().unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Unknown grid
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/tiles/nope/0/0/0").send().unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Only web mercator tiles are cached
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let res = conn.query("
+                SELECT ref FROM tiles
+            ", &[]).unwrap();
+
+            assert_eq!(res.len(), 0);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,219 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+    #[test]
+    fn tiles_seed() {
+        { // Reset Database:
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("DROP DATABASE IF EXISTS hecate;", &[]).unwrap();
+            conn.execute("CREATE DATABASE hecate;", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Seeding requires an admin by default
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/tiles/seed")
+                .body(r#"{
+                    "bbox": [ -77.1, 38.8, -77.0, 38.9 ],
+                    "maxzoom": 3
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_client_error());
+        }
+
+        {
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            conn.execute("
+                UPDATE users SET access = 'admin' WHERE id = 1;
+            ", &[]).unwrap();
+        }
+
+        { //maxzoom is required
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/tiles/seed")
+                .body(r#"{
+                    "bbox": [ -77.1, 38.8, -77.0, 38.9 ]
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "maxzoom param required",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //bbox & bounds are exclusive
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/tiles/seed")
+                .body(r#"{
+                    "bbox": [ -77.1, 38.8, -77.0, 38.9 ],
+                    "bounds": "dc",
+                    "maxzoom": 3
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Either bbox or bounds param must be specified",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //jobs over max_tiles are rejected
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/tiles/seed")
+                .body(r#"{
+                    "bbox": [ -180, -85, 180, 85 ],
+                    "maxzoom": 17
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 400,
+                "reason": "Area covers up to 22906492245 tiles, more than the limit of 100000",
+                "status": "Bad Request"
+            }));
+        }
+
+        { //Queue a seed job
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/tiles/seed")
+                .body(r#"{
+                    "bbox": [ -77.1, 38.8, -77.0, 38.9 ],
+                    "minzoom": 1,
+                    "maxzoom": 4
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["id"], json!(1));
+            assert_eq!(json_body["minzoom"], json!(1));
+            assert_eq!(json_body["maxzoom"], json!(4));
+            assert_eq!(json_body["bbox"], json!([ -77.1, 38.8, -77.0, 38.9 ]));
+        }
+
+        thread::sleep(Duration::from_secs(1));
+
+        { //Seed job progress
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/tiles/seed/1")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "id": 1,
+                "status": "complete",
+                "bbox": [ -77.1, 38.8, -77.0, 38.9 ],
+                "bounds": null,
+                "minzoom": 1,
+                "maxzoom": 4,
+                "total": 4,
+                "complete": 4,
+                "failed": 0,
+                "error": null
+            }));
+        }
+
+        { //Seeded tiles are cached
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+            let res = conn.query("
+                SELECT
+                    ref
+                FROM
+                    tiles
+                ORDER BY
+                    ref
+            ", &[]).unwrap();
+
+            let refs: Vec<String> = res.iter().map(|row| row.get(0)).collect();
+            assert_eq!(refs, vec![String::from("1/0/0"), String::from("2/1/1"), String::from("3/2/3"), String::from("4/4/6")]);
+        }
+
+        { //Finished jobs cannot be cancelled
+            let client = reqwest::Client::new();
+            let resp = client.delete("http://localhost:8000/api/tiles/seed/1")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        { //Unknown job
+            let client = reqwest::Client::new();
+            let resp = client.get("http://localhost:8000/api/tiles/seed/2")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,102 @@
+extern crate reqwest;
+extern crate postgres;
+#[macro_use] extern crate serde_json;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+    #[test]
+    fn tiles_tilejson() {
+        { // Reset Database:
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("DROP DATABASE IF EXISTS hecate;", &[]).unwrap();
+            conn.execute("CREATE DATABASE hecate;", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //TileJSON describes the default config
+            let mut resp = reqwest::get("http://localhost:8000/api/tiles/tilejson.json").unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "tilejson": "3.0.0",
+                "name": "hecate",
+                "format": "pbf",
+                "tiles": [ "http://localhost:8000/api/tiles/{z}/{x}/{y}" ],
+                "bounds": [ -180.0, -85.0511287798066, 180.0, 85.0511287798066 ],
+                "center": [ 0.0, 0.0, 0 ],
+                "minzoom": 0,
+                "maxzoom": 17,
+                "vector_layers": [{
+                    "id": "data",
+                    "fields": { "count": "Number" },
+                    "minzoom": 0,
+                    "maxzoom": 17
+                }]
+            }));
+        }
+
+        let token: String;
+
+        { // Create Token
+            let client = reqwest::Client::new();
+
+            let mut resp = client.post("http://localhost:8000/api/user/token")
+                .body(r#"{
+                    "name": "Map Token",
+                    "hours": 5
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            token = json_body["token"].as_str().unwrap().to_string();
+        }
+
+        { //Tile url retains the token prefix
+            let mut resp = reqwest::get(format!("http://localhost:8000/token/{}/api/tiles/tilejson.json", token).as_str()).unwrap();
+            assert!(resp.status().is_success());
+
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body["tiles"], json!([ format!("http://localhost:8000/token/{}/api/tiles/{{z}}/{{x}}/{{y}}", token) ]));
+        }
+
+        server.kill().unwrap();
+    }
+}
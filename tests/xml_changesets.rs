@@ -0,0 +1,148 @@
+extern crate reqwest;
+extern crate postgres;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+    #[test]
+    fn xml_changesets() {
+        { // Reset Database:
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //User Details
+            let client = reqwest::Client::new();
+            let mut resp = client.get("http://localhost:8000/api/0.6/user/details")
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.text().unwrap(), r#"<osm version="0.6" generator="Hecate Server"><user id="1" display_name="ingalls"><description/><messages><received count="0" unread="0"/><sent count="0"/></messages></user></osm>"#);
+            assert!(resp.status().is_success());
+        }
+
+        { //User Details - unauthenticated
+            let resp = reqwest::get("http://localhost:8000/api/0.6/user/details").unwrap();
+            assert_eq!(resp.status().as_u16(), 401);
+        }
+
+        { //XML Changeset Create
+            let client = reqwest::Client::new();
+            let mut resp = client.put("http://localhost:8000/api/0.6/changeset/create")
+                .body(r#"<osm><changeset><tag k="created_by" v="Hecate Server"/><tag k="comment" v="Add a shop"/></changeset></osm>"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.text().unwrap(), "1");
+            assert!(resp.status().is_success());
+        }
+
+        { //Open Changeset
+            let mut resp = reqwest::get("http://localhost:8000/api/0.6/changeset/1").unwrap();
+            assert!(resp.status().is_success());
+
+            let body = resp.text().unwrap();
+            assert!(body.contains(r#"<changeset id="1" user="ingalls" uid="1" created_at=""#));
+            assert!(body.contains(r#"open="true" changes_count="0">"#));
+            assert!(body.contains(r#"<tag k="comment" v="Add a shop"/>"#));
+            assert!(body.contains(r#"<tag k="created_by" v="Hecate Server"/>"#));
+        }
+
+        { //XML Node Create
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/0.6/changeset/1/upload")
+                .body(r#"
+                    <osmChange version="0.6" generator="Hecate Server">
+                        <create>
+                            <node id='-1' version='1' changeset='1' lat='-0.5' lon='3.5'>
+                                <tag k='amenity' v='shop' />
+                            </node>
+                        </create>
+                    </osmChange>
+                "#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.text().unwrap(), r#"<diffResult generator="Hecate Server" version="0.6"><node old_id="-1" new_id="1" new_version="1"/></diffResult>"#);
+            assert!(resp.status().is_success());
+        }
+
+        { //Closed Changeset
+            let mut resp = reqwest::get("http://localhost:8000/api/0.6/changeset/1").unwrap();
+            assert!(resp.status().is_success());
+
+            let body = resp.text().unwrap();
+            assert!(body.contains(r#"open="false" changes_count="1" min_lon="3.5" min_lat="-0.5" max_lon="3.5" max_lat="-0.5">"#));
+        }
+
+        { //Changeset Not Found
+            let resp = reqwest::get("http://localhost:8000/api/0.6/changeset/2").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Query Changesets
+            let mut resp = reqwest::get("http://localhost:8000/api/0.6/changesets?user=1&bbox=3,-1,4,0&time=2000-01-01T00:00:00Z").unwrap();
+            assert!(resp.status().is_success());
+            assert!(resp.text().unwrap().contains(r#"<changeset id="1" user="ingalls""#));
+
+            let mut resp = reqwest::get("http://localhost:8000/api/0.6/changesets?user=2").unwrap();
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), r#"<osm version="0.6" generator="Hecate Server"></osm>"#);
+
+            let mut resp = reqwest::get("http://localhost:8000/api/0.6/changesets?bbox=10,10,11,11").unwrap();
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), r#"<osm version="0.6" generator="Hecate Server"></osm>"#);
+
+            let mut resp = reqwest::get("http://localhost:8000/api/0.6/changesets?time=2000-01-01T00:00:00Z,2001-01-01T00:00:00Z").unwrap();
+            assert!(resp.status().is_success());
+            assert_eq!(resp.text().unwrap(), r#"<osm version="0.6" generator="Hecate Server"></osm>"#);
+
+            let mut resp = reqwest::get("http://localhost:8000/api/0.6/changesets?time=2000-01-01T01:00:00%2B01:00").unwrap();
+            assert!(resp.status().is_success());
+            assert!(resp.text().unwrap().contains(r#"<changeset id="1" user="ingalls""#));
+
+            let resp = reqwest::get("http://localhost:8000/api/0.6/changesets?time=yesterday").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -229,7 +229,7 @@ mod test {
         { // Get XML By BBOX
             let mut resp = reqwest::get("http://localhost:8000/api/0.6/map?bbox=-79.463264,43.670270,-79.456344,43.674693").unwrap();
             assert!(resp.status().is_success());
-            assert_eq!(resp.text().unwrap(), r#"<?xml version="1.0" encoding="UTF-8"?><osm version="0.6" generator="ROSM"><node id="1" version="1" lon="-79.46014970541" lat="43.6726345821896"><tag k="addr:housenumber" v="1234"/><tag k="addr:street" v="Main St"/></node><node id="7000000000000000001" version="1" lat="43.6724366984115" lon="-79.4584304094314"/><node id="7000000000000000002" version="1" lat="43.6724269982095" lon="-79.4582131505013"/><node id="7000000000000000003" version="1" lat="43.6731292887804" lon="-79.4608980417252"/><node id="7000000000000000004" version="1" lat="43.6718760241634" lon="-79.4603669643402"/><node id="7000000000000000005" version="1" lat="43.6720622694773" lon="-79.4596266746521"/><node id="7000000000000000006" version="1" lat="43.6721553919176" lon="-79.4590795040131"/><node id="7000000000000000007" version="1" lat="43.6720661495819" lon="-79.458532333374"/><node id="7000000000000000008" version="1" lat="43.6720040678789" lon="-79.4583123922348"/><node id="7000000000000000009" version="1" lat="43.6717906615348" lon="-79.4575130939484"/><node id="7000000000000000010" version="1" lat="43.6734687909438" lon="-79.4609811902046"/><node id="7000000000000000011" version="1" lat="43.6734687909438" lon="-79.4606646895409"/><node id="7000000000000000012" version="1" lat="43.6736744313202" lon="-79.4606646895409"/><node id="7000000000000000013" version="1" lat="43.6736744313202" lon="-79.4609811902046"/><node id="7000000000000000014" version="1" lat="43.6729954273994" lon="-79.4596239924431"/><node id="7000000000000000015" version="1" lat="43.6729954273994" lon="-79.4588783383369"/><node id="7000000000000000016" version="1" lat="43.6734920710218" lon="-79.4588783383369"/><node id="7000000000000000017" version="1" lat="43.6734920710218" lon="-79.4596239924431"/><node id="7000000000000000018" version="1" lat="43.6731137686356" lon="-79.4594416022301"/><node id="7000000000000000019" version="1" lat="43.6731137686356" lon="-79.4590553641319"/><node id="7000000000000000020" version="1" lat="43.6733601504605" lon="-79.4590553641319"/><node id="7000000000000000021" version="1" lat="43.6733601504605" lon="-79.4594416022301"/><node id="7000000000000000022" version="1" lat="43.6736259312949" lon="-79.4587898254395"/><node id="7000000000000000023" version="1" lat="43.6736259312949" lon="-79.4583016633987"/><node id="7000000000000000024" version="1" lat="43.6739402107628" lon="-79.4583016633987"/><node id="7000000000000000025" version="1" lat="43.6739402107628" lon="-79.4587898254395"/><node id="7000000000000000026" version="1" lat="43.6737151713112" lon="-79.4586879014969"/><node id="7000000000000000027" version="1" lat="43.6738664910354" lon="-79.4586879014969"/><node id="7000000000000000028" version="1" lat="43.6738664910354" lon="-79.4584357738495"/><node id="7000000000000000029" version="1" lat="43.6737151713112" lon="-79.4584357738495"/><node id="7000000000000000030" version="1" lat="43.673160329058" lon="-79.4585376977921"/><node id="7000000000000000031" version="1" lat="43.673160329058" lon="-79.4580334424973"/><node id="7000000000000000032" version="1" lat="43.6734746109642" lon="-79.4580334424973"/><node id="7000000000000000033" version="1" lat="43.6734746109642" lon="-79.4585376977921"/><node id="7000000000000000034" version="1" lat="43.6732844900077" lon="-79.4583821296692"/><node id="7000000000000000035" version="1" lat="43.6732844900077" lon="-79.4582158327103"/><node id="7000000000000000036" version="1" lat="43.6734008906648" lon="-79.4582158327103"/><node id="7000000000000000037" version="1" lat="43.6734008906648" lon="-79.4583821296692"/><way id="3" version="1"><nd ref="7000000000000000003"/><nd ref="7000000000000000004"/><tag k="highway" v="residential"/><tag k="name" v="Main St E"/></way><way id="8000000000000000001" version="1"><nd ref="7000000000000000005"/><nd ref="7000000000000000006"/><nd ref="7000000000000000007"/></way><way id="8000000000000000002" version="1"><nd ref="7000000000000000008"/><nd ref="7000000000000000009"/></way><way id="5" version="1"><nd ref="7000000000000000010"/><nd ref="7000000000000000011"/><nd ref="7000000000000000012"/><nd ref="7000000000000000013"/><nd ref="7000000000000000010"/><tag k="building" v="yes"/></way><way id="8000000000000000003" version="1"><nd ref="7000000000000000014"/><nd ref="7000000000000000015"/><nd ref="7000000000000000016"/><nd ref="7000000000000000017"/><nd ref="7000000000000000014"/></way><way id="8000000000000000004" version="1"><nd ref="7000000000000000018"/><nd ref="7000000000000000019"/><nd ref="7000000000000000020"/><nd ref="7000000000000000021"/><nd ref="7000000000000000018"/></way><way id="8000000000000000005" version="1"><nd ref="7000000000000000022"/><nd ref="7000000000000000023"/><nd ref="7000000000000000024"/><nd ref="7000000000000000025"/><nd ref="7000000000000000022"/></way><way id="8000000000000000006" version="1"><nd ref="7000000000000000026"/><nd ref="7000000000000000027"/><nd ref="7000000000000000028"/><nd ref="7000000000000000029"/><nd ref="7000000000000000026"/></way><way id="8000000000000000007" version="1"><nd ref="7000000000000000030"/><nd ref="7000000000000000031"/><nd ref="7000000000000000032"/><nd ref="7000000000000000033"/><nd ref="7000000000000000030"/></way><way id="8000000000000000008" version="1"><nd ref="7000000000000000034"/><nd ref="7000000000000000035"/><nd ref="7000000000000000036"/><nd ref="7000000000000000037"/><nd ref="7000000000000000034"/></way><relation id="2" version="1"><tag k="addr:housenumber" v="1234"/><tag k="addr:street" v="yet another street"/><tag k="type" v="multipoint"/><member ref="7000000000000000001" type="node" role="point"/><member ref="7000000000000000002" type="node" role="point"/></relation><relation id="4" version="1"><tag k="highway" v="service"/><tag k="name" v="Don&apos;t drive on me"/><tag k="type" v="multilinestring"/><member ref="8000000000000000001" role="line" type="way"/><member ref="8000000000000000002" role="line" type="way"/></relation><relation id="6" version="1"><tag k="building" v="yes"/><tag k="type" v="multipolygon"/><member ref="8000000000000000003" role="outer" type="way"/><member ref="8000000000000000004" role="inner" type="way"/></relation><relation id="7" version="1"><tag k="amenity" v="hospital"/><tag k="building" v="yes"/><tag k="type" v="multipolygon"/><member ref="8000000000000000005" role="outer" type="way"/><member ref="8000000000000000006" role="inner" type="way"/><member ref="8000000000000000007" role="outer" type="way"/><member ref="8000000000000000008" role="inner" type="way"/></relation></osm>"#);
+            assert_eq!(resp.text().unwrap(), r#"<?xml version="1.0" encoding="UTF-8"?><osm version="0.6" generator="ROSM"><node id="1" version="1" lon="-79.46014970541" lat="43.6726345821896"><tag k="addr:housenumber" v="1234"/><tag k="addr:street" v="Main St"/></node><node id="7000000000002000000" version="1" lat="43.6724366984115" lon="-79.4584304094314"/><node id="7000000000002000001" version="1" lat="43.6724269982095" lon="-79.4582131505013"/><node id="7000000000003000000" version="1" lat="43.6731292887804" lon="-79.4608980417252"/><node id="7000000000003000001" version="1" lat="43.6718760241634" lon="-79.4603669643402"/><node id="7000000000004000000" version="1" lat="43.6720622694773" lon="-79.4596266746521"/><node id="7000000000004000001" version="1" lat="43.6721553919176" lon="-79.4590795040131"/><node id="7000000000004000002" version="1" lat="43.6720661495819" lon="-79.458532333374"/><node id="7000000000004000003" version="1" lat="43.6720040678789" lon="-79.4583123922348"/><node id="7000000000004000004" version="1" lat="43.6717906615348" lon="-79.4575130939484"/><node id="7000000000005000000" version="1" lat="43.6734687909438" lon="-79.4609811902046"/><node id="7000000000005000001" version="1" lat="43.6734687909438" lon="-79.4606646895409"/><node id="7000000000005000002" version="1" lat="43.6736744313202" lon="-79.4606646895409"/><node id="7000000000005000003" version="1" lat="43.6736744313202" lon="-79.4609811902046"/><node id="7000000000006000000" version="1" lat="43.6729954273994" lon="-79.4596239924431"/><node id="7000000000006000001" version="1" lat="43.6729954273994" lon="-79.4588783383369"/><node id="7000000000006000002" version="1" lat="43.6734920710218" lon="-79.4588783383369"/><node id="7000000000006000003" version="1" lat="43.6734920710218" lon="-79.4596239924431"/><node id="7000000000006000005" version="1" lat="43.6731137686356" lon="-79.4594416022301"/><node id="7000000000006000006" version="1" lat="43.6731137686356" lon="-79.4590553641319"/><node id="7000000000006000007" version="1" lat="43.6733601504605" lon="-79.4590553641319"/><node id="7000000000006000008" version="1" lat="43.6733601504605" lon="-79.4594416022301"/><node id="7000000000007000000" version="1" lat="43.6736259312949" lon="-79.4587898254395"/><node id="7000000000007000001" version="1" lat="43.6736259312949" lon="-79.4583016633987"/><node id="7000000000007000002" version="1" lat="43.6739402107628" lon="-79.4583016633987"/><node id="7000000000007000003" version="1" lat="43.6739402107628" lon="-79.4587898254395"/><node id="7000000000007000005" version="1" lat="43.6737151713112" lon="-79.4586879014969"/><node id="7000000000007000006" version="1" lat="43.6738664910354" lon="-79.4586879014969"/><node id="7000000000007000007" version="1" lat="43.6738664910354" lon="-79.4584357738495"/><node id="7000000000007000008" version="1" lat="43.6737151713112" lon="-79.4584357738495"/><node id="7000000000007000010" version="1" lat="43.673160329058" lon="-79.4585376977921"/><node id="7000000000007000011" version="1" lat="43.673160329058" lon="-79.4580334424973"/><node id="7000000000007000012" version="1" lat="43.6734746109642" lon="-79.4580334424973"/><node id="7000000000007000013" version="1" lat="43.6734746109642" lon="-79.4585376977921"/><node id="7000000000007000015" version="1" lat="43.6732844900077" lon="-79.4583821296692"/><node id="7000000000007000016" version="1" lat="43.6732844900077" lon="-79.4582158327103"/><node id="7000000000007000017" version="1" lat="43.6734008906648" lon="-79.4582158327103"/><node id="7000000000007000018" version="1" lat="43.6734008906648" lon="-79.4583821296692"/><way id="3" version="1"><nd ref="7000000000003000000"/><nd ref="7000000000003000001"/><tag k="highway" v="residential"/><tag k="name" v="Main St E"/></way><way id="8000000000004000000" version="1"><nd ref="7000000000004000000"/><nd ref="7000000000004000001"/><nd ref="7000000000004000002"/></way><way id="8000000000004000001" version="1"><nd ref="7000000000004000003"/><nd ref="7000000000004000004"/></way><way id="5" version="1"><nd ref="7000000000005000000"/><nd ref="7000000000005000001"/><nd ref="7000000000005000002"/><nd ref="7000000000005000003"/><nd ref="7000000000005000000"/><tag k="building" v="yes"/></way><way id="8000000000006000000" version="1"><nd ref="7000000000006000000"/><nd ref="7000000000006000001"/><nd ref="7000000000006000002"/><nd ref="7000000000006000003"/><nd ref="7000000000006000000"/></way><way id="8000000000006000001" version="1"><nd ref="7000000000006000005"/><nd ref="7000000000006000006"/><nd ref="7000000000006000007"/><nd ref="7000000000006000008"/><nd ref="7000000000006000005"/></way><way id="8000000000007000000" version="1"><nd ref="7000000000007000000"/><nd ref="7000000000007000001"/><nd ref="7000000000007000002"/><nd ref="7000000000007000003"/><nd ref="7000000000007000000"/></way><way id="8000000000007000001" version="1"><nd ref="7000000000007000005"/><nd ref="7000000000007000006"/><nd ref="7000000000007000007"/><nd ref="7000000000007000008"/><nd ref="7000000000007000005"/></way><way id="8000000000007000002" version="1"><nd ref="7000000000007000010"/><nd ref="7000000000007000011"/><nd ref="7000000000007000012"/><nd ref="7000000000007000013"/><nd ref="7000000000007000010"/></way><way id="8000000000007000003" version="1"><nd ref="7000000000007000015"/><nd ref="7000000000007000016"/><nd ref="7000000000007000017"/><nd ref="7000000000007000018"/><nd ref="7000000000007000015"/></way><relation id="2" version="1"><tag k="addr:housenumber" v="1234"/><tag k="addr:street" v="yet another street"/><tag k="type" v="multipoint"/><member ref="7000000000002000000" type="node" role="point"/><member ref="7000000000002000001" type="node" role="point"/></relation><relation id="4" version="1"><tag k="highway" v="service"/><tag k="name" v="Don&apos;t drive on me"/><tag k="type" v="multilinestring"/><member ref="8000000000004000000" role="line" type="way"/><member ref="8000000000004000001" role="line" type="way"/></relation><relation id="6" version="1"><tag k="building" v="yes"/><tag k="type" v="multipolygon"/><member ref="8000000000006000000" role="outer" type="way"/><member ref="8000000000006000001" role="inner" type="way"/></relation><relation id="7" version="1"><tag k="amenity" v="hospital"/><tag k="building" v="yes"/><tag k="type" v="multipolygon"/><member ref="8000000000007000000" role="outer" type="way"/><member ref="8000000000007000001" role="inner" type="way"/><member ref="8000000000007000002" role="outer" type="way"/><member ref="8000000000007000003" role="inner" type="way"/></relation></osm>"#);
         }
 
         server.kill().unwrap();
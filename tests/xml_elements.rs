@@ -0,0 +1,197 @@
+extern crate reqwest;
+extern crate postgres;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+    #[test]
+    fn xml_elements() {
+        { // Reset Database:
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create LineString
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a LineString",
+                    "properties": { "highway": "residential" },
+                    "geometry": { "type": "LineString", "coordinates": [[ 0, 0 ], [ 1, 1 ]] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create Point
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a Point",
+                    "properties": { "shop": "bakery" },
+                    "geometry": { "type": "Point", "coordinates": [ 5, 5 ] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Modify LineString
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "type": "Feature",
+                    "version": 1,
+                    "action": "modify",
+                    "message": "Extend a LineString",
+                    "properties": { "highway": "residential" },
+                    "geometry": { "type": "LineString", "coordinates": [[ 0, 0 ], [ 1, 1 ], [ 2, 2 ]] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Node from a Point
+            let mut resp = reqwest::get("http://localhost:8000/api/0.6/node/2").unwrap();
+            assert_eq!(resp.text().unwrap(), String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osm version="0.6" generator="ROSM">"#)
+                + r#"<node id="2" version="1" lon="5" lat="5"><tag k="shop" v="bakery"/></node>"#
+                + r#"</osm>"#);
+            assert!(resp.status().is_success());
+        }
+
+        { //Node from a vertex
+            let mut resp = reqwest::get("http://localhost:8000/api/0.6/node/7000000000001000001").unwrap();
+            assert_eq!(resp.text().unwrap(), String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osm version="0.6" generator="ROSM">"#)
+                + r#"<node id="7000000000001000001" version="2" lat="1" lon="1"/>"#
+                + r#"</osm>"#);
+            assert!(resp.status().is_success());
+        }
+
+        { //Node Not Found
+            let resp = reqwest::get("http://localhost:8000/api/0.6/node/3").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+
+            let resp = reqwest::get("http://localhost:8000/api/0.6/node/1").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+
+            let resp = reqwest::get("http://localhost:8000/api/0.6/node/7000000000001000003").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Multiple Nodes
+            let mut resp = reqwest::get("http://localhost:8000/api/0.6/nodes?nodes=2,7000000000001000000").unwrap();
+            assert_eq!(resp.text().unwrap(), String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osm version="0.6" generator="ROSM">"#)
+                + r#"<node id="2" version="1" lon="5" lat="5"><tag k="shop" v="bakery"/></node>"#
+                + r#"<node id="7000000000001000000" version="2" lat="0" lon="0"/>"#
+                + r#"</osm>"#);
+            assert!(resp.status().is_success());
+
+            let resp = reqwest::get("http://localhost:8000/api/0.6/nodes?nodes=2,3").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+
+            let resp = reqwest::get("http://localhost:8000/api/0.6/nodes?nodes=2,a").unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
+        { //Full Way
+            let mut resp = reqwest::get("http://localhost:8000/api/0.6/way/1/full").unwrap();
+            assert_eq!(resp.text().unwrap(), String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osm version="0.6" generator="ROSM">"#)
+                + r#"<node id="7000000000001000000" version="2" lat="0" lon="0"/>"#
+                + r#"<node id="7000000000001000001" version="2" lat="1" lon="1"/>"#
+                + r#"<node id="7000000000001000002" version="2" lat="2" lon="2"/>"#
+                + r#"<way id="1" version="2"><nd ref="7000000000001000000"/><nd ref="7000000000001000001"/><nd ref="7000000000001000002"/><tag k="highway" v="residential"/></way>"#
+                + r#"</osm>"#);
+            assert!(resp.status().is_success());
+
+            let resp = reqwest::get("http://localhost:8000/api/0.6/way/2/full").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        { //Way History
+            let mut resp = reqwest::get("http://localhost:8000/api/0.6/way/1/history").unwrap();
+            assert_eq!(resp.text().unwrap(), String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osm version="0.6" generator="ROSM">"#)
+                + r#"<way id="1" version="1"><nd ref="7000000000001000000"/><nd ref="7000000000001000001"/><tag k="highway" v="residential"/></way>"#
+                + r#"<way id="1" version="2"><nd ref="7000000000001000000"/><nd ref="7000000000001000001"/><nd ref="7000000000001000002"/><tag k="highway" v="residential"/></way>"#
+                + r#"</osm>"#);
+            assert!(resp.status().is_success());
+        }
+
+        { //Node History
+            let mut resp = reqwest::get("http://localhost:8000/api/0.6/node/7000000000001000002/history").unwrap();
+            assert_eq!(resp.text().unwrap(), String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osm version="0.6" generator="ROSM">"#)
+                + r#"<node id="7000000000001000002" version="2" lat="2" lon="2"/>"#
+                + r#"</osm>"#);
+            assert!(resp.status().is_success());
+        }
+
+        { //History Not Found
+            let resp = reqwest::get("http://localhost:8000/api/0.6/relation/1/history").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+
+            let resp = reqwest::get("http://localhost:8000/api/0.6/way/3/history").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+
+            let resp = reqwest::get("http://localhost:8000/api/0.6/area/1/history").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        server.kill().unwrap();
+    }
+}
@@ -0,0 +1,168 @@
+extern crate reqwest;
+extern crate postgres;
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use postgres::{Connection, TlsMode};
+    use std::process::Command;
+    use std::time::Duration;
+    use std::thread;
+    use reqwest;
+
+    #[test]
+    fn xml_osmchange() {
+        { // Reset Database:
+            let conn = Connection::connect("postgres://postgres@localhost:5432", TlsMode::None).unwrap();
+
+            conn.execute("
+                SELECT pg_terminate_backend(pg_stat_activity.pid)
+                FROM pg_stat_activity
+                WHERE
+                    pg_stat_activity.datname = 'hecate'
+                    AND pid <> pg_backend_pid();
+            ", &[]).unwrap();
+
+            conn.execute("
+                DROP DATABASE IF EXISTS hecate;
+            ", &[]).unwrap();
+
+            conn.execute("
+                CREATE DATABASE hecate;
+            ", &[]).unwrap();
+
+            let conn = Connection::connect("postgres://postgres@localhost:5432/hecate", TlsMode::None).unwrap();
+
+            let mut file = File::open("./src/schema.sql").unwrap();
+            let mut table_sql = String::new();
+            file.read_to_string(&mut table_sql).unwrap();
+            conn.batch_execute(&*table_sql).unwrap();
+        }
+
+        let mut server = Command::new("cargo").args(&[ "run" ]).spawn().unwrap();
+        thread::sleep(Duration::from_secs(1));
+
+        { //Create Username
+            let mut resp = reqwest::get("http://localhost:8000/api/user/create?username=ingalls&password=yeahehyeah&email=ingalls@protonmail.com").unwrap();
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create LineString
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "type": "Feature",
+                    "action": "create",
+                    "message": "Creating a LineString",
+                    "properties": { "highway": "residential" },
+                    "geometry": { "type": "LineString", "coordinates": [[ 0, 0 ], [ 1, 1 ], [ 2, 2 ]] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Modify LineString
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "type": "Feature",
+                    "version": 1,
+                    "action": "modify",
+                    "message": "Shorten a LineString",
+                    "properties": { "highway": "service" },
+                    "geometry": { "type": "LineString", "coordinates": [[ 0, 0 ], [ 1, 1 ]] }
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Delete LineString
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/data/feature")
+                .body(r#"{
+                    "id": 1,
+                    "type": "Feature",
+                    "version": 2,
+                    "action": "delete",
+                    "message": "Delete a LineString",
+                    "properties": null,
+                    "geometry": null
+                }"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .unwrap();
+
+            assert_eq!(resp.text().unwrap(), "true");
+            assert!(resp.status().is_success());
+        }
+
+        { //Create osmChange
+            let mut resp = reqwest::get("http://localhost:8000/api/delta/1.osc").unwrap();
+            assert_eq!(resp.text().unwrap(), String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osmChange version="0.6" generator="Hecate Server">"#)
+                + r#"<create>"#
+                + r#"<node id="7000000000001000000" version="1" lat="0" lon="0"/>"#
+                + r#"<node id="7000000000001000001" version="1" lat="1" lon="1"/>"#
+                + r#"<node id="7000000000001000002" version="1" lat="2" lon="2"/>"#
+                + r#"<way id="1" version="1"><nd ref="7000000000001000000"/><nd ref="7000000000001000001"/><nd ref="7000000000001000002"/><tag k="highway" v="residential"/></way>"#
+                + r#"</create>"#
+                + r#"</osmChange>"#);
+            assert!(resp.status().is_success());
+        }
+
+        { //Modify osmChange
+            let mut resp = reqwest::get("http://localhost:8000/api/0.6/changeset/2/download").unwrap();
+            assert_eq!(resp.text().unwrap(), String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osmChange version="0.6" generator="Hecate Server">"#)
+                + r#"<modify>"#
+                + r#"<way id="1" version="2"><nd ref="7000000000001000000"/><nd ref="7000000000001000001"/><tag k="highway" v="service"/></way>"#
+                + r#"</modify>"#
+                + r#"<delete><node id="7000000000001000002" version="2"/></delete>"#
+                + r#"</osmChange>"#);
+            assert!(resp.status().is_success());
+        }
+
+        { //Delete osmChange
+            let mut resp = reqwest::get("http://localhost:8000/api/delta/3.osc").unwrap();
+            assert_eq!(resp.text().unwrap(), String::from(r#"<?xml version="1.0" encoding="UTF-8"?><osmChange version="0.6" generator="Hecate Server">"#)
+                + r#"<delete>"#
+                + r#"<way id="1" version="3"/>"#
+                + r#"<node id="7000000000001000000" version="3"/>"#
+                + r#"<node id="7000000000001000001" version="3"/>"#
+                + r#"</delete>"#
+                + r#"</osmChange>"#);
+            assert!(resp.status().is_success());
+
+            let mut resp = reqwest::get("http://localhost:8000/api/0.6/changeset/3/download").unwrap();
+            assert!(resp.text().unwrap().contains(r#"<way id="1" version="3"/>"#));
+            assert!(resp.status().is_success());
+        }
+
+        { //Delta JSON is unaffected
+            let resp = reqwest::get("http://localhost:8000/api/delta/3").unwrap();
+            assert!(resp.status().is_success());
+        }
+
+        { //Delta Not Found
+            let resp = reqwest::get("http://localhost:8000/api/delta/4.osc").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+
+            let resp = reqwest::get("http://localhost:8000/api/0.6/changeset/4/download").unwrap();
+            assert_eq!(resp.status().as_u16(), 404);
+        }
+
+        server.kill().unwrap();
+    }
+}
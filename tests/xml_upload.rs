@@ -1,5 +1,6 @@
 extern crate reqwest;
 extern crate postgres;
+#[macro_use] extern crate serde_json;
 
 #[cfg(test)]
 mod test {
@@ -10,6 +11,7 @@ mod test {
     use std::time::Duration;
     use std::thread;
     use reqwest;
+    use serde_json;
 
     #[test]
     fn xml_upload() {
@@ -173,6 +175,260 @@ mod test {
             assert!(resp.status().is_client_error());
         }
 
+        { //XML Changeset Create (Multipolygon Create)
+            let client = reqwest::Client::new();
+            let mut resp = client.put("http://localhost:8000/api/0.6/changeset/create")
+                .body(r#"<osm><changeset><tag k="created_by" v="Hecate Server"/><tag k="comment" v="Buncho Random Text"/></changeset></osm>"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.text().unwrap(), "4");
+            assert!(resp.status().is_success());
+        }
+
+        { //XML Multipolygon Create
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/0.6/changeset/4/upload")
+                .body(r#"
+                    <osmChange version="0.6" generator="Hecate Server">
+                        <create>
+                            <node id='-1' version='1' changeset='4' lat='0' lon='0' />
+                            <node id='-2' version='1' changeset='4' lat='0' lon='4' />
+                            <node id='-3' version='1' changeset='4' lat='4' lon='4' />
+                            <node id='-4' version='1' changeset='4' lat='4' lon='0' />
+                            <node id='-5' version='1' changeset='4' lat='1' lon='1' />
+                            <node id='-6' version='1' changeset='4' lat='1' lon='2' />
+                            <node id='-7' version='1' changeset='4' lat='2' lon='2' />
+                            <way id='-8' version='1' changeset='4'>
+                                <nd ref='-1' />
+                                <nd ref='-2' />
+                                <nd ref='-3' />
+                            </way>
+                            <way id='-9' version='1' changeset='4'>
+                                <nd ref='-3' />
+                                <nd ref='-4' />
+                                <nd ref='-1' />
+                            </way>
+                            <way id='-10' version='1' changeset='4'>
+                                <nd ref='-5' />
+                                <nd ref='-6' />
+                                <nd ref='-7' />
+                                <nd ref='-5' />
+                            </way>
+                            <relation id='-11' version='1' changeset='4'>
+                                <member type='way' ref='-8' role='outer' />
+                                <member type='way' ref='-9' role='outer' />
+                                <member type='way' ref='-10' role='inner' />
+                                <tag k='type' v='multipolygon' />
+                                <tag k='building' v='yes' />
+                            </relation>
+                        </create>
+                    </osmChange>
+                "#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.text().unwrap(), r#"<diffResult generator="Hecate Server" version="0.6"><relation old_id="-11" new_id="2" new_version="1"/></diffResult>"#);
+            assert!(resp.status().is_success());
+        }
+
+        { //XML Multipolygon Verification
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/2").unwrap();
+            assert!(resp.status().is_success());
+
+            let feat: serde_json::Value = resp.json().unwrap();
+            assert_eq!(feat["properties"], json!({ "building": "yes" }));
+            assert_eq!(feat["geometry"], json!({
+                "type": "MultiPolygon",
+                "coordinates": [[
+                    [[0, 0], [4, 0], [4, 4], [0, 4], [0, 0]],
+                    [[1, 1], [2, 1], [2, 2], [1, 1]]
+                ]]
+            }));
+        }
+
+        { //XML Changeset Create (Vertex Move)
+            let client = reqwest::Client::new();
+            let mut resp = client.put("http://localhost:8000/api/0.6/changeset/create")
+                .body(r#"<osm><changeset><tag k="created_by" v="Hecate Server"/><tag k="comment" v="Buncho Random Text"/></changeset></osm>"#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.text().unwrap(), "5");
+            assert!(resp.status().is_success());
+        }
+
+        { //XML Vertex Move - the second vertex of feature 2 as downloaded from /api/0.6/map
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/0.6/changeset/5/upload")
+                .body(r#"
+                    <osmChange version="0.6" generator="Hecate Server">
+                        <modify>
+                            <node id='7000000000002000001' version='1' changeset='5' lat='0' lon='5' />
+                        </modify>
+                    </osmChange>
+                "#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.text().unwrap(), r#"<diffResult generator="Hecate Server" version="0.6"><node old_id="7000000000002000001" new_id="7000000000002000001" new_version="2"/></diffResult>"#);
+            assert!(resp.status().is_success());
+        }
+
+        { //XML Vertex Move Verification
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/2").unwrap();
+            assert!(resp.status().is_success());
+
+            let feat: serde_json::Value = resp.json().unwrap();
+            assert_eq!(feat["version"], json!(2));
+            assert_eq!(feat["geometry"], json!({
+                "type": "MultiPolygon",
+                "coordinates": [[
+                    [[0, 0], [5, 0], [4, 4], [0, 4], [0, 0]],
+                    [[1, 1], [2, 1], [2, 2], [1, 1]]
+                ]]
+            }));
+        }
+
+        { //XML Vertex Move - a stale version of the node is rejected
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/0.6/changeset/5/upload")
+                .body(r#"
+                    <osmChange version="0.6" generator="Hecate Server">
+                        <modify>
+                            <node id='7000000000002000001' version='1' changeset='5' lat='0' lon='6' />
+                        </modify>
+                    </osmChange>
+                "#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.status().as_u16(), 409);
+            let json_body: serde_json::value::Value = resp.json().unwrap();
+            assert_eq!(json_body, json!({
+                "code": 409,
+                "reason": "Modify Version Mismatch",
+                "status": "Conflict"
+            }));
+        }
+
+        { //XML Relation Modify - unchanged member ways are not uploaded
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/0.6/changeset/5/upload")
+                .body(r#"
+                    <osmChange version="0.6" generator="Hecate Server">
+                        <modify>
+                            <relation id='2' version='2' changeset='5'>
+                                <member type='way' ref='8000000000002000000' role='outer' />
+                                <member type='way' ref='8000000000002000001' role='inner' />
+                                <tag k='type' v='multipolygon' />
+                                <tag k='building' v='house' />
+                            </relation>
+                        </modify>
+                    </osmChange>
+                "#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.text().unwrap(), r#"<diffResult generator="Hecate Server" version="0.6"><relation old_id="2" new_id="2" new_version="3"/></diffResult>"#);
+            assert!(resp.status().is_success());
+        }
+
+        { //XML Relation Modify Verification
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/2").unwrap();
+            assert!(resp.status().is_success());
+
+            let feat: serde_json::Value = resp.json().unwrap();
+            assert_eq!(feat["version"], json!(3));
+            assert_eq!(feat["properties"], json!({ "building": "house" }));
+            assert_eq!(feat["geometry"], json!({
+                "type": "MultiPolygon",
+                "coordinates": [[
+                    [[0, 0], [5, 0], [4, 4], [0, 4], [0, 0]],
+                    [[1, 1], [2, 1], [2, 2], [1, 1]]
+                ]]
+            }));
+        }
+
+        { //XML Way Modify - a vertex is added to an inner ring
+            let client = reqwest::Client::new();
+            let mut resp = client.post("http://localhost:8000/api/0.6/changeset/5/upload")
+                .body(r#"
+                    <osmChange version="0.6" generator="Hecate Server">
+                        <create>
+                            <node id='-1' version='0' changeset='5' lat='1.75' lon='1.5' />
+                        </create>
+                        <modify>
+                            <way id='8000000000002000001' version='3' changeset='5'>
+                                <nd ref='7000000000002000005' />
+                                <nd ref='7000000000002000006' />
+                                <nd ref='7000000000002000007' />
+                                <nd ref='-1' />
+                                <nd ref='7000000000002000005' />
+                            </way>
+                        </modify>
+                    </osmChange>
+                "#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.text().unwrap(), r#"<diffResult generator="Hecate Server" version="0.6"><way old_id="8000000000002000001" new_id="8000000000002000001" new_version="4"/></diffResult>"#);
+            assert!(resp.status().is_success());
+        }
+
+        { //XML Way Modify Verification
+            let mut resp = reqwest::get("http://localhost:8000/api/data/feature/2").unwrap();
+            assert!(resp.status().is_success());
+
+            let feat: serde_json::Value = resp.json().unwrap();
+            assert_eq!(feat["version"], json!(4));
+            assert_eq!(feat["properties"], json!({ "building": "house" }));
+            assert_eq!(feat["geometry"], json!({
+                "type": "MultiPolygon",
+                "coordinates": [[
+                    [[0, 0], [5, 0], [4, 4], [0, 4], [0, 0]],
+                    [[1, 1], [2, 1], [2, 2], [1.5, 1.75], [1, 1]]
+                ]]
+            }));
+        }
+
+        { //XML Way Modify - a stale way version is rejected
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/0.6/changeset/5/upload")
+                .body(r#"
+                    <osmChange version="0.6" generator="Hecate Server">
+                        <modify>
+                            <way id='8000000000002000001' version='3' changeset='5'>
+                                <nd ref='7000000000002000005' />
+                                <nd ref='7000000000002000006' />
+                                <nd ref='7000000000002000007' />
+                                <nd ref='7000000000002000005' />
+                            </way>
+                        </modify>
+                    </osmChange>
+                "#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.status().as_u16(), 409);
+        }
+
+        { //XML Node Delete - a vertex can't be deleted without its feature
+            let client = reqwest::Client::new();
+            let resp = client.post("http://localhost:8000/api/0.6/changeset/5/upload")
+                .body(r#"
+                    <osmChange version="0.6" generator="Hecate Server">
+                        <delete>
+                            <node id='7000000000002000008' version='4' changeset='5' />
+                        </delete>
+                    </osmChange>
+                "#)
+                .basic_auth("ingalls", Some("yeahehyeah"))
+                .send()
+                .unwrap();
+            assert_eq!(resp.status().as_u16(), 400);
+        }
+
         server.kill().unwrap();
     }
 }